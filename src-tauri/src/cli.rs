@@ -0,0 +1,446 @@
+use crate::error::FrameFitError;
+use crate::placement::Placement;
+use crate::presets::{self, Preset, STORE_FILE};
+use crate::window_manager::{
+    detect_backend, place_window, resize_window, DisplayTarget, OversizePolicy, ResizeOptions, ResizeOutcome, ScreenInfo, Size,
+    SizeMode, WindowBackend, WindowInfo,
+};
+
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use regex::Regex;
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Matches `identifier` in tauri.conf.json, which names the app data directory.
+const APP_IDENTIFIER: &str = "com.eliostruyf.framefit";
+
+/// Exit codes, so scripts can tell failures apart.
+pub mod exit {
+    pub const OK: i32 = 0;
+    pub const FAILED: i32 = 1;
+    /// Bad arguments or an unknown preset.
+    pub const USAGE: i32 = 2;
+    pub const NO_WINDOW: i32 = 3;
+    pub const PERMISSION_DENIED: i32 = 4;
+    /// Unknown display, or a size that doesn't fit it.
+    pub const DISPLAY: i32 = 5;
+    pub const UNAVAILABLE: i32 = 6;
+    /// The resize went through but the app settled on another size.
+    pub const SIZE_MISMATCH: i32 = 7;
+}
+
+/// Resize windows from scripts, without the UI.
+#[derive(Debug, Parser)]
+#[command(name = "framefit", version)]
+pub struct Cli {
+    /// Print JSON instead of a table.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List the windows that can be resized.
+    List,
+    /// List the displays and their work areas.
+    Screens,
+    /// Resize a window, the frontmost one unless selected otherwise.
+    Resize {
+        /// The new size, e.g. 390x844.
+        size: Size,
+        #[command(flatten)]
+        window: WindowSelector,
+        #[command(flatten)]
+        options: ResizeArgs,
+    },
+    /// Apply a default or custom preset by name.
+    Preset {
+        name: String,
+        #[command(flatten)]
+        window: WindowSelector,
+        #[command(flatten)]
+        options: ResizeArgs,
+    },
+    /// Center a window without changing its size.
+    Center {
+        #[command(flatten)]
+        window: WindowSelector,
+        /// Move the window to this display, by index or name.
+        #[arg(long)]
+        display: Option<DisplayTarget>,
+    },
+}
+
+#[derive(Debug, Args)]
+#[group(multiple = false)]
+struct WindowSelector {
+    /// The window with this id, as shown by `list`.
+    #[arg(long)]
+    id: Option<u32>,
+    /// The frontmost window of this app.
+    #[arg(long)]
+    app: Option<String>,
+    /// The frontmost window whose title matches this regular expression.
+    #[arg(long)]
+    title_regex: Option<Regex>,
+}
+
+#[derive(Debug, Args)]
+struct ResizeArgs {
+    /// Where the window goes, e.g. center, top-left:20 or absolute:0,0.
+    /// Defaults to keeping it in place, or to the preset's placement.
+    #[arg(long)]
+    placement: Option<Placement>,
+    /// Move the window to this display, by index or name.
+    #[arg(long)]
+    display: Option<DisplayTarget>,
+    /// Size the content, e.g. a browser's viewport, instead of the whole window.
+    #[arg(long)]
+    content: bool,
+    /// Fail instead of shrinking sizes that don't fit the display.
+    #[arg(long)]
+    refuse_oversize: bool,
+}
+
+impl ResizeArgs {
+    fn options(&self, default_placement: Placement) -> ResizeOptions {
+        ResizeOptions {
+            placement: self.placement.unwrap_or(default_placement),
+            display: self.display.clone(),
+            oversize: if self.refuse_oversize { OversizePolicy::Refuse } else { OversizePolicy::Clamp },
+            size_mode: if self.content { SizeMode::Content } else { SizeMode::Frame },
+        }
+    }
+}
+
+/// Whether the arguments ask for the CLI rather than the app. Anything else,
+/// e.g. no arguments or a deep link URL, starts the app.
+pub fn is_requested(args: &[String]) -> bool {
+    let Some(first) = args.get(1) else {
+        return false;
+    };
+
+    let command = Cli::command();
+    let mut subcommands = command.get_subcommands();
+    ["--help", "-h", "--version", "-V", "--json", "help"].contains(&first.as_str())
+        || subcommands.any(|subcommand| subcommand.get_name() == first)
+}
+
+/// Runs the CLI and returns the process exit code.
+pub fn run(args: Vec<String>) -> i32 {
+    let cli = match Cli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return if e.use_stderr() { exit::USAGE } else { exit::OK };
+        }
+    };
+
+    let backend = detect_backend();
+    let presets = presets::with_custom_presets(read_custom_presets());
+    let mut stdout = std::io::stdout().lock();
+
+    match execute(&cli, &*backend, &presets, &mut stdout) {
+        Ok(code) => code,
+        Err(Failure::Usage(message)) => {
+            eprintln!("error: {}", message);
+            exit::USAGE
+        }
+        Err(Failure::FrameFit(e)) => {
+            if cli.json {
+                eprintln!("{}", serde_json::to_string(&e).unwrap());
+            } else {
+                eprintln!("error: {}", e);
+            }
+            exit_code(&e)
+        }
+    }
+}
+
+/// Where the app's store plugin keeps `presets.json`.
+fn store_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER).join(STORE_FILE))
+}
+
+fn read_custom_presets() -> Option<serde_json::Value> {
+    let path = store_path()?;
+    let contents = std::fs::read_to_string(&path).ok()?;
+
+    match serde_json::from_str::<serde_json::Value>(&contents) {
+        Ok(mut store) => store.get_mut(presets::STORE_KEY).map(serde_json::Value::take),
+        Err(e) => {
+            eprintln!("Ignoring {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Failure {
+    Usage(String),
+    FrameFit(FrameFitError),
+}
+
+impl From<FrameFitError> for Failure {
+    fn from(error: FrameFitError) -> Self {
+        Self::FrameFit(error)
+    }
+}
+
+fn exit_code(error: &FrameFitError) -> i32 {
+    match error {
+        FrameFitError::WindowNotFound { .. }
+        | FrameFitError::NoMatchingWindow { .. }
+        | FrameFitError::NoWindows
+        | FrameFitError::SelfResizeRefused => exit::NO_WINDOW,
+        FrameFitError::PermissionDenied => exit::PERMISSION_DENIED,
+        FrameFitError::DisplayNotFound { .. } | FrameFitError::ExceedsWorkArea { .. } => exit::DISPLAY,
+        FrameFitError::BackendUnavailable { .. } | FrameFitError::Unsupported => exit::UNAVAILABLE,
+        FrameFitError::ScriptFailed { .. } | FrameFitError::BackendFailed { .. } => exit::FAILED,
+        FrameFitError::InvalidPreset { .. } | FrameFitError::PresetNotFound { .. } => exit::USAGE,
+    }
+}
+
+fn execute(cli: &Cli, backend: &dyn WindowBackend, presets: &[Preset], out: &mut impl Write) -> Result<i32, Failure> {
+    let outcome = match &cli.command {
+        Command::List => {
+            let windows = backend.list_windows()?;
+            print(out, cli.json, windows.as_slice(), window_table)?;
+            return Ok(exit::OK);
+        }
+        Command::Screens => {
+            let screens = backend.list_screens()?;
+            print(out, cli.json, screens.as_slice(), screen_table)?;
+            return Ok(exit::OK);
+        }
+        Command::Resize { size, window, options } => {
+            let window = window.select(backend)?;
+            resize_window(backend, &window, size.width, size.height, &options.options(Placement::KEEP))?
+        }
+        Command::Preset { name, window, options } => {
+            let preset = presets::find(presets, name).ok_or_else(|| {
+                let names: Vec<&str> = presets.iter().map(|preset| preset.name.as_str()).collect();
+                Failure::Usage(format!("Unknown preset '{}', expected one of: {}", name, names.join(", ")))
+            })?;
+            let window = window.select(backend)?;
+            let options = options.options(preset.placement.unwrap_or(Placement::CENTER));
+            resize_window(backend, &window, preset.width, preset.height, &options)?
+        }
+        Command::Center { window, display } => {
+            let window = window.select(backend)?;
+            let options = ResizeOptions { placement: Placement::CENTER, display: display.clone(), ..Default::default() };
+            place_window(backend, &window, &options)?
+        }
+    };
+
+    print(out, cli.json, &outcome, outcome_table)?;
+    Ok(if outcome.mismatch.is_some() { exit::SIZE_MISMATCH } else { exit::OK })
+}
+
+impl WindowSelector {
+    /// The selected window; windows are listed front to back, so the first
+    /// match is the frontmost one.
+    fn select(&self, backend: &dyn WindowBackend) -> Result<WindowInfo, FrameFitError> {
+        if let Some(id) = self.id {
+            return find_window(backend, |window| window.id == id).ok_or(FrameFitError::WindowNotFound { id });
+        }
+        if let Some(app) = &self.app {
+            return find_window(backend, |window| window.app_name.eq_ignore_ascii_case(app))
+                .ok_or_else(|| FrameFitError::NoMatchingWindow { query: format!("app '{}'", app) });
+        }
+        if let Some(pattern) = &self.title_regex {
+            return find_window(backend, |window| pattern.is_match(&window.title))
+                .ok_or_else(|| FrameFitError::NoMatchingWindow { query: format!("title /{}/", pattern) });
+        }
+
+        backend.get_frontmost_window()
+    }
+}
+
+fn find_window(backend: &dyn WindowBackend, matches: impl Fn(&WindowInfo) -> bool) -> Option<WindowInfo> {
+    // A failed listing surfaces again, with its real error, when resizing
+    backend.list_windows().ok()?.into_iter().find(|window| matches(window))
+}
+
+fn print<T: Serialize + ?Sized>(
+    out: &mut impl Write,
+    json: bool,
+    value: &T,
+    table: impl Fn(&T) -> Vec<Vec<String>>,
+) -> Result<(), FrameFitError> {
+    let text = if json {
+        serde_json::to_string_pretty(value).unwrap()
+    } else {
+        format_table(&table(value))
+    };
+
+    writeln!(out, "{}", text).map_err(|e| FrameFitError::backend(format!("Failed to write output: {}", e)))
+}
+
+/// Left-aligned columns; the first row is the header.
+fn format_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| rows.iter().filter_map(|row| row.get(column)).map(|cell| cell.chars().count()).max().unwrap_or(0))
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            cells.join("  ").trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn window_table(windows: &[WindowInfo]) -> Vec<Vec<String>> {
+    let header = ["ID", "APP", "X", "Y", "SIZE", "TITLE"].map(String::from).to_vec();
+    let rows = windows.iter().map(|window| {
+        vec![
+            window.id.to_string(),
+            window.app_name.clone(),
+            window.x.to_string(),
+            window.y.to_string(),
+            format!("{}x{}", window.width, window.height),
+            window.title.clone(),
+        ]
+    });
+
+    std::iter::once(header).chain(rows).collect()
+}
+
+fn screen_table(screens: &[ScreenInfo]) -> Vec<Vec<String>> {
+    let header = ["ID", "NAME", "FRAME", "WORK AREA", "SCALE", "MAIN"].map(String::from).to_vec();
+    let rect = |r: &crate::window_manager::Rect| format!("{}x{}+{}+{}", r.width, r.height, r.x, r.y);
+    let rows = screens.iter().map(|screen| {
+        vec![
+            screen.id.to_string(),
+            screen.name.clone(),
+            rect(&screen.frame),
+            rect(&screen.work_area),
+            screen.scale_factor.to_string(),
+            if screen.is_main { "yes" } else { "" }.to_string(),
+        ]
+    });
+
+    std::iter::once(header).chain(rows).collect()
+}
+
+fn outcome_table(outcome: &ResizeOutcome) -> Vec<Vec<String>> {
+    let size = |size: Size| format!("{}x{}", size.width, size.height);
+    let mut rows = vec![
+        vec!["Window".to_string(), outcome.window_id.to_string()],
+        vec!["Requested".to_string(), size(outcome.requested)],
+        vec!["Achieved".to_string(), size(outcome.achieved)],
+    ];
+
+    if let Some(mismatch) = outcome.mismatch {
+        // The same kebab-case names as in the JSON output
+        let reason = serde_json::to_value(mismatch).unwrap();
+        rows.push(vec!["Mismatch".to_string(), reason.as_str().unwrap_or_default().to_string()]);
+    }
+    for warning in &outcome.warnings {
+        rows.push(vec!["Warning".to_string(), warning.clone()]);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::fake::{FakeWindow, FakeWindowServer};
+    use crate::window_manager::Rect;
+
+    fn desktop() -> FakeWindowServer {
+        FakeWindowServer::new()
+            .with_screen("Studio Display", 0, 0, 2560, 1440)
+            .with_window(FakeWindow::new(1, "Safari", "Apple", 100, 100, 1024, 768))
+            .with_window(FakeWindow::new(2, "Terminal", "~ — zsh", 200, 200, 720, 480).min_size(400, 300))
+            .with_window(FakeWindow::new(3, "Safari", "GitHub", 300, 300, 800, 600))
+    }
+
+    fn run_with(server: &FakeWindowServer, args: &[&str]) -> (Result<i32, Failure>, String) {
+        let cli = Cli::try_parse_from(std::iter::once("framefit").chain(args.iter().copied())).unwrap();
+        let mut out = Vec::new();
+        let result = execute(&cli, server, &presets::default_presets(), &mut out);
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn detects_cli_invocations() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        assert!(is_requested(&args(&["FrameFit", "list"])));
+        assert!(is_requested(&args(&["FrameFit", "--json", "screens"])));
+        assert!(!is_requested(&args(&["FrameFit"])));
+        assert!(!is_requested(&args(&["FrameFit", "framefit://preset/iPad"])));
+    }
+
+    #[test]
+    fn lists_windows_as_a_table() {
+        let (result, output) = run_with(&desktop(), &["list"]);
+
+        assert_eq!(result.unwrap(), exit::OK);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "ID  APP       X    Y    SIZE      TITLE");
+        assert_eq!(lines[2], "2   Terminal  200  200  720x480   ~ — zsh");
+    }
+
+    #[test]
+    fn selects_windows_by_app_and_title() {
+        let server = desktop();
+
+        run_with(&server, &["resize", "390x844", "--title-regex", "^Git"]).0.unwrap();
+        assert_eq!(server.window(3).unwrap().width, 390);
+
+        let (result, _) = run_with(&server, &["resize", "390x844", "--app", "Finder"]);
+        let Err(Failure::FrameFit(error)) = result else { panic!("expected an error") };
+        assert_eq!(exit_code(&error), exit::NO_WINDOW);
+    }
+
+    #[test]
+    fn centers_without_resizing() {
+        let server = FakeWindowServer::new()
+            .with_screen("Built-in", 0, 0, 1440, 900)
+            .with_window(FakeWindow::new(1, "Safari", "Apple", 100, 100, 2000, 1200));
+
+        let (result, _) = run_with(&server, &["center", "--id", "1"]);
+
+        assert_eq!(result.unwrap(), exit::OK);
+        assert_eq!(server.window(1).unwrap().frame(), Rect::new(0, 0, 2000, 1200));
+    }
+
+    #[test]
+    fn applies_presets_by_name() {
+        let server = desktop();
+
+        let (result, output) = run_with(&server, &["--json", "preset", "ipad", "--id", "1"]);
+
+        assert_eq!(result.unwrap(), exit::OK);
+        let outcome: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(outcome["achieved"], serde_json::json!({ "width": 768, "height": 1024 }));
+        assert!(matches!(run_with(&server, &["preset", "Watch"]).0, Err(Failure::Usage(_))));
+    }
+
+    #[test]
+    fn reports_size_mismatches_in_the_exit_code() {
+        let (result, output) = run_with(&desktop(), &["resize", "300x200", "--id", "2"]);
+
+        assert_eq!(result.unwrap(), exit::SIZE_MISMATCH);
+        assert!(output.contains("Mismatch   min-size"));
+    }
+
+    #[test]
+    fn rejects_conflicting_selectors() {
+        assert!(Cli::try_parse_from(["framefit", "center", "--id", "1", "--app", "Safari"]).is_err());
+        assert!(Cli::try_parse_from(["framefit", "resize", "wide"]).is_err());
+    }
+}
@@ -0,0 +1,519 @@
+//! A JSON-RPC 2.0 server on a Unix socket, so scripts and launchers can drive
+//! the running app without starting a second process.
+//!
+//! Requests and responses are single lines of JSON. Every request may carry
+//! the protocol `version` it was written against; responses always carry the
+//! server's. Methods mirror the Tauri commands, e.g.
+//! `{"jsonrpc":"2.0","id":1,"method":"apply_preset","params":{"name":"iPad"}}`.
+
+mod client;
+mod server;
+
+pub use client::{Client, ClientError};
+pub use server::ControlServer;
+
+use crate::error::FrameFitError;
+use crate::placement::Placement;
+use crate::presets::{self, Preset};
+use crate::window_manager::{
+    resize_frontmost, resize_window_by_id, DisplayTarget, OversizePolicy, ResizeOptions,
+    ResizeOutcome, ResizeRequest, SizeMode, WindowBackend,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Bumped on incompatible changes to the methods or their results. Requests
+/// for an older version are still served as long as it's listed here.
+pub const PROTOCOL_VERSION: u32 = 1;
+const SUPPORTED_VERSIONS: [u32; 1] = [1];
+
+/// Overrides the socket path, e.g. to run a second instance in development.
+pub const SOCKET_ENV: &str = "FRAMEFIT_SOCKET";
+
+const SOCKET_NAME: &str = "framefit.sock";
+
+/// Where the running app listens: the user's runtime directory on Linux, or
+/// a directory of the user's own in the temporary directory, e.g. on macOS.
+pub fn socket_path() -> io::Result<PathBuf> {
+    if let Some(path) = std::env::var_os(SOCKET_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+    Ok(user_dir()?.join(SOCKET_NAME))
+}
+
+/// A directory only the current user can write to: the runtime directory,
+/// or `framefit-<uid>` in the temporary directory, which may be shared with
+/// other users, so it's created for the user alone and checked before use.
+pub(crate) fn user_dir() -> io::Result<PathBuf> {
+    if let Some(dir) = dirs::runtime_dir() {
+        return Ok(dir);
+    }
+
+    // SAFETY: getuid can't fail and has no preconditions
+    let uid = unsafe { libc::getuid() };
+    let dir = std::env::temp_dir().join(format!("framefit-{}", uid));
+    private_dir(&dir, uid)?;
+    Ok(dir)
+}
+
+/// Creates `dir` readable by its owner only, or checks that it already is
+/// and belongs to `uid`, so nobody else can put a socket in it.
+fn private_dir(dir: &Path, uid: u32) -> io::Result<()> {
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e),
+    }
+
+    // Not following links, which could point anywhere
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.permissions().mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is not a private directory of this user", dir.display()),
+        ));
+    }
+    Ok(())
+}
+
+/// What the server needs from the app, so it can be tested without Tauri.
+pub trait Context: Send + Sync + 'static {
+    fn backend(&self) -> &dyn WindowBackend;
+
+    /// The default and custom presets, as shown in the UI.
+    fn presets(&self) -> Vec<Preset>;
+
+    /// Called after every successful resize, e.g. to make it undoable.
+    fn resized(&self, _outcome: &ResizeOutcome) {}
+}
+
+/// Resize options as sent over the socket; everything but the size is optional.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResizeParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<Placement>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<DisplayTarget>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oversize: Option<OversizePolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_mode: Option<SizeMode>,
+}
+
+impl ResizeParams {
+    fn options(&self, default_placement: Placement) -> ResizeOptions {
+        ResizeOptions {
+            placement: self.placement.unwrap_or(default_placement),
+            display: self.display.clone(),
+            oversize: self.oversize.unwrap_or_default(),
+            size_mode: self.size_mode.unwrap_or_default(),
+        }
+    }
+}
+
+/// The methods of protocol version 1, named like the Tauri commands.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum Method {
+    /// Returns a [`VersionInfo`].
+    Version,
+    CheckPermissions,
+    GetWindows,
+    GetScreens,
+    GetPresets,
+    ResizeFrontmostWindow {
+        width: i32,
+        height: i32,
+        #[serde(flatten)]
+        options: ResizeParams,
+    },
+    ResizeSpecificWindow {
+        window_id: u32,
+        width: i32,
+        height: i32,
+        #[serde(flatten)]
+        options: ResizeParams,
+    },
+    /// Applies a preset to a window, or to the frontmost one. The preset is
+    /// picked by `id`, which survives renames, or else by `name`.
+    ApplyPreset {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<Uuid>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        window_id: Option<u32>,
+        #[serde(flatten)]
+        options: ResizeParams,
+    },
+}
+
+const METHODS: [&str; 8] = [
+    "version",
+    "check_permissions",
+    "get_windows",
+    "get_screens",
+    "get_presets",
+    "resize_frontmost_window",
+    "resize_specific_window",
+    "apply_preset",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub protocol: u32,
+    pub supported: Vec<u32>,
+    pub app: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    /// Absent for notifications, which get no response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    #[serde(default = "current_version")]
+    pub version: u32,
+    #[serde(flatten)]
+    pub method: Method,
+}
+
+fn current_version() -> u32 {
+    PROTOCOL_VERSION
+}
+
+impl Request {
+    pub fn new(id: u64, method: Method) -> Self {
+        Self { jsonrpc: "2.0".to_string(), id: Some(id.into()), version: PROTOCOL_VERSION, method }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    pub version: u32,
+    pub id: Value,
+    #[serde(flatten)]
+    pub reply: Reply,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Reply {
+    Result(Value),
+    Error(RpcError),
+}
+
+impl Response {
+    fn new(id: Value, reply: Reply) -> Self {
+        Self { jsonrpc: "2.0".to_string(), version: PROTOCOL_VERSION, id, reply }
+    }
+}
+
+/// A JSON-RPC error. Failed window operations use [`RpcError::APP_ERROR`]
+/// with the serialized [`FrameFitError`] as `data`, so its `code` string is
+/// the same one the UI sees.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i32,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub const PARSE_ERROR: i32 = -32700;
+    pub const INVALID_REQUEST: i32 = -32600;
+    pub const METHOD_NOT_FOUND: i32 = -32601;
+    pub const INVALID_PARAMS: i32 = -32602;
+    pub const APP_ERROR: i32 = -32000;
+    pub const UNKNOWN_PRESET: i32 = -32001;
+    pub const UNSUPPORTED_VERSION: i32 = -32002;
+
+    fn new(code: i32, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), data: None }
+    }
+
+    /// The `code` of the [`FrameFitError`] behind an [`RpcError::APP_ERROR`].
+    pub fn app_code(&self) -> Option<&str> {
+        self.data.as_ref()?.get("code")?.as_str()
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+impl From<FrameFitError> for RpcError {
+    fn from(error: FrameFitError) -> Self {
+        Self { code: Self::APP_ERROR, message: error.to_string(), data: Some(serde_json::to_value(&error).unwrap()) }
+    }
+}
+
+/// Handles one line of input, returning the line to answer with, if any.
+pub fn handle_line(context: &dyn Context, line: &str) -> Option<String> {
+    let response = match parse_request(line) {
+        Ok((id, method)) => {
+            let reply = match call(context, method) {
+                Ok(result) => Reply::Result(result),
+                Err(e) => Reply::Error(e),
+            };
+            Response::new(id?, reply)
+        }
+        Err((id, error)) => Response::new(id, Reply::Error(error)),
+    };
+
+    Some(serde_json::to_string(&response).unwrap())
+}
+
+/// The request's id, or `None` for notifications, and its method. Errors
+/// carry the id when it could be read, or `null`.
+fn parse_request(line: &str) -> Result<(Option<Value>, Method), (Value, RpcError)> {
+    let value: Value = serde_json::from_str(line)
+        .map_err(|e| (Value::Null, RpcError::new(RpcError::PARSE_ERROR, format!("Parse error: {}", e))))?;
+    let id = value.get("id").cloned();
+    let fail = |code, message: String| (id.clone().unwrap_or(Value::Null), RpcError::new(code, message));
+
+    if value.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Err(fail(RpcError::INVALID_REQUEST, "Invalid request: expected jsonrpc 2.0".to_string()));
+    }
+
+    let version = value.get("version").map_or(Some(PROTOCOL_VERSION as u64), Value::as_u64);
+    if !version.is_some_and(|version| SUPPORTED_VERSIONS.iter().any(|v| u64::from(*v) == version)) {
+        let (id, mut error) =
+            fail(RpcError::UNSUPPORTED_VERSION, format!("Unsupported protocol version {}", value["version"]));
+        error.data = Some(serde_json::json!({ "supported": SUPPORTED_VERSIONS }));
+        return Err((id, error));
+    }
+
+    let Some(name) = value.get("method").and_then(Value::as_str) else {
+        return Err(fail(RpcError::INVALID_REQUEST, "Invalid request: missing method".to_string()));
+    };
+    if !METHODS.contains(&name) {
+        return Err(fail(RpcError::METHOD_NOT_FOUND, format!("Method not found: {}", name)));
+    }
+
+    // Clients often send empty params to methods without any
+    let mut call = serde_json::json!({ "method": name });
+    match value.get("params") {
+        None | Some(Value::Null) => {}
+        Some(Value::Array(params)) if params.is_empty() => {}
+        Some(Value::Object(params)) if params.is_empty() && !takes_params(name) => {}
+        Some(params) => call["params"] = params.clone(),
+    }
+
+    let method = serde_json::from_value(call)
+        .map_err(|e| fail(RpcError::INVALID_PARAMS, format!("Invalid params: {}", e)))?;
+    Ok((id, method))
+}
+
+fn takes_params(method: &str) -> bool {
+    !matches!(method, "version" | "check_permissions" | "get_windows" | "get_screens" | "get_presets")
+}
+
+/// Runs `method`, like the Tauri command of the same name would.
+pub fn call(context: &dyn Context, method: Method) -> Result<Value, RpcError> {
+    let backend = context.backend();
+
+    let outcome = match method {
+        Method::Version => {
+            return Ok(to_value(VersionInfo {
+                protocol: PROTOCOL_VERSION,
+                supported: SUPPORTED_VERSIONS.to_vec(),
+                app: env!("CARGO_PKG_VERSION").to_string(),
+            }))
+        }
+        Method::CheckPermissions => return Ok(to_value(backend.check_permissions())),
+        Method::GetWindows => return Ok(to_value(backend.list_windows()?)),
+        Method::GetScreens => return Ok(to_value(backend.list_screens()?)),
+        Method::GetPresets => return Ok(to_value(context.presets())),
+        Method::ResizeFrontmostWindow { width, height, options } => {
+            resize_frontmost(backend, width, height, &options.options(Placement::KEEP))?
+        }
+        Method::ResizeSpecificWindow { window_id, width, height, options } => {
+            let request = ResizeRequest { window_id, width, height };
+            resize_window_by_id(backend, &request, &options.options(Placement::KEEP))?
+        }
+        Method::ApplyPreset { id, name, window_id, options } => {
+            let presets = context.presets();
+            let (found, wanted) = match (id, &name) {
+                (Some(id), _) => (presets::find_by_id(&presets, id), id.to_string()),
+                (None, Some(name)) => (presets::find(&presets, name), name.clone()),
+                (None, None) => return Err(RpcError::new(RpcError::INVALID_PARAMS, "Missing preset id or name")),
+            };
+            let preset = found.ok_or_else(|| RpcError {
+                code: RpcError::UNKNOWN_PRESET,
+                message: format!("Unknown preset '{}'", wanted),
+                data: Some(serde_json::json!({
+                    "presets": presets
+                        .iter()
+                        .map(|p| serde_json::json!({ "id": p.id, "name": p.name }))
+                        .collect::<Vec<_>>()
+                })),
+            })?;
+            let options = options.options(preset.placement.unwrap_or(Placement::CENTER));
+
+            match window_id {
+                Some(window_id) => {
+                    let request = ResizeRequest { window_id, width: preset.width, height: preset.height };
+                    resize_window_by_id(backend, &request, &options)?
+                }
+                None => resize_frontmost(backend, preset.width, preset.height, &options)?,
+            }
+        }
+    };
+
+    context.resized(&outcome);
+    Ok(to_value(outcome))
+}
+
+fn to_value(value: impl Serialize) -> Value {
+    serde_json::to_value(value).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::fake::{FakeWindow, FakeWindowServer};
+
+    struct FakeContext(FakeWindowServer);
+
+    impl Context for FakeContext {
+        fn backend(&self) -> &dyn WindowBackend {
+            &self.0
+        }
+
+        fn presets(&self) -> Vec<Preset> {
+            presets::default_presets()
+        }
+    }
+
+    fn context() -> FakeContext {
+        FakeContext(
+            FakeWindowServer::new()
+                .with_screen("Studio Display", 0, 0, 2560, 1440)
+                .with_window(FakeWindow::new(7, "Safari", "Apple", 100, 100, 1024, 768)),
+        )
+    }
+
+    fn respond(context: &FakeContext, line: &str) -> Value {
+        serde_json::from_str(&handle_line(context, line).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn accepts_empty_params_for_methods_without_any() {
+        let context = context();
+
+        let response = respond(&context, r#"{"jsonrpc":"2.0","id":1,"method":"get_windows","params":[]}"#);
+
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["version"], PROTOCOL_VERSION);
+        assert_eq!(response["result"][0]["id"], 7);
+    }
+
+    #[test]
+    fn reports_protocol_errors_with_their_codes() {
+        let context = context();
+        let error = |line| respond(&context, line)["error"]["code"].clone();
+
+        assert_eq!(error("{"), RpcError::PARSE_ERROR);
+        assert_eq!(error(r#"{"id":1,"method":"get_windows"}"#), RpcError::INVALID_REQUEST);
+        assert_eq!(error(r#"{"jsonrpc":"2.0","id":1,"method":"close_window"}"#), RpcError::METHOD_NOT_FOUND);
+        assert_eq!(error(r#"{"jsonrpc":"2.0","id":1,"method":"apply_preset","params":{}}"#), RpcError::INVALID_PARAMS);
+        assert_eq!(
+            error(r#"{"jsonrpc":"2.0","id":1,"version":2,"method":"get_windows"}"#),
+            RpcError::UNSUPPORTED_VERSION
+        );
+    }
+
+    #[test]
+    fn window_errors_carry_the_app_error() {
+        let context = context();
+
+        let line = r#"{"jsonrpc":"2.0","id":"a","method":"resize_specific_window","params":{"window_id":9,"width":390,"height":844}}"#;
+        let response: Response = serde_json::from_value(respond(&context, line)).unwrap();
+
+        let Reply::Error(error) = response.reply else { panic!("expected an error") };
+        assert_eq!(error.code, RpcError::APP_ERROR);
+        assert_eq!(error.app_code(), Some("WINDOW_NOT_FOUND"));
+    }
+
+    #[test]
+    fn notifications_get_no_response() {
+        let context = context();
+
+        let line = r#"{"jsonrpc":"2.0","method":"apply_preset","params":{"name":"ipad"}}"#;
+
+        assert_eq!(handle_line(&context, line), None);
+        assert_eq!(context.0.window(7).unwrap().width, 768);
+    }
+
+    #[test]
+    fn applies_presets_by_id_before_name() {
+        let context = context();
+        let ipad = presets::find(&context.presets(), "iPad").unwrap().id;
+        let apply = |params: Value| {
+            let line = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "apply_preset", "params": params });
+            respond(&context, &line.to_string())
+        };
+
+        apply(serde_json::json!({ "id": ipad, "name": "HD" }));
+        assert_eq!(context.0.window(7).unwrap().width, 768);
+
+        let unknown = apply(serde_json::json!({ "id": Uuid::nil() }));
+        assert_eq!(unknown["error"]["code"], RpcError::UNKNOWN_PRESET);
+        assert!(unknown["error"]["data"]["presets"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!({ "id": ipad, "name": "iPad" })));
+    }
+
+    #[test]
+    fn the_socket_directory_is_private() {
+        let dir = std::env::temp_dir().join(format!("framefit-test-{}", std::process::id()));
+        let uid = unsafe { libc::getuid() };
+        let _ = std::fs::remove_dir(&dir);
+
+        private_dir(&dir, uid).unwrap();
+        assert_eq!(std::fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+        private_dir(&dir, uid).unwrap();
+
+        // E.g. made by someone else before the app started
+        assert!(private_dir(&dir, uid + 1).is_err());
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(private_dir(&dir, uid).is_err());
+
+        std::fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn requests_round_trip() {
+        let request = Request::new(
+            3,
+            Method::ApplyPreset {
+                id: None,
+                name: Some("iPad".to_string()),
+                window_id: Some(7),
+                options: ResizeParams::default(),
+            },
+        );
+
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "jsonrpc": "2.0", "id": 3, "version": 1,
+                "method": "apply_preset", "params": { "name": "iPad", "window_id": 7 }
+            })
+        );
+        assert_eq!(serde_json::from_value::<Request>(json).unwrap(), request);
+    }
+}
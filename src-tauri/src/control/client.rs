@@ -0,0 +1,134 @@
+use super::{socket_path, Method, Reply, Request, ResizeParams, Response, RpcError, VersionInfo};
+use crate::presets::Preset;
+use crate::window_manager::{ResizeOutcome, ScreenInfo, WindowInfo};
+
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The app isn't running, or the connection broke.
+    Io(io::Error),
+    /// The app answered with something that isn't a valid response.
+    Protocol(String),
+    /// The app answered with an error.
+    Rpc(RpcError),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "FrameFit control socket: {}", e),
+            Self::Protocol(message) => write!(f, "Invalid response from FrameFit: {}", message),
+            Self::Rpc(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<io::Error> for ClientError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// A connection to a running FrameFit.
+pub struct Client {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+    next_id: u64,
+}
+
+impl Client {
+    /// Connects to the app at the default [`socket_path`].
+    pub fn connect_default() -> Result<Self, ClientError> {
+        Self::connect(&socket_path()?)
+    }
+
+    pub fn connect(path: &Path) -> Result<Self, ClientError> {
+        let writer = UnixStream::connect(path)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Self { reader, writer, next_id: 1 })
+    }
+
+    /// Sends `method` and waits for its result.
+    pub fn call<T: DeserializeOwned>(&mut self, method: Method) -> Result<T, ClientError> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let request = serde_json::to_string(&Request::new(id, method)).unwrap();
+        writeln!(self.writer, "{}", request)?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(ClientError::Protocol("connection closed".to_string()));
+        }
+
+        let response: Response =
+            serde_json::from_str(&line).map_err(|e| ClientError::Protocol(e.to_string()))?;
+        if response.id != id {
+            return Err(ClientError::Protocol(format!("expected id {}, got {}", id, response.id)));
+        }
+
+        match response.reply {
+            Reply::Result(result) => serde_json::from_value(result).map_err(|e| ClientError::Protocol(e.to_string())),
+            Reply::Error(error) => Err(ClientError::Rpc(error)),
+        }
+    }
+
+    pub fn version(&mut self) -> Result<VersionInfo, ClientError> {
+        self.call(Method::Version)
+    }
+
+    pub fn windows(&mut self) -> Result<Vec<WindowInfo>, ClientError> {
+        self.call(Method::GetWindows)
+    }
+
+    pub fn screens(&mut self) -> Result<Vec<ScreenInfo>, ClientError> {
+        self.call(Method::GetScreens)
+    }
+
+    pub fn presets(&mut self) -> Result<Vec<Preset>, ClientError> {
+        self.call(Method::GetPresets)
+    }
+
+    /// Resizes the window with `window_id`, or the frontmost one.
+    pub fn resize(
+        &mut self,
+        window_id: Option<u32>,
+        width: i32,
+        height: i32,
+        options: ResizeParams,
+    ) -> Result<ResizeOutcome, ClientError> {
+        match window_id {
+            Some(window_id) => self.call(Method::ResizeSpecificWindow { window_id, width, height, options }),
+            None => self.call(Method::ResizeFrontmostWindow { width, height, options }),
+        }
+    }
+
+    /// Applies the preset called `name` to the window with `window_id`, or
+    /// the frontmost one.
+    pub fn apply_preset(
+        &mut self,
+        name: &str,
+        window_id: Option<u32>,
+        options: ResizeParams,
+    ) -> Result<ResizeOutcome, ClientError> {
+        self.call(Method::ApplyPreset { id: None, name: Some(name.to_string()), window_id, options })
+    }
+
+    /// Applies the preset with `id`, which unlike its name survives renames.
+    pub fn apply_preset_by_id(
+        &mut self,
+        id: Uuid,
+        window_id: Option<u32>,
+        options: ResizeParams,
+    ) -> Result<ResizeOutcome, ClientError> {
+        self.call(Method::ApplyPreset { id: Some(id), name: None, window_id, options })
+    }
+}
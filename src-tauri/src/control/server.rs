@@ -0,0 +1,99 @@
+use super::{handle_line, Context};
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// The listening socket. Dropping it stops accepting connections and
+/// removes the socket file.
+pub struct ControlServer {
+    path: PathBuf,
+    stopped: Arc<AtomicBool>,
+}
+
+impl ControlServer {
+    /// Listens on `path`, serving every connection on its own thread.
+    ///
+    /// Fails with `AddrInUse` when another instance is already listening
+    /// there; a socket file left behind by a crashed instance is replaced.
+    pub fn bind(path: &Path, context: Arc<dyn Context>) -> io::Result<Self> {
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is used by another FrameFit", path.display()),
+            ));
+        }
+        let _ = std::fs::remove_file(path);
+
+        // Anyone who can connect can move the user's windows, so the socket
+        // is created for the user alone instead of being tightened after
+        // SAFETY: umask can't fail, and the previous mask is restored at once
+        let previous_mask = unsafe { libc::umask(0o177) };
+        let listener = UnixListener::bind(path);
+        unsafe { libc::umask(previous_mask) };
+        let listener = listener?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&stopped);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                match stream {
+                    Ok(stream) => {
+                        let context = Arc::clone(&context);
+                        std::thread::spawn(move || serve(stream, &*context));
+                    }
+                    Err(e) => eprintln!("Control socket error: {}", e),
+                }
+            }
+        });
+
+        Ok(Self { path: path.to_path_buf(), stopped })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wakes the accept loop up so it sees the flag
+        let _ = UnixStream::connect(&self.path);
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Answers requests line by line until the client hangs up.
+fn serve(stream: UnixStream, context: &dyn Context) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => {
+            eprintln!("Control socket error: {}", e);
+            return;
+        }
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(response) = handle_line(context, &line) {
+            if writeln!(writer, "{}", response).is_err() {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Parses `framefit://` links, e.g. from QA runbooks:
+//! `framefit://resize?w=1280&h=720&center=1` or `framefit://preset/iPhone%2014`.
+
+use crate::placement::Placement;
+use crate::presets::{MAX_DIMENSION, MIN_DIMENSION};
+use std::fmt;
+
+pub const SCHEME: &str = "framefit";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLink {
+    /// `framefit://resize?w=W&h=H`, optionally with `center=1` or a
+    /// `placement` like `top-right:20`.
+    Resize { width: i32, height: i32, placement: Placement },
+    /// `framefit://preset/NAME`, with the name percent-encoded.
+    Preset { name: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLinkError {
+    NotFrameFit,
+    UnknownAction(String),
+    MissingParameter(&'static str),
+    UnknownParameter(String),
+    DuplicateParameter(String),
+    ConflictingParameters(&'static str, &'static str),
+    InvalidValue { name: String, value: String },
+    OutOfRange { name: &'static str, value: i32 },
+    /// A `%` escape that isn't two hex digits or doesn't decode to UTF-8.
+    InvalidEncoding,
+    MissingPresetName,
+}
+
+impl fmt::Display for DeepLinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFrameFit => write!(f, "Not a {}:// link", SCHEME),
+            Self::UnknownAction(action) => write!(f, "Unknown link action '{}', expected resize or preset", action),
+            Self::MissingParameter(name) => write!(f, "Missing parameter '{}'", name),
+            Self::UnknownParameter(name) => write!(f, "Unknown parameter '{}'", name),
+            Self::DuplicateParameter(name) => write!(f, "Parameter '{}' is given more than once", name),
+            Self::ConflictingParameters(a, b) => write!(f, "Parameters '{}' and '{}' can't be combined", a, b),
+            Self::InvalidValue { name, value } => write!(f, "Invalid value '{}' for '{}'", value, name),
+            Self::OutOfRange { name, value } => write!(
+                f,
+                "'{}' must be between {} and {}, got {}",
+                name, MIN_DIMENSION, MAX_DIMENSION, value
+            ),
+            Self::InvalidEncoding => write!(f, "Invalid percent-encoding"),
+            Self::MissingPresetName => write!(f, "Missing preset name"),
+        }
+    }
+}
+
+impl std::error::Error for DeepLinkError {}
+
+pub fn parse(url: &str) -> Result<DeepLink, DeepLinkError> {
+    let (scheme, rest) = url.trim().split_once("://").ok_or(DeepLinkError::NotFrameFit)?;
+    if !scheme.eq_ignore_ascii_case(SCHEME) {
+        return Err(DeepLinkError::NotFrameFit);
+    }
+
+    let rest = rest.split_once('#').map_or(rest, |(rest, _fragment)| rest);
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let params = parse_query(query)?;
+    let (action, argument) = path.split_once('/').unwrap_or((path, ""));
+
+    match action.to_ascii_lowercase().as_str() {
+        "resize" if argument.is_empty() => parse_resize(&params),
+        "preset" => {
+            if let Some((name, _)) = params.first() {
+                return Err(DeepLinkError::UnknownParameter(name.clone()));
+            }
+            // Slashes in names have to be encoded, a trailing one is tolerated
+            let argument = argument.strip_suffix('/').unwrap_or(argument);
+            if argument.contains('/') {
+                return Err(DeepLinkError::InvalidValue { name: "preset".to_string(), value: argument.to_string() });
+            }
+
+            let name = percent_decode(argument, false)?.trim().to_string();
+            if name.is_empty() {
+                return Err(DeepLinkError::MissingPresetName);
+            }
+            Ok(DeepLink::Preset { name })
+        }
+        _ => Err(DeepLinkError::UnknownAction(path.to_string())),
+    }
+}
+
+fn parse_resize(params: &[(String, String)]) -> Result<DeepLink, DeepLinkError> {
+    let get = |name: &str| params.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+
+    if let Some((name, _)) = params.iter().find(|(key, _)| !["w", "h", "center", "placement"].contains(&key.as_str())) {
+        return Err(DeepLinkError::UnknownParameter(name.clone()));
+    }
+
+    let width = dimension("w", get("w"))?;
+    let height = dimension("h", get("h"))?;
+    let placement = match (get("center"), get("placement")) {
+        (Some(_), Some(_)) => return Err(DeepLinkError::ConflictingParameters("center", "placement")),
+        (Some(center), None) => Placement::from_center(flag("center", center)?),
+        (None, Some(placement)) => placement.parse().map_err(|_| invalid("placement", placement))?,
+        (None, None) => Placement::KEEP,
+    };
+
+    Ok(DeepLink::Resize { width, height, placement })
+}
+
+fn dimension(name: &'static str, value: Option<&str>) -> Result<i32, DeepLinkError> {
+    let value = value.ok_or(DeepLinkError::MissingParameter(name))?;
+    // Digits only, so `+720` or `720.5` aren't silently accepted
+    if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(invalid(name, value));
+    }
+
+    let number = value.parse::<i32>().unwrap_or(i32::MAX);
+    if !(MIN_DIMENSION..=MAX_DIMENSION).contains(&number) {
+        return Err(DeepLinkError::OutOfRange { name, value: number });
+    }
+    Ok(number)
+}
+
+fn flag(name: &str, value: &str) -> Result<bool, DeepLinkError> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" => Ok(true),
+        "0" | "false" | "no" => Ok(false),
+        _ => Err(invalid(name, value)),
+    }
+}
+
+fn invalid(name: &str, value: &str) -> DeepLinkError {
+    DeepLinkError::InvalidValue { name: name.to_string(), value: value.to_string() }
+}
+
+/// Decoded `key=value` pairs, in order. Keys are case-sensitive, like in
+/// most URL parsers.
+fn parse_query(query: &str) -> Result<Vec<(String, String)>, DeepLinkError> {
+    let mut params: Vec<(String, String)> = Vec::new();
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = percent_decode(key, true)?;
+        let value = percent_decode(value, true)?;
+
+        if params.iter().any(|(existing, _)| *existing == key) {
+            return Err(DeepLinkError::DuplicateParameter(key));
+        }
+        params.push((key, value));
+    }
+
+    Ok(params)
+}
+
+/// Decodes `%XX` escapes, and `+` as a space in query strings.
+fn percent_decode(value: &str, plus_as_space: bool) -> Result<String, DeepLinkError> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+
+    while let Some(byte) = input.next() {
+        match byte {
+            b'%' => {
+                let high = input.next().and_then(|digit| (digit as char).to_digit(16));
+                let low = input.next().and_then(|digit| (digit as char).to_digit(16));
+                let (Some(high), Some(low)) = (high, low) else {
+                    return Err(DeepLinkError::InvalidEncoding);
+                };
+                bytes.push((high * 16 + low) as u8);
+            }
+            b'+' if plus_as_space => bytes.push(b' '),
+            byte => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8(bytes).map_err(|_| DeepLinkError::InvalidEncoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::placement::Anchor;
+
+    #[test]
+    fn parses_resize_links() {
+        assert_eq!(
+            parse("framefit://resize?w=1280&h=720&center=1"),
+            Ok(DeepLink::Resize { width: 1280, height: 720, placement: Placement::CENTER })
+        );
+        assert_eq!(
+            parse("FrameFit://Resize/?h=844&w=390"),
+            Ok(DeepLink::Resize { width: 390, height: 844, placement: Placement::KEEP })
+        );
+        assert_eq!(
+            parse("framefit://resize?w=390&h=844&placement=bottom-right%3A20"),
+            Ok(DeepLink::Resize { width: 390, height: 844, placement: Placement::new(Anchor::BottomRight, 20) })
+        );
+    }
+
+    #[test]
+    fn parses_preset_links() {
+        assert_eq!(parse("framefit://preset/iPhone%2014"), Ok(DeepLink::Preset { name: "iPhone 14".to_string() }));
+        assert_eq!(parse("framefit://preset/Blog%2FHero/"), Ok(DeepLink::Preset { name: "Blog/Hero".to_string() }));
+        assert_eq!(parse("framefit://preset/Caf%C3%A9#wiki"), Ok(DeepLink::Preset { name: "Café".to_string() }));
+    }
+
+    #[test]
+    fn rejects_malformed_links() {
+        assert_eq!(parse("https://resize?w=1280&h=720"), Err(DeepLinkError::NotFrameFit));
+        assert_eq!(parse("framefit:resize"), Err(DeepLinkError::NotFrameFit));
+        assert_eq!(parse("framefit://maximize"), Err(DeepLinkError::UnknownAction("maximize".to_string())));
+        assert_eq!(parse("framefit://resize?w=1280"), Err(DeepLinkError::MissingParameter("h")));
+        assert_eq!(parse("framefit://resize?w=1280&h=720&w=800"), Err(DeepLinkError::DuplicateParameter("w".to_string())));
+        assert_eq!(parse("framefit://resize?w=1280&h=720&x=5"), Err(DeepLinkError::UnknownParameter("x".to_string())));
+        assert_eq!(
+            parse("framefit://resize?w=1280&h=720&center=1&placement=top"),
+            Err(DeepLinkError::ConflictingParameters("center", "placement"))
+        );
+        assert_eq!(parse("framefit://resize?w=1280&h=720&center=maybe"), Err(invalid("center", "maybe")));
+        assert_eq!(parse("framefit://resize?w=-5&h=720"), Err(invalid("w", "-5")));
+        assert_eq!(parse("framefit://resize?w=12.5&h=720"), Err(invalid("w", "12.5")));
+        assert_eq!(parse("framefit://preset/"), Err(DeepLinkError::MissingPresetName));
+        assert_eq!(parse("framefit://preset/iPad?center=1"), Err(DeepLinkError::UnknownParameter("center".to_string())));
+        assert_eq!(parse("framefit://preset/iPhone%2"), Err(DeepLinkError::InvalidEncoding));
+        assert_eq!(parse("framefit://preset/%FF"), Err(DeepLinkError::InvalidEncoding));
+    }
+
+    #[test]
+    fn rejects_out_of_range_sizes() {
+        assert_eq!(parse("framefit://resize?w=99&h=720"), Err(DeepLinkError::OutOfRange { name: "w", value: 99 }));
+        assert_eq!(parse("framefit://resize?w=1280&h=8193"), Err(DeepLinkError::OutOfRange { name: "h", value: 8193 }));
+        assert_eq!(
+            parse("framefit://resize?w=99999999999&h=720"),
+            Err(DeepLinkError::OutOfRange { name: "w", value: i32::MAX })
+        );
+    }
+}
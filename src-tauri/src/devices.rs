@@ -0,0 +1,293 @@
+//! The built-in catalog of real device viewports, read from
+//! `data/devices.json`. Adding or correcting a device only touches that file.
+
+use crate::presets::{self, Preset};
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+const CATALOG_JSON: &str = include_str!("../data/devices.json");
+
+/// Version of `data/devices.json` this FrameFit reads.
+const CATALOG_VERSION: u32 = 1;
+
+/// How many results a search returns unless the filters say otherwise.
+pub const DEFAULT_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceCategory {
+    Phone,
+    Tablet,
+    Laptop,
+    Desktop,
+    Tv,
+    /// Video and post formats of social platforms, e.g. 9:16 reels.
+    Social,
+}
+
+impl DeviceCategory {
+    /// The preset category, as grouped in the UI and the tray menu.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Phone => "Phone",
+            Self::Tablet => "Tablet",
+            Self::Laptop => "Laptop",
+            Self::Desktop => "Desktop",
+            Self::Tv => "TV",
+            Self::Social => "Social",
+        }
+    }
+}
+
+/// A device and its CSS viewport, in portrait for phones and tablets.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Device {
+    pub id: String,
+    pub name: String,
+    /// The platform for social formats, "Generic" for plain resolutions.
+    pub manufacturer: String,
+    pub category: DeviceCategory,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<u16>,
+    pub width: i32,
+    pub height: i32,
+    pub device_pixel_ratio: f64,
+}
+
+impl Device {
+    /// "Apple iPhone 15", or just the name for generic entries.
+    pub fn display_name(&self) -> String {
+        if self.manufacturer == "Generic" {
+            self.name.clone()
+        } else {
+            format!("{} {}", self.manufacturer, self.name)
+        }
+    }
+
+    /// A custom preset for the device. The id comes from the device id, so
+    /// adding the same device twice gives the same preset.
+    pub fn to_preset(&self) -> Preset {
+        let mut tags = vec![self.manufacturer.clone()];
+        tags.extend(self.year.map(|year| year.to_string()));
+
+        Preset {
+            id: presets::derived_id(&format!("device:{}", self.id)),
+            category: Some(self.category.label().to_string()),
+            device_pixel_ratio: self.device_pixel_ratio,
+            tags,
+            ..Preset::new(&self.display_name(), self.width, self.height)
+        }
+    }
+
+    fn haystack(&self) -> String {
+        format!(
+            "{} {} {} {}x{}",
+            self.manufacturer,
+            self.name,
+            self.category.label(),
+            self.width,
+            self.height
+        )
+        .to_lowercase()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceFilters {
+    /// Any of these categories, all when empty.
+    pub categories: Vec<DeviceCategory>,
+    /// Ignoring case.
+    pub manufacturer: Option<String>,
+    /// Devices without a year, like social formats, don't match year filters.
+    pub min_year: Option<u16>,
+    pub max_year: Option<u16>,
+    pub limit: Option<usize>,
+}
+
+impl DeviceFilters {
+    fn matches(&self, device: &Device) -> bool {
+        let year_matches = |bound: Option<u16>, ok: fn(u16, u16) -> bool| match (bound, device.year) {
+            (None, _) => true,
+            (Some(bound), Some(year)) => ok(year, bound),
+            (Some(_), None) => false,
+        };
+
+        (self.categories.is_empty() || self.categories.contains(&device.category))
+            && self
+                .manufacturer
+                .as_ref()
+                .is_none_or(|manufacturer| device.manufacturer.eq_ignore_ascii_case(manufacturer))
+            && year_matches(self.min_year, |year, min| year >= min)
+            && year_matches(self.max_year, |year, max| year <= max)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CatalogFile {
+    version: u32,
+    devices: Vec<Device>,
+}
+
+/// Reads a catalog file, refusing duplicate ids and empty viewports.
+pub fn parse(json: &str) -> Result<Vec<Device>, String> {
+    let file: CatalogFile = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if file.version != CATALOG_VERSION {
+        return Err(format!("Device catalog has version {}, expected {}", file.version, CATALOG_VERSION));
+    }
+
+    let mut ids = HashSet::new();
+    for device in &file.devices {
+        if !ids.insert(device.id.as_str()) {
+            return Err(format!("Duplicate device id \"{}\"", device.id));
+        }
+        if device.width <= 0 || device.height <= 0 || device.device_pixel_ratio <= 0.0 {
+            return Err(format!("Device \"{}\" has an empty viewport", device.id));
+        }
+    }
+
+    Ok(file.devices)
+}
+
+/// The bundled catalog, read on first use.
+pub fn catalog() -> &'static [Device] {
+    static CATALOG: OnceLock<Vec<Device>> = OnceLock::new();
+    CATALOG.get_or_init(|| parse(CATALOG_JSON).expect("Invalid data/devices.json"))
+}
+
+pub fn find<'a>(devices: &'a [Device], id: &str) -> Option<&'a Device> {
+    devices.iter().find(|device| device.id == id)
+}
+
+/// Devices matching every word of `query` and the filters. Names starting
+/// with the query come first, then newer devices; an empty query lists the
+/// whole (filtered) catalog.
+pub fn search<'a>(devices: &'a [Device], query: &str, filters: &DeviceFilters) -> Vec<&'a Device> {
+    let query = query.trim().to_lowercase();
+    let words: Vec<&str> = query.split_whitespace().collect();
+
+    let mut results: Vec<&Device> = devices
+        .iter()
+        .filter(|device| filters.matches(device))
+        .filter(|device| {
+            let haystack = device.haystack();
+            words.iter().all(|word| haystack.contains(word))
+        })
+        .collect();
+
+    if !query.is_empty() {
+        let rank = |device: &Device| {
+            let name = device.name.to_lowercase();
+            let display_name = device.display_name().to_lowercase();
+            if name == query || display_name == query {
+                0
+            } else if name.starts_with(&query) || display_name.starts_with(&query) {
+                1
+            } else {
+                2
+            }
+        };
+        // Stable, so devices of the same year keep the catalog order
+        results.sort_by_key(|device| (rank(device), std::cmp::Reverse(device.year)));
+    }
+
+    results.truncate(filters.limit.unwrap_or(DEFAULT_LIMIT));
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(devices: &[&Device]) -> Vec<String> {
+        devices.iter().map(|device| device.id.clone()).collect()
+    }
+
+    #[test]
+    fn the_bundled_catalog_is_valid() {
+        let devices = parse(CATALOG_JSON).unwrap();
+
+        assert!(devices.len() >= 300, "only {} devices", devices.len());
+        for category in [
+            DeviceCategory::Phone,
+            DeviceCategory::Tablet,
+            DeviceCategory::Laptop,
+            DeviceCategory::Desktop,
+            DeviceCategory::Tv,
+            DeviceCategory::Social,
+        ] {
+            assert!(devices.iter().any(|device| device.category == category), "no {:?} devices", category);
+        }
+        // The built-in presets are real devices too
+        assert!(find(&devices, "apple-iphone-se-2nd-generation").is_some());
+        assert!(find(&devices, "apple-iphone-14").is_some());
+        // Every device can be added as a preset
+        for device in &devices {
+            if let Err(e) = presets::validate(&device.to_preset(), &[]) {
+                panic!("{} can't be added: {}", device.id, e);
+            }
+        }
+    }
+
+    #[test]
+    fn refuses_broken_catalogs() {
+        let file = |version: u32, devices: &[&str]| {
+            format!(r#"{{ "version": {}, "devices": [{}] }}"#, version, devices.join(","))
+        };
+        let device = r#"{ "id": "a", "name": "A", "manufacturer": "B", "category": "phone",
+                          "width": 1, "height": 1, "device_pixel_ratio": 1 }"#;
+        let empty = device.replace(r#""width": 1"#, r#""width": 0"#);
+
+        assert!(parse(&file(1, &[device, device])).unwrap_err().contains("Duplicate"));
+        assert!(parse(&file(2, &[device])).unwrap_err().contains("version"));
+        assert!(parse(&file(1, &[&empty])).unwrap_err().contains("empty"));
+        assert_eq!(parse(&file(1, &[device])).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn searches_by_every_word() {
+        let results = search(catalog(), "iphone 15", &DeviceFilters::default());
+
+        assert_eq!(
+            ids(&results),
+            ["apple-iphone-15", "apple-iphone-15-plus", "apple-iphone-15-pro", "apple-iphone-15-pro-max"]
+        );
+        assert_eq!(search(catalog(), "  Pixel 8 pro ", &DeviceFilters::default())[0].id, "google-pixel-8-pro");
+        assert_eq!(ids(&search(catalog(), "390x844", &DeviceFilters::default()))[0], "apple-iphone-16e");
+        assert!(search(catalog(), "no such device", &DeviceFilters::default()).is_empty());
+    }
+
+    #[test]
+    fn filters_by_category_manufacturer_and_year() {
+        let filters = DeviceFilters {
+            categories: vec![DeviceCategory::Tablet],
+            manufacturer: Some("samsung".to_string()),
+            min_year: Some(2023),
+            ..Default::default()
+        };
+        let results = search(catalog(), "", &filters);
+
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|device| device.category == DeviceCategory::Tablet && device.year >= Some(2023)));
+
+        let social = DeviceFilters { categories: vec![DeviceCategory::Social], ..Default::default() };
+        assert!(search(catalog(), "9:16", &social).len() > 5);
+        assert!(search(catalog(), "9:16", &DeviceFilters { max_year: Some(2030), ..social }).is_empty());
+        assert_eq!(search(catalog(), "", &DeviceFilters { limit: Some(3), ..Default::default() }).len(), 3);
+    }
+
+    #[test]
+    fn makes_presets_from_devices() {
+        let pixel = find(catalog(), "google-pixel-8").unwrap();
+        let preset = pixel.to_preset();
+
+        assert_eq!(preset.name, "Google Pixel 8");
+        assert_eq!((preset.width, preset.height, preset.device_pixel_ratio), (412, 915, 2.625));
+        assert_eq!(preset.category.as_deref(), Some("Phone"));
+        assert_eq!(preset.tags, ["Google", "2023"]);
+        assert_eq!(pixel.to_preset().id, preset.id);
+        assert_ne!(preset.id, Preset::new("Google Pixel 8", 412, 915).id);
+    }
+}
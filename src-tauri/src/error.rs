@@ -0,0 +1,119 @@
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use std::fmt;
+
+/// Errors returned to the frontend.
+///
+/// Serializes as an object with a stable `code` (e.g. `"PERMISSION_DENIED"`),
+/// a human readable `message` and the variant's fields, so the UI can react
+/// to specific failures instead of matching on error text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameFitError {
+    /// The OS refused access, e.g. missing macOS Accessibility permissions.
+    PermissionDenied,
+    WindowNotFound { id: u32 },
+    /// No window matches an app name or title pattern.
+    NoMatchingWindow { query: String },
+    /// No display matches the requested index or name.
+    DisplayNotFound { display: String },
+    /// The requested size is bigger than the display's work area.
+    ExceedsWorkArea { width: i32, height: i32, max_width: i32, max_height: i32 },
+    /// There is no window other than FrameFit to act on.
+    NoWindows,
+    SelfResizeRefused,
+    /// The window system could not be reached at all.
+    BackendUnavailable { reason: String },
+    /// An AppleScript or KWin script ran but reported an error.
+    ScriptFailed { stderr: String, exit_code: Option<i32> },
+    /// The window system was reached but a request to it failed.
+    BackendFailed { message: String },
+    Unsupported,
+    /// A preset that can't be saved, e.g. with a name that's taken.
+    InvalidPreset { reason: String },
+    PresetNotFound { id: String },
+}
+
+impl FrameFitError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::PermissionDenied => "PERMISSION_DENIED",
+            Self::WindowNotFound { .. } => "WINDOW_NOT_FOUND",
+            Self::NoMatchingWindow { .. } => "NO_MATCHING_WINDOW",
+            Self::DisplayNotFound { .. } => "DISPLAY_NOT_FOUND",
+            Self::ExceedsWorkArea { .. } => "EXCEEDS_WORK_AREA",
+            Self::NoWindows => "NO_WINDOWS",
+            Self::SelfResizeRefused => "SELF_RESIZE_REFUSED",
+            Self::BackendUnavailable { .. } => "BACKEND_UNAVAILABLE",
+            Self::ScriptFailed { .. } => "SCRIPT_FAILED",
+            Self::BackendFailed { .. } => "BACKEND_FAILED",
+            Self::Unsupported => "UNSUPPORTED",
+            Self::InvalidPreset { .. } => "INVALID_PRESET",
+            Self::PresetNotFound { .. } => "PRESET_NOT_FOUND",
+        }
+    }
+
+    pub fn unavailable(reason: impl Into<String>) -> Self {
+        Self::BackendUnavailable { reason: reason.into() }
+    }
+
+    pub fn backend(message: impl Into<String>) -> Self {
+        Self::BackendFailed { message: message.into() }
+    }
+}
+
+impl fmt::Display for FrameFitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PermissionDenied => write!(f, "Accessibility permissions are required to control other windows"),
+            Self::WindowNotFound { id } => write!(f, "Window not found (id {})", id),
+            Self::NoMatchingWindow { query } => write!(f, "No window matches {}", query),
+            Self::DisplayNotFound { display } => write!(f, "Display not found: {}", display),
+            Self::ExceedsWorkArea { width, height, max_width, max_height } => write!(
+                f,
+                "{}×{} doesn't fit the available {}×{} on this display",
+                width, height, max_width, max_height
+            ),
+            Self::NoWindows => write!(f, "No suitable window found. Please open another application."),
+            Self::SelfResizeRefused => write!(f, "Cannot resize the FrameFit app itself"),
+            Self::BackendUnavailable { reason } => write!(f, "Window backend unavailable: {}", reason),
+            Self::ScriptFailed { stderr, .. } => write!(f, "Script error: {}", stderr.trim()),
+            Self::BackendFailed { message } => write!(f, "{}", message),
+            Self::Unsupported => write!(f, "Not supported on this platform"),
+            Self::InvalidPreset { reason } => write!(f, "{}", reason),
+            Self::PresetNotFound { id } => write!(f, "Preset not found (id {})", id),
+        }
+    }
+}
+
+impl std::error::Error for FrameFitError {}
+
+impl Serialize for FrameFitError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("code", self.code())?;
+        map.serialize_entry("message", &self.to_string())?;
+
+        match self {
+            Self::WindowNotFound { id } => map.serialize_entry("id", id)?,
+            Self::NoMatchingWindow { query } => map.serialize_entry("query", query)?,
+            Self::DisplayNotFound { display } => map.serialize_entry("display", display)?,
+            Self::ExceedsWorkArea { width, height, max_width, max_height } => {
+                map.serialize_entry("width", width)?;
+                map.serialize_entry("height", height)?;
+                map.serialize_entry("max_width", max_width)?;
+                map.serialize_entry("max_height", max_height)?;
+            }
+            Self::BackendUnavailable { reason } | Self::InvalidPreset { reason } => {
+                map.serialize_entry("reason", reason)?
+            }
+            Self::PresetNotFound { id } => map.serialize_entry("id", id)?,
+            Self::ScriptFailed { stderr, exit_code } => {
+                map.serialize_entry("stderr", stderr)?;
+                map.serialize_entry("exit_code", exit_code)?;
+            }
+            _ => {}
+        }
+
+        map.end()
+    }
+}
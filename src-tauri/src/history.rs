@@ -0,0 +1,346 @@
+use crate::error::FrameFitError;
+use crate::window_manager::{
+    self, restore_window, Rect, ResizeOptions, ResizeOutcome, ResizeRequest, WindowBackend,
+};
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// How many resizes can be undone.
+pub const MAX_ENTRIES: usize = 50;
+
+/// A change of one window's frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HistoryEntry {
+    pub window_id: u32,
+    pub before: Rect,
+    pub after: Rect,
+}
+
+/// What the UI needs to enable its undo, redo and restore buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HistoryState {
+    pub can_undo: bool,
+    pub can_redo: bool,
+    /// The window "Restore original size" applies to by default.
+    pub last_window: Option<u32>,
+}
+
+/// Undo and redo stacks of the resizes done by FrameFit, across windows,
+/// plus the frame each window had before FrameFit first touched it.
+#[derive(Debug, Default)]
+pub struct History {
+    undo: VecDeque<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    originals: HashMap<u32, Rect>,
+}
+
+impl History {
+    pub fn record(&mut self, outcome: &ResizeOutcome) {
+        self.push(HistoryEntry { window_id: outcome.window_id, before: outcome.previous, after: outcome.frame });
+    }
+
+    fn push(&mut self, entry: HistoryEntry) {
+        if entry.before == entry.after {
+            return;
+        }
+
+        self.originals.entry(entry.window_id).or_insert(entry.before);
+        self.undo.push_back(entry);
+        if self.undo.len() > MAX_ENTRIES {
+            self.undo.pop_front();
+        }
+        self.redo.clear();
+    }
+
+    pub fn state(&self) -> HistoryState {
+        HistoryState {
+            can_undo: !self.undo.is_empty(),
+            can_redo: !self.redo.is_empty(),
+            last_window: self.undo.back().or(self.redo.last()).map(|entry| entry.window_id),
+        }
+    }
+
+    /// Puts the most recently changed window back, or returns `None` when
+    /// there's nothing to undo. Entries of windows that were closed are
+    /// dropped, so the next undo moves on to older ones.
+    pub fn undo(&mut self, backend: &dyn WindowBackend) -> Result<Option<HistoryEntry>, FrameFitError> {
+        let Some(entry) = self.undo.pop_back() else {
+            return Ok(None);
+        };
+
+        match restore_window(backend, entry.window_id, &entry.before) {
+            Ok(()) => {
+                self.redo.push(entry);
+                Ok(Some(entry))
+            }
+            Err(e @ FrameFitError::WindowNotFound { .. }) => {
+                self.forget(entry.window_id);
+                Err(e)
+            }
+            Err(e) => {
+                self.undo.push_back(entry);
+                Err(e)
+            }
+        }
+    }
+
+    /// Applies the most recently undone change again.
+    pub fn redo(&mut self, backend: &dyn WindowBackend) -> Result<Option<HistoryEntry>, FrameFitError> {
+        let Some(entry) = self.redo.pop() else {
+            return Ok(None);
+        };
+
+        match restore_window(backend, entry.window_id, &entry.after) {
+            Ok(()) => {
+                self.undo.push_back(entry);
+                Ok(Some(entry))
+            }
+            Err(e @ FrameFitError::WindowNotFound { .. }) => {
+                self.forget(entry.window_id);
+                Err(e)
+            }
+            Err(e) => {
+                self.redo.push(entry);
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns a window to the frame it had before FrameFit first resized it.
+    /// This is itself a change that can be undone.
+    pub fn restore_original(
+        &mut self,
+        backend: &dyn WindowBackend,
+        window_id: u32,
+    ) -> Result<Option<HistoryEntry>, FrameFitError> {
+        let Some(original) = self.originals.get(&window_id).copied() else {
+            return Ok(None);
+        };
+
+        let window = match backend.list_windows()?.into_iter().find(|window| window.id == window_id) {
+            Some(window) => window,
+            None => {
+                self.forget(window_id);
+                return Err(FrameFitError::WindowNotFound { id: window_id });
+            }
+        };
+
+        restore_window(backend, window_id, &original)?;
+        let entry = HistoryEntry { window_id, before: window.frame(), after: original };
+        self.push(entry);
+        Ok(Some(entry))
+    }
+
+    fn forget(&mut self, window_id: u32) {
+        self.undo.retain(|entry| entry.window_id != window_id);
+        self.redo.retain(|entry| entry.window_id != window_id);
+        self.originals.remove(&window_id);
+    }
+}
+
+/// The resizes done by FrameFit, shared by the commands, hotkeys, links and
+/// rules.
+#[derive(Debug, Default)]
+pub struct ResizeHistory(Mutex<History>);
+
+impl ResizeHistory {
+    pub fn record(&self, outcome: &ResizeOutcome) {
+        self.0.lock().unwrap().record(outcome);
+    }
+
+    pub fn state(&self) -> HistoryState {
+        self.0.lock().unwrap().state()
+    }
+}
+
+/// Resizes the frontmost window and records it for undo.
+pub fn resize_frontmost(
+    backend: &dyn WindowBackend,
+    history: &ResizeHistory,
+    width: i32,
+    height: i32,
+    options: &ResizeOptions,
+) -> Result<ResizeOutcome, FrameFitError> {
+    let outcome = window_manager::resize_frontmost(backend, width, height, options)?;
+    history.record(&outcome);
+    Ok(outcome)
+}
+
+/// Resizes the window in `request` and records it for undo.
+pub fn resize_window(
+    backend: &dyn WindowBackend,
+    history: &ResizeHistory,
+    request: &ResizeRequest,
+    options: &ResizeOptions,
+) -> Result<ResizeOutcome, FrameFitError> {
+    let outcome = window_manager::resize_window_by_id(backend, request, options)?;
+    history.record(&outcome);
+    Ok(outcome)
+}
+
+pub fn undo(backend: &dyn WindowBackend, history: &ResizeHistory) -> Result<Option<HistoryEntry>, FrameFitError> {
+    history.0.lock().unwrap().undo(backend)
+}
+
+pub fn redo(backend: &dyn WindowBackend, history: &ResizeHistory) -> Result<Option<HistoryEntry>, FrameFitError> {
+    history.0.lock().unwrap().redo(backend)
+}
+
+/// Restores the original frame of `window_id`, or of the window changed last.
+pub fn restore_original(
+    backend: &dyn WindowBackend,
+    history: &ResizeHistory,
+    window_id: Option<u32>,
+) -> Result<Option<HistoryEntry>, FrameFitError> {
+    let mut history = history.0.lock().unwrap();
+    match window_id.or(history.state().last_window) {
+        Some(window_id) => history.restore_original(backend, window_id),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::placement::Placement;
+    use crate::window_manager::fake::{FakeWindow, FakeWindowServer};
+    use crate::window_manager::resize_window_by_id;
+
+    fn desktop() -> FakeWindowServer {
+        FakeWindowServer::new()
+            .with_screen("Built-in", 0, 0, 1440, 900)
+            .with_window(FakeWindow::new(1, "Safari", "Apple", 100, 100, 1024, 768))
+            .with_window(FakeWindow::new(2, "Terminal", "~ — zsh", 200, 200, 720, 480))
+    }
+
+    fn resize(server: &FakeWindowServer, history: &mut History, window_id: u32, width: i32, height: i32) {
+        let request = ResizeRequest { window_id, width, height };
+        let options = ResizeOptions { placement: Placement::CENTER, ..Default::default() };
+        history.record(&resize_window_by_id(server, &request, &options).unwrap());
+    }
+
+    fn frame(server: &FakeWindowServer, id: u32) -> Rect {
+        server.window(id).unwrap().frame()
+    }
+
+    #[test]
+    fn undoes_and_redoes_across_windows() {
+        let server = desktop();
+        let mut history = History::default();
+        resize(&server, &mut history, 1, 390, 844);
+        resize(&server, &mut history, 2, 800, 600);
+        let resized = frame(&server, 2);
+
+        history.undo(&server).unwrap().unwrap();
+        assert_eq!(frame(&server, 2), Rect::new(200, 200, 720, 480));
+        history.undo(&server).unwrap().unwrap();
+        assert_eq!(frame(&server, 1), Rect::new(100, 100, 1024, 768));
+        assert_eq!(history.undo(&server).unwrap(), None);
+
+        history.redo(&server).unwrap().unwrap();
+        history.redo(&server).unwrap().unwrap();
+        assert_eq!(frame(&server, 2), resized);
+        assert_eq!(history.state(), HistoryState { can_undo: true, can_redo: false, last_window: Some(2) });
+    }
+
+    #[test]
+    fn new_resizes_clear_the_redo_stack() {
+        let server = desktop();
+        let mut history = History::default();
+        resize(&server, &mut history, 1, 390, 844);
+        history.undo(&server).unwrap();
+
+        resize(&server, &mut history, 1, 768, 800);
+
+        assert_eq!(history.redo(&server).unwrap(), None);
+    }
+
+    #[test]
+    fn keeps_a_bounded_history_but_every_original() {
+        let server = desktop();
+        let mut history = History::default();
+        for step in 0..MAX_ENTRIES as i32 + 10 {
+            resize(&server, &mut history, 1, 400 + step, 400);
+        }
+
+        let mut undone = 0;
+        while history.undo(&server).unwrap().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_ENTRIES);
+
+        history.restore_original(&server, 1).unwrap().unwrap();
+        assert_eq!(frame(&server, 1), Rect::new(100, 100, 1024, 768));
+    }
+
+    #[test]
+    fn restoring_the_original_can_be_undone() {
+        let server = desktop();
+        let mut history = History::default();
+        resize(&server, &mut history, 2, 390, 844);
+        resize(&server, &mut history, 2, 768, 800);
+        let resized = frame(&server, 2);
+
+        history.restore_original(&server, 2).unwrap().unwrap();
+        assert_eq!(frame(&server, 2), Rect::new(200, 200, 720, 480));
+
+        history.undo(&server).unwrap();
+        assert_eq!(frame(&server, 2), resized);
+        assert_eq!(history.restore_original(&server, 1).unwrap(), None);
+    }
+
+    #[test]
+    fn forgets_closed_windows() {
+        let server = desktop();
+        let mut history = History::default();
+        resize(&server, &mut history, 1, 390, 844);
+        resize(&server, &mut history, 2, 800, 600);
+        let server = FakeWindowServer::new()
+            .with_screen("Built-in", 0, 0, 1440, 900)
+            .with_window(FakeWindow::new(1, "Safari", "Apple", 0, 0, 390, 844));
+
+        assert_eq!(history.undo(&server).unwrap_err(), FrameFitError::WindowNotFound { id: 2 });
+        history.undo(&server).unwrap().unwrap();
+        assert_eq!(frame(&server, 1), Rect::new(100, 100, 1024, 768));
+        assert_eq!(history.state().last_window, Some(1));
+    }
+
+    #[test]
+    fn shared_resizes_are_recorded_for_undo() {
+        let server = desktop();
+        let history = ResizeHistory::default();
+        let options = ResizeOptions { placement: Placement::CENTER, ..Default::default() };
+
+        let outcome = resize_frontmost(&server, &history, 390, 844, &options).unwrap();
+        assert_eq!(outcome.window_id, 1);
+        resize_window(&server, &history, &ResizeRequest { window_id: 2, width: 800, height: 600 }, &options).unwrap();
+        let missing = ResizeRequest { window_id: 3, width: 800, height: 600 };
+        assert_eq!(
+            resize_window(&server, &history, &missing, &options).unwrap_err(),
+            FrameFitError::WindowNotFound { id: 3 }
+        );
+        assert_eq!(history.state(), HistoryState { can_undo: true, can_redo: false, last_window: Some(2) });
+
+        assert_eq!(undo(&server, &history).unwrap().unwrap().window_id, 2);
+        assert_eq!(frame(&server, 2), Rect::new(200, 200, 720, 480));
+        assert_eq!(redo(&server, &history).unwrap().unwrap().window_id, 2);
+    }
+
+    #[test]
+    fn restores_the_window_changed_last_by_default() {
+        let server = desktop();
+        let history = ResizeHistory::default();
+        assert_eq!(restore_original(&server, &history, None).unwrap(), None);
+
+        let options = ResizeOptions { placement: Placement::CENTER, ..Default::default() };
+        resize_window(&server, &history, &ResizeRequest { window_id: 1, width: 390, height: 844 }, &options).unwrap();
+        resize_window(&server, &history, &ResizeRequest { window_id: 2, width: 800, height: 600 }, &options).unwrap();
+
+        assert_eq!(restore_original(&server, &history, None).unwrap().unwrap().window_id, 2);
+        assert_eq!(frame(&server, 2), Rect::new(200, 200, 720, 480));
+        restore_original(&server, &history, Some(1)).unwrap().unwrap();
+        assert_eq!(frame(&server, 1), Rect::new(100, 100, 1024, 768));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::presets::{self, Preset};
+
+use serde::{Deserialize, Serialize};
+use tauri_plugin_global_shortcut::Shortcut;
+use uuid::Uuid;
+
+/// What a global hotkey does. Every action works on the frontmost window.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum HotkeyAction {
+    /// Apply the preset with this id, which stays the same when it's renamed.
+    Preset { id: Uuid },
+    /// Apply the preset after the one applied last, wrapping around.
+    CyclePresets,
+    CenterFrontmost,
+    UndoLastResize,
+    RedoResize,
+    /// Return the frontmost window to its size before FrameFit first resized it.
+    RestoreOriginal,
+}
+
+/// A key combination bound to an action, stored next to `customPresets` in
+/// `presets.json`, e.g. `{ "shortcut": "CmdOrCtrl+Alt+1", "action": "preset", "id": "1b4e28ba-…" }`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct HotkeyBinding {
+    pub shortcut: String,
+    #[serde(flatten)]
+    pub action: HotkeyAction,
+}
+
+/// Why a binding can't be registered.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum HotkeyConflict {
+    Invalid { shortcut: String, message: String },
+    /// Several bindings use the same key combination, however it is spelled.
+    Duplicate { shortcut: String, actions: Vec<HotkeyAction> },
+    /// The preset was deleted.
+    UnknownPreset { shortcut: String, id: Uuid },
+    /// Registering failed, usually because another app owns the shortcut.
+    Unavailable { shortcut: String, message: String },
+}
+
+/// Bindings as read from the store by [`migrate`].
+#[derive(Debug)]
+pub struct Migrated {
+    pub bindings: Vec<HotkeyBinding>,
+    /// Whether preset names were replaced by ids and the bindings should be
+    /// saved again.
+    pub upgraded: bool,
+}
+
+/// Reads the stored bindings. Preset bindings from before presets had ids
+/// hold a name, which is looked up in `presets`. Bindings with unknown names
+/// are reported as conflicts, and the stored names are kept until they're
+/// fixed.
+pub fn migrate(mut value: serde_json::Value, presets: &[Preset]) -> Result<Migrated, String> {
+    let mut renamed = false;
+    let mut unknown = false;
+
+    for binding in value.as_array_mut().into_iter().flatten().filter_map(serde_json::Value::as_object_mut) {
+        if binding.get("action").and_then(serde_json::Value::as_str) != Some("preset") || binding.contains_key("id") {
+            continue;
+        }
+        let Some(name) = binding.remove("name") else {
+            continue;
+        };
+
+        let id = presets::id_for_name(presets, name.as_str().unwrap_or_default());
+        unknown |= id.is_nil();
+        renamed = true;
+        binding.insert("id".to_string(), id.to_string().into());
+    }
+
+    let bindings = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    Ok(Migrated { bindings, upgraded: renamed && !unknown })
+}
+
+/// Checks `bindings` against each other and the ids of the existing presets.
+/// Returns the bindings that can be registered along with the conflicts of
+/// the others; duplicated shortcuts are left out entirely.
+pub fn resolve(bindings: &[HotkeyBinding], preset_ids: &[Uuid]) -> (Vec<(Shortcut, HotkeyAction)>, Vec<HotkeyConflict>) {
+    let mut conflicts = Vec::new();
+    let mut groups: Vec<(Shortcut, Vec<&HotkeyBinding>)> = Vec::new();
+
+    for binding in bindings {
+        let shortcut = match binding.shortcut.parse::<Shortcut>() {
+            Ok(shortcut) => shortcut,
+            Err(e) => {
+                conflicts.push(HotkeyConflict::Invalid {
+                    shortcut: binding.shortcut.clone(),
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        if let HotkeyAction::Preset { id } = &binding.action {
+            if !preset_ids.contains(id) {
+                conflicts.push(HotkeyConflict::UnknownPreset {
+                    shortcut: binding.shortcut.clone(),
+                    id: *id,
+                });
+                continue;
+            }
+        }
+
+        match groups.iter_mut().find(|(existing, _)| *existing == shortcut) {
+            Some((_, group)) => group.push(binding),
+            None => groups.push((shortcut, vec![binding])),
+        }
+    }
+
+    let mut resolved = Vec::new();
+    for (shortcut, group) in groups {
+        match group[..] {
+            [binding] => resolved.push((shortcut, binding.action.clone())),
+            _ => conflicts.push(HotkeyConflict::Duplicate {
+                shortcut: group[0].shortcut.clone(),
+                actions: group.iter().map(|binding| binding.action.clone()).collect(),
+            }),
+        }
+    }
+
+    (resolved, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn binding(shortcut: &str, action: HotkeyAction) -> HotkeyBinding {
+        HotkeyBinding { shortcut: shortcut.to_string(), action }
+    }
+
+    fn preset(name: &str) -> HotkeyAction {
+        HotkeyAction::Preset { id: presets::id_for_name(&presets::default_presets(), name) }
+    }
+
+    #[test]
+    fn reads_bindings_from_the_store_format() {
+        let iphone = presets::find(&presets::default_presets(), "iPhone 14").unwrap().id;
+        let stored = json!([
+            { "shortcut": "Alt+1", "action": "preset", "id": iphone },
+            { "shortcut": "Alt+U", "action": "undo-last-resize" },
+        ]);
+
+        let migrated = migrate(stored, &presets::default_presets()).unwrap();
+
+        assert!(!migrated.upgraded);
+        assert_eq!(migrated.bindings[0].action, HotkeyAction::Preset { id: iphone });
+        assert_eq!(migrated.bindings[1].action, HotkeyAction::UndoLastResize);
+    }
+
+    #[test]
+    fn migrates_preset_names_to_ids() {
+        let mut presets = presets::default_presets();
+        let stored = json!([{ "shortcut": "Alt+1", "action": "preset", "name": "iphone 14" }]);
+
+        let migrated = migrate(stored.clone(), &presets).unwrap();
+        assert!(migrated.upgraded);
+        assert_eq!(migrated.bindings[0].action, preset("iPhone 14"));
+
+        // Renaming the preset afterwards keeps the binding
+        presets[1].name = "iPhone 14 (old)".to_string();
+        let preset_ids: Vec<Uuid> = presets.iter().map(|preset| preset.id).collect();
+        let (resolved, conflicts) = resolve(&migrated.bindings, &preset_ids);
+        assert_eq!(resolved.len(), 1);
+        assert!(conflicts.is_empty());
+
+        // Unknown names are reported but not saved over
+        let migrated = migrate(stored, &presets).unwrap();
+        assert!(!migrated.upgraded);
+        assert_eq!(migrated.bindings[0].action, HotkeyAction::Preset { id: Uuid::nil() });
+    }
+
+    #[test]
+    fn detects_conflicting_bindings() {
+        let bindings = [
+            binding("Ctrl+Shift+1", preset("iPhone SE")),
+            binding("shift+control+1", HotkeyAction::CyclePresets),
+            binding("Alt+2", HotkeyAction::Preset { id: Uuid::new_v4() }),
+            binding("Alt+Nope", HotkeyAction::CenterFrontmost),
+            binding("Alt+U", HotkeyAction::UndoLastResize),
+        ];
+        let preset_ids: Vec<Uuid> = presets::default_presets().iter().map(|preset| preset.id).collect();
+
+        let (resolved, conflicts) = resolve(&bindings, &preset_ids);
+
+        assert_eq!(resolved, [("Alt+U".parse().unwrap(), HotkeyAction::UndoLastResize)]);
+        assert!(matches!(conflicts[0], HotkeyConflict::UnknownPreset { .. }));
+        assert!(matches!(conflicts[1], HotkeyConflict::Invalid { .. }));
+        assert_eq!(
+            conflicts[2],
+            HotkeyConflict::Duplicate {
+                shortcut: "Ctrl+Shift+1".to_string(),
+                actions: vec![preset("iPhone SE"), HotkeyAction::CyclePresets],
+            }
+        );
+    }
+}
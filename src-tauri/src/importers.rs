@@ -0,0 +1,524 @@
+//! Reads device lists from other tools into presets: Chrome DevTools custom
+//! devices, Playwright device descriptors, Responsively App configs and CSV,
+//! besides FrameFit's own exports.
+
+use crate::presets::{self, Preset};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportFormat {
+    /// Exports of any FrameFit version.
+    #[serde(rename = "framefit")]
+    FrameFit,
+    /// The `customEmulatedDeviceList` of DevTools, bare or in Chrome's
+    /// `Preferences` file.
+    ChromeDevtools,
+    /// `deviceDescriptors` JSON, an object of descriptors keyed by name.
+    Playwright,
+    /// The app config or its `customDevices` list.
+    Responsively,
+    /// `name,width,height` rows, optionally with a header row naming more
+    /// columns.
+    Csv,
+}
+
+/// An entry that didn't become a preset.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rejected {
+    /// The entry's name, or its position when it has none.
+    pub entry: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportReport {
+    pub format: ImportFormat,
+    pub presets: Vec<Preset>,
+    pub rejected: Vec<Rejected>,
+    /// Names of entries skipped because an existing or earlier preset has
+    /// the same name and size. Same names with other sizes are rejected.
+    pub duplicates: Vec<String>,
+}
+
+/// An entry's label and what it was read as.
+type Entry = (String, Result<Preset, String>);
+
+/// Reads `text` as `format`, or the format it looks like. Entries that
+/// match one of `existing` by name (ignoring case) and size are skipped.
+pub fn import(text: &str, format: Option<ImportFormat>, existing: &[Preset]) -> Result<ImportReport, String> {
+    let format = match format {
+        Some(format) => format,
+        None => detect(text).ok_or("Unrecognized preset file")?,
+    };
+
+    let entries = match format {
+        ImportFormat::Csv => read_csv(text)?,
+        _ => {
+            let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+            match format {
+                ImportFormat::FrameFit => read_framefit(value)?,
+                ImportFormat::ChromeDevtools => read_chrome_devtools(value)?,
+                ImportFormat::Playwright => read_playwright(value)?,
+                ImportFormat::Responsively => read_responsively(value)?,
+                ImportFormat::Csv => unreachable!(),
+            }
+        }
+    };
+
+    Ok(finish(format, entries, existing))
+}
+
+/// Guesses the format from the shape of the file. Anything that isn't JSON
+/// is taken for CSV.
+pub fn detect(text: &str) -> Option<ImportFormat> {
+    let value: Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(_) if text.trim_start().starts_with(['{', '[']) => return None,
+        Err(_) => return Some(ImportFormat::Csv),
+    };
+
+    match &value {
+        Value::Array(entries) => match entries.first() {
+            Some(entry) if entry.get("screen").is_some() && entry.get("title").is_some() => {
+                Some(ImportFormat::ChromeDevtools)
+            }
+            Some(entry) if entry.get("dpr").is_some() => Some(ImportFormat::Responsively),
+            _ => Some(ImportFormat::FrameFit),
+        },
+        Value::Object(object) => {
+            if object.contains_key("version") && object.contains_key("presets") {
+                Some(ImportFormat::FrameFit)
+            } else if devtools_device_list(&value).is_some() {
+                Some(ImportFormat::ChromeDevtools)
+            } else if responsively_devices(&value).is_some() {
+                Some(ImportFormat::Responsively)
+            } else if !object.is_empty() && object.values().all(|entry| entry.get("viewport").is_some()) {
+                Some(ImportFormat::Playwright)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+fn read_framefit(value: Value) -> Result<Vec<Entry>, String> {
+    let migrated = presets::migrate(value)?;
+    Ok(migrated.presets.into_iter().map(|preset| (preset.name.clone(), Ok(preset))).collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct Viewport {
+    width: i32,
+    height: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct DevtoolsDevice {
+    title: String,
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    #[serde(default, rename = "user-agent")]
+    user_agent: Option<String>,
+    screen: DevtoolsScreen,
+}
+
+#[derive(Debug, Deserialize)]
+struct DevtoolsScreen {
+    #[serde(default = "presets::default_pixel_ratio", rename = "device-pixel-ratio")]
+    device_pixel_ratio: f64,
+    vertical: Viewport,
+}
+
+/// The device list of a DevTools export, which Chrome's `Preferences` file
+/// keeps as a JSON string.
+fn devtools_device_list(value: &Value) -> Option<Value> {
+    let list = value.pointer("/devtools/preferences/customEmulatedDeviceList")?;
+    match list {
+        Value::String(json) => serde_json::from_str(json).ok(),
+        list => Some(list.clone()),
+    }
+}
+
+fn read_chrome_devtools(value: Value) -> Result<Vec<Entry>, String> {
+    let list = if value.is_array() { value } else { devtools_device_list(&value).ok_or("No DevTools devices")? };
+
+    Ok(each_entry(list, "title")?
+        .map(|(label, entry)| {
+            let preset = serde_json::from_value::<DevtoolsDevice>(entry).map(|device| Preset {
+                category: device.kind.as_deref().and_then(category),
+                device_pixel_ratio: device.screen.device_pixel_ratio,
+                notes: user_agent_note(device.user_agent),
+                ..preset(&device.title, device.screen.vertical.width, device.screen.vertical.height)
+            });
+            (label, preset.map_err(|e| e.to_string()))
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaywrightDevice {
+    viewport: Viewport,
+    #[serde(default = "presets::default_pixel_ratio")]
+    device_scale_factor: f64,
+    #[serde(default)]
+    is_mobile: bool,
+    #[serde(default)]
+    user_agent: Option<String>,
+    #[serde(default)]
+    default_browser_type: Option<String>,
+}
+
+fn read_playwright(value: Value) -> Result<Vec<Entry>, String> {
+    let Value::Object(descriptors) = value else {
+        return Err("Expected an object of Playwright device descriptors".to_string());
+    };
+
+    Ok(descriptors
+        .into_iter()
+        .map(|(name, entry)| {
+            let preset = serde_json::from_value::<PlaywrightDevice>(entry).map(|device| {
+                let viewport = &device.viewport;
+                // Playwright only tells mobile from desktop
+                let category = match device.is_mobile {
+                    true if viewport.width.min(viewport.height) >= 600 => "Tablet",
+                    true => "Phone",
+                    false => "Desktop",
+                };
+                Preset {
+                    category: Some(category.to_string()),
+                    device_pixel_ratio: device.device_scale_factor,
+                    tags: device.default_browser_type.into_iter().collect(),
+                    notes: user_agent_note(device.user_agent),
+                    ..preset(&name, viewport.width, viewport.height)
+                }
+            });
+            (name, preset.map_err(|e| e.to_string()))
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResponsivelyDevice {
+    name: String,
+    width: i32,
+    height: i32,
+    #[serde(default = "presets::default_pixel_ratio")]
+    dpr: f64,
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    #[serde(default)]
+    user_agent: Option<String>,
+}
+
+fn responsively_devices(value: &Value) -> Option<&Value> {
+    value.pointer("/deviceManager/customDevices").or_else(|| value.get("customDevices"))
+}
+
+fn read_responsively(value: Value) -> Result<Vec<Entry>, String> {
+    let list = match &value {
+        Value::Array(_) => value.clone(),
+        _ => responsively_devices(&value).cloned().ok_or("No Responsively custom devices")?,
+    };
+
+    Ok(each_entry(list, "name")?
+        .map(|(label, entry)| {
+            let preset = serde_json::from_value::<ResponsivelyDevice>(entry).map(|device| Preset {
+                category: device.kind.as_deref().and_then(category),
+                device_pixel_ratio: device.dpr,
+                notes: user_agent_note(device.user_agent),
+                ..preset(&device.name, device.width, device.height)
+            });
+            (label, preset.map_err(|e| e.to_string()))
+        })
+        .collect())
+}
+
+/// Columns of a CSV file without a header row.
+const CSV_COLUMNS: [&str; 7] = ["name", "width", "height", "category", "dpr", "tags", "notes"];
+
+fn read_csv(text: &str) -> Result<Vec<Entry>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(text.as_bytes());
+
+    let mut columns: Vec<String> = CSV_COLUMNS.iter().map(|column| column.to_string()).collect();
+    let mut entries = Vec::new();
+
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| e.to_string())?;
+        let line = record.position().map_or(index as u64 + 1, |position| position.line());
+
+        // A header row has no number where the width goes
+        if index == 0 && record.get(1).is_some_and(|width| width.parse::<f64>().is_err()) {
+            columns = record.iter().map(csv_column).collect();
+            continue;
+        }
+
+        let field = |column: &str| {
+            columns
+                .iter()
+                .position(|name| name == column)
+                .and_then(|position| record.get(position))
+                .filter(|value| !value.is_empty())
+        };
+        let label = field("name").map_or_else(|| format!("line {}", line), str::to_string);
+        entries.push((label, csv_preset(field)));
+    }
+
+    Ok(entries)
+}
+
+fn csv_preset<'a>(field: impl Fn(&str) -> Option<&'a str>) -> Result<Preset, String> {
+    let number = |column: &str| {
+        let value = field(column).ok_or(format!("Missing {}", column))?;
+        value.parse::<i32>().map_err(|_| format!("Invalid {} \"{}\"", column, value))
+    };
+    let device_pixel_ratio = match field("dpr") {
+        Some(value) => value.parse().map_err(|_| format!("Invalid pixel ratio \"{}\"", value))?,
+        None => presets::default_pixel_ratio(),
+    };
+    let tags = field("tags").map_or_else(Vec::new, |tags| {
+        tags.split([';', '|']).map(str::trim).filter(|tag| !tag.is_empty()).map(str::to_string).collect()
+    });
+
+    Ok(Preset {
+        category: field("category").map(|value| category(value).unwrap_or_else(|| value.to_string())),
+        device_pixel_ratio,
+        tags,
+        notes: field("notes").map(str::to_string),
+        ..preset(field("name").unwrap_or_default(), number("width")?, number("height")?)
+    })
+}
+
+/// Header names as `CSV_COLUMNS` names them.
+fn csv_column(header: &str) -> String {
+    let header: String = header.chars().filter(char::is_ascii_alphanumeric).collect::<String>().to_lowercase();
+    match header.as_str() {
+        "title" | "device" => "name",
+        "w" => "width",
+        "h" => "height",
+        "type" => "category",
+        "devicepixelratio" | "pixelratio" | "scale" => "dpr",
+        "tag" => "tags",
+        "note" => "notes",
+        _ => return header,
+    }
+    .to_string()
+}
+
+/// The entries of a JSON list, labeled by their `name_key` or position.
+fn each_entry(list: Value, name_key: &str) -> Result<impl Iterator<Item = (String, Value)> + '_, String> {
+    let Value::Array(entries) = list else {
+        return Err("Expected a list of devices".to_string());
+    };
+
+    Ok(entries.into_iter().enumerate().map(move |(index, entry)| {
+        let label = match entry.get(name_key).and_then(Value::as_str) {
+            Some(name) if !name.trim().is_empty() => name.to_string(),
+            _ => format!("entry {}", index + 1),
+        };
+        (label, entry)
+    }))
+}
+
+/// A custom preset with an id derived from its name and size, so importing
+/// a file twice gives the same ids.
+fn preset(name: &str, width: i32, height: i32) -> Preset {
+    let name = name.trim();
+    Preset {
+        id: presets::derived_id(&format!("import:{}:{}x{}", name.to_lowercase(), width, height)),
+        ..Preset::new(name, width, height)
+    }
+}
+
+/// FrameFit's category for the device types of other tools.
+fn category(kind: &str) -> Option<String> {
+    let category = match kind.to_lowercase().as_str() {
+        "phone" | "mobile" => "Phone",
+        "tablet" => "Tablet",
+        "notebook" | "laptop" => "Laptop",
+        "desktop" | "desktop-touch" => "Desktop",
+        "tv" => "TV",
+        _ => return None,
+    };
+    Some(category.to_string())
+}
+
+fn user_agent_note(user_agent: Option<String>) -> Option<String> {
+    user_agent.filter(|user_agent| !user_agent.is_empty()).map(|user_agent| format!("User agent: {}", user_agent))
+}
+
+fn finish(format: ImportFormat, entries: Vec<Entry>, existing: &[Preset]) -> ImportReport {
+    let key = |preset: &Preset| (preset.name.to_lowercase(), preset.width, preset.height);
+    let mut seen: HashSet<_> = existing.iter().map(key).collect();
+    let mut all_presets = existing.to_vec();
+    let mut report = ImportReport { format, presets: Vec::new(), rejected: Vec::new(), duplicates: Vec::new() };
+
+    for (label, entry) in entries {
+        let preset = match entry {
+            Ok(preset) if seen.contains(&key(&preset)) => {
+                report.duplicates.push(preset.name);
+                continue;
+            }
+            Ok(preset) => preset,
+            Err(reason) => {
+                report.rejected.push(Rejected { entry: label, reason });
+                continue;
+            }
+        };
+
+        let mut preset = Preset { builtin: false, ..preset };
+        // E.g. a preset that was renamed since it was exported
+        if all_presets.iter().any(|other| other.id == preset.id) {
+            preset.id = Uuid::new_v4();
+        }
+
+        // Same checks as presets added in the app, unique names included
+        match presets::validate(&preset, &all_presets) {
+            Ok(()) => {
+                seen.insert(key(&preset));
+                all_presets.push(preset.clone());
+                report.presets.push(preset);
+            }
+            Err(e) => report.rejected.push(Rejected { entry: label, reason: e.to_string() }),
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CHROME_DEVTOOLS: &str = include_str!("../tests/fixtures/importers/chrome_devtools.json");
+    const CHROME_PREFERENCES: &str = include_str!("../tests/fixtures/importers/chrome_preferences.json");
+    const PLAYWRIGHT: &str = include_str!("../tests/fixtures/importers/playwright.json");
+    const RESPONSIVELY: &str = include_str!("../tests/fixtures/importers/responsively.json");
+    const CSV: &str = include_str!("../tests/fixtures/importers/devices.csv");
+
+    fn names(presets: &[Preset]) -> Vec<&str> {
+        presets.iter().map(|preset| preset.name.as_str()).collect()
+    }
+
+    fn reasons(report: &ImportReport) -> Vec<(&str, &str)> {
+        report.rejected.iter().map(|rejected| (rejected.entry.as_str(), rejected.reason.as_str())).collect()
+    }
+
+    #[test]
+    fn detects_formats() {
+        assert_eq!(detect(CHROME_DEVTOOLS), Some(ImportFormat::ChromeDevtools));
+        assert_eq!(detect(CHROME_PREFERENCES), Some(ImportFormat::ChromeDevtools));
+        assert_eq!(detect(PLAYWRIGHT), Some(ImportFormat::Playwright));
+        assert_eq!(detect(RESPONSIVELY), Some(ImportFormat::Responsively));
+        assert_eq!(detect(CSV), Some(ImportFormat::Csv));
+        assert_eq!(detect(&presets::to_store_value(&[]).to_string()), Some(ImportFormat::FrameFit));
+        assert_eq!(detect(r#"[{ "name": "Blog", "width": 1200, "height": 800 }]"#), Some(ImportFormat::FrameFit));
+        assert_eq!(detect(r#"{ "name": "Blog" }"#), None);
+        assert_eq!(detect("{ not json"), None);
+    }
+
+    #[test]
+    fn imports_chrome_devtools_devices() {
+        let report = import(CHROME_DEVTOOLS, None, &[]).unwrap();
+
+        assert_eq!(names(&report.presets), ["Pixel 8", "Kiosk"]);
+        let pixel = &report.presets[0];
+        assert_eq!((pixel.width, pixel.height, pixel.device_pixel_ratio), (412, 915, 2.625));
+        assert_eq!(pixel.category.as_deref(), Some("Phone"));
+        assert!(pixel.notes.as_deref().unwrap().contains("Android 14"));
+        assert_eq!(report.presets[1].notes, None);
+        assert_eq!(
+            reasons(&report),
+            [("Apple Watch", "Width 68 is outside 100–8192"), ("Half configured", "missing field `screen`")]
+        );
+
+        let preferences = import(CHROME_PREFERENCES, None, &[]).unwrap();
+        assert_eq!(names(&preferences.presets), ["Team tablet"]);
+        assert_eq!(preferences.presets[0].category.as_deref(), Some("Tablet"));
+    }
+
+    #[test]
+    fn imports_playwright_descriptors() {
+        let report = import(PLAYWRIGHT, None, &[]).unwrap();
+
+        // Without serde_json's preserve_order the descriptors come sorted by name
+        let mut presets = report.presets.clone();
+        presets.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(
+            names(&presets),
+            ["Desktop Chrome", "Desktop Firefox", "Galaxy Tab S4", "iPhone 13", "iPhone 13 landscape"]
+        );
+        let categories: Vec<_> = presets.iter().map(|preset| preset.category.as_deref().unwrap()).collect();
+        assert_eq!(categories, ["Desktop", "Desktop", "Tablet", "Phone", "Phone"]);
+        // The viewport, not the screen
+        assert_eq!((presets[3].width, presets[3].height), (390, 664));
+        assert_eq!(presets[3].tags, ["webkit"]);
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].entry, "Broken");
+    }
+
+    #[test]
+    fn imports_responsively_devices() {
+        let report = import(RESPONSIVELY, None, &[]).unwrap();
+
+        assert_eq!(names(&report.presets), ["Storybook phone", "Wide notebook"]);
+        assert_eq!(report.presets[1].category.as_deref(), Some("Laptop"));
+        assert_eq!(report.presets[1].device_pixel_ratio, 2.0);
+        assert_eq!(reasons(&report), [("entry 3", "Missing name")]);
+    }
+
+    #[test]
+    fn imports_csv_with_and_without_headers() {
+        let report = import(CSV, None, &presets::default_presets()).unwrap();
+
+        assert_eq!(names(&report.presets), ["Checkout, narrow", "Marketing site"]);
+        let checkout = &report.presets[0];
+        assert_eq!((checkout.width, checkout.height, checkout.device_pixel_ratio), (360, 740, 3.0));
+        assert_eq!(checkout.tags, ["mobile", "checkout"]);
+        assert_eq!(report.presets[1].notes.as_deref(), Some("Hero must fit, no scrolling"));
+        assert_eq!(report.duplicates, ["iPhone 14"]);
+        assert_eq!(
+            reasons(&report),
+            [("Too tall", "Height 9000 is outside 100–8192"), ("Typo", "Invalid width \"12OO\"")]
+        );
+
+        let plain = import("Blog,1200,800\n# ignored\nSidebar,320,800,Phone,2\n,100,100", Some(ImportFormat::Csv), &[]);
+        let plain = plain.unwrap();
+        assert_eq!(names(&plain.presets), ["Blog", "Sidebar"]);
+        assert_eq!(plain.presets[1].category.as_deref(), Some("Phone"));
+        assert_eq!(reasons(&plain), [("line 4", "Missing name")]);
+    }
+
+    #[test]
+    fn deduplicates_by_name_and_size() {
+        let file = json!({ "version": 2, "presets": [
+            Preset::new("Blog", 1200, 800),
+            Preset::new("blog", 1200, 800),
+            Preset::new("Blog", 800, 1200),
+            Preset::new("ipad", 768, 1024),
+        ]});
+
+        let report = import(&file.to_string(), None, &presets::default_presets()).unwrap();
+
+        assert_eq!(report.format, ImportFormat::FrameFit);
+        assert_eq!(names(&report.presets), ["Blog"]);
+        assert_eq!(report.duplicates, ["blog", "ipad"]);
+        // Names have to be unique even when the sizes differ
+        assert_eq!(reasons(&report), [("Blog", "There already is a preset named \"Blog\"")]);
+        // The same file gives the same ids
+        assert_eq!(import(CSV, None, &[]).unwrap().presets, import(CSV, None, &[]).unwrap().presets);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::error::FrameFitError;
+use crate::placement::{Anchor, Placement};
+use crate::window_manager::{
+    find_screen, resize_window, screen_for_window, DisplayTarget, ResizeOptions, ResizeOutcome, ScreenInfo,
+    WindowBackend, WindowInfo,
+};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Key of the layouts in the presets store.
+pub const STORE_KEY: &str = "layouts";
+
+/// A named arrangement of several windows, e.g. an editor on the left and a
+/// browser on the right.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layout {
+    pub name: String,
+    /// Front to back, like `list_windows()`.
+    pub windows: Vec<LayoutWindow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutWindow {
+    pub app_name: String,
+    /// Regular expression the title has to match; any title when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_pattern: Option<String>,
+    /// Offset from the top-left corner of the display's work area.
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    /// Name of the display; windows stay on their current one when it's gone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+impl LayoutWindow {
+    fn matches(&self, window: &WindowInfo, title: Option<&Regex>) -> bool {
+        window.app_name.eq_ignore_ascii_case(&self.app_name) && title.is_none_or(|title| title.is_match(&window.title))
+    }
+
+    fn describe(&self) -> String {
+        match &self.title_pattern {
+            Some(pattern) => format!("{} /{}/", self.app_name, pattern),
+            None => self.app_name.clone(),
+        }
+    }
+}
+
+/// Snapshots `windows`, each relative to the display showing most of it.
+/// Titles are matched exactly; edit the patterns to match more loosely.
+pub fn capture(name: &str, windows: &[WindowInfo], screens: &[ScreenInfo]) -> Layout {
+    let windows = windows
+        .iter()
+        .map(|window| {
+            let frame = window.frame();
+            let screen = screen_for_window(screens, &frame);
+            let origin = screen.map_or((0, 0), |screen| (screen.work_area.x, screen.work_area.y));
+
+            LayoutWindow {
+                app_name: window.app_name.clone(),
+                title_pattern: Some(format!("^{}$", regex::escape(&window.title))),
+                x: frame.x - origin.0,
+                y: frame.y - origin.1,
+                width: frame.width,
+                height: frame.height,
+                display: screen.map(|screen| screen.name.clone()),
+            }
+        })
+        .collect();
+
+    Layout { name: name.trim().to_string(), windows }
+}
+
+/// Checks what the store can't: names, sizes and title patterns.
+pub fn validate(layout: &Layout) -> Result<(), String> {
+    if layout.name.trim().is_empty() {
+        return Err("Layout name is empty".to_string());
+    }
+    if layout.windows.is_empty() {
+        return Err(format!("Layout '{}' has no windows", layout.name));
+    }
+
+    for window in &layout.windows {
+        if window.width <= 0 || window.height <= 0 {
+            return Err(format!("Invalid size {}×{} for {}", window.width, window.height, window.app_name));
+        }
+        if let Some(pattern) = &window.title_pattern {
+            Regex::new(pattern).map_err(|e| format!("Invalid title pattern for {}: {}", window.app_name, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A layout window that couldn't be restored.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedWindow {
+    pub app_name: String,
+    pub title_pattern: Option<String>,
+    pub error: FrameFitError,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LayoutOutcome {
+    pub restored: Vec<ResizeOutcome>,
+    pub skipped: Vec<SkippedWindow>,
+}
+
+/// Resizes and moves every window of the layout. Each open window is used
+/// at most once, so two entries for the same app take two of its windows.
+pub fn restore(backend: &dyn WindowBackend, layout: &Layout) -> Result<LayoutOutcome, FrameFitError> {
+    validate(layout).map_err(FrameFitError::backend)?;
+
+    let windows = backend.list_windows()?;
+    let screens = backend.list_screens()?;
+    let mut used = Vec::new();
+    let mut restored = Vec::new();
+    let mut skipped = Vec::new();
+
+    // Back to front, so focusing each window leaves the stacking order as captured
+    for entry in layout.windows.iter().rev() {
+        let title = entry.title_pattern.as_deref().map(|pattern| Regex::new(pattern).unwrap());
+        let window = windows
+            .iter()
+            .find(|window| !used.contains(&window.id) && entry.matches(window, title.as_ref()));
+
+        let result = match window {
+            Some(window) => {
+                used.push(window.id);
+                resize_window(backend, window, entry.width, entry.height, &restore_options(entry, &screens))
+            }
+            None => Err(FrameFitError::NoMatchingWindow { query: entry.describe() }),
+        };
+
+        match result {
+            Ok(mut outcome) => {
+                if entry.display.is_some() && restore_display(entry, &screens).is_none() {
+                    let display = entry.display.as_deref().unwrap_or_default();
+                    outcome.warnings.push(format!("Display {} is gone, kept {} on its display", display, entry.app_name));
+                }
+                restored.push(outcome);
+            }
+            Err(error) => skipped.push(SkippedWindow {
+                app_name: entry.app_name.clone(),
+                title_pattern: entry.title_pattern.clone(),
+                error,
+            }),
+        }
+    }
+
+    restored.reverse();
+    skipped.reverse();
+    Ok(LayoutOutcome { restored, skipped })
+}
+
+fn restore_display(entry: &LayoutWindow, screens: &[ScreenInfo]) -> Option<DisplayTarget> {
+    let target = DisplayTarget::Name(entry.display.clone()?);
+    find_screen(screens, &target).ok().map(|_| target)
+}
+
+fn restore_options(entry: &LayoutWindow, screens: &[ScreenInfo]) -> ResizeOptions {
+    ResizeOptions {
+        placement: Placement::new(Anchor::Absolute { x: entry.x, y: entry.y }, 0),
+        display: restore_display(entry, screens),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::fake::{FakeWindow, FakeWindowServer};
+    use crate::window_manager::Rect;
+
+    fn desk() -> FakeWindowServer {
+        FakeWindowServer::new()
+            .with_display("Built-in", Rect::new(0, 0, 1512, 982), Rect::new(0, 33, 1512, 949), 2.0)
+            .with_screen("Studio Display", 1512, 0, 2560, 1440)
+            .with_window(FakeWindow::new(1, "Code", "main.rs — framefit", 1600, 40, 1280, 720))
+            .with_window(FakeWindow::new(2, "Safari", "localhost:1420", 2900, 40, 390, 844))
+            .with_window(FakeWindow::new(3, "Terminal", "~ — zsh", 10, 60, 800, 400))
+    }
+
+    fn frames(server: &FakeWindowServer) -> Vec<Rect> {
+        server.list_windows().unwrap().iter().map(WindowInfo::frame).collect()
+    }
+
+    #[test]
+    fn captures_windows_relative_to_their_display() {
+        let server = desk();
+
+        let layout = capture(" Demo ", &server.list_windows().unwrap(), &server.list_screens().unwrap());
+
+        assert_eq!(layout.name, "Demo");
+        assert_eq!(
+            layout.windows[0],
+            LayoutWindow {
+                app_name: "Code".to_string(),
+                title_pattern: Some(r"^main\.rs — framefit$".to_string()),
+                x: 88,
+                y: 40,
+                width: 1280,
+                height: 720,
+                display: Some("Studio Display".to_string()),
+            }
+        );
+        assert_eq!((layout.windows[2].x, layout.windows[2].y), (10, 27));
+        assert!(validate(&layout).is_ok());
+    }
+
+    #[test]
+    fn restores_geometry_and_stacking_order() {
+        let server = desk();
+        let layout = capture("Demo", &server.list_windows().unwrap(), &server.list_screens().unwrap());
+        let captured = frames(&server);
+
+        for id in 1..=3 {
+            let window = server.window(id).unwrap();
+            resize_window(&server, &window, 500, 500, &ResizeOptions::default()).unwrap();
+        }
+        server.focus_window(&server.window(3).unwrap()).unwrap();
+
+        let outcome = restore(&server, &layout).unwrap();
+
+        assert_eq!(outcome.restored.len(), 3);
+        assert!(outcome.skipped.is_empty());
+        assert_eq!(frames(&server), captured);
+        assert_eq!(server.list_windows().unwrap()[0].id, 1);
+    }
+
+    #[test]
+    fn skips_windows_that_are_not_open() {
+        let server = desk();
+        let mut layout = capture("Demo", &server.list_windows().unwrap(), &server.list_screens().unwrap());
+        layout.windows[1].title_pattern = Some("^Storybook".to_string());
+        layout.windows[2].display = Some("Projector".to_string());
+
+        let outcome = restore(&server, &layout).unwrap();
+
+        assert_eq!(outcome.restored.len(), 2);
+        assert_eq!(outcome.skipped[0].app_name, "Safari");
+        assert_eq!(outcome.skipped[0].error.code(), "NO_MATCHING_WINDOW");
+        assert_eq!(outcome.restored[1].warnings, ["Display Projector is gone, kept Terminal on its display"]);
+        assert_eq!(server.window(3).unwrap().frame(), Rect::new(10, 60, 800, 400));
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        let server = desk();
+        let mut layout = capture("Demo", &server.list_windows().unwrap(), &server.list_screens().unwrap());
+        layout.windows[0].title_pattern = Some("(unclosed".to_string());
+
+        assert!(validate(&layout).unwrap_err().starts_with("Invalid title pattern for Code"));
+        assert!(restore(&server, &layout).is_err());
+        assert!(validate(&Layout { name: "Empty".to_string(), windows: Vec::new() }).is_err());
+    }
+}
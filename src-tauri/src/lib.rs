@@ -1,10 +1,39 @@
-mod window_manager;
-use tauri::{TitleBarStyle, WebviewUrl, WebviewWindowBuilder, Manager, Emitter};
+pub mod cli;
+#[cfg(unix)]
+pub mod control;
+pub mod deep_link;
+mod devices;
+pub mod error;
+mod history;
+mod hotkeys;
+mod importers;
+mod layouts;
+pub mod placement;
+pub mod presets;
+mod rules;
+mod watcher;
+pub mod window_manager;
+use std::sync::Mutex;
+use tauri::{TitleBarStyle, WebviewUrl, WebviewWindowBuilder, Manager, Emitter, State};
 use tauri::tray::TrayIconBuilder;
 use tauri::menu::{MenuBuilder, SubmenuBuilder};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
 use tauri_plugin_store::StoreExt;
-use window_manager::{get_frontmost_window, list_windows, resize_window, resize_window_by_id, check_accessibility_permissions, WindowInfo, ResizeRequest};
+use deep_link::DeepLink;
+use devices::{Device, DeviceFilters};
+use error::FrameFitError;
+use history::{HistoryEntry, HistoryState, ResizeHistory};
+use hotkeys::{HotkeyAction, HotkeyBinding, HotkeyConflict};
+use importers::{ImportFormat, ImportReport};
+use layouts::{Layout, LayoutOutcome};
+use placement::Placement;
+use presets::{Preset, STORE_FILE};
+use rules::{Rule, RuleMatch, RuleRunner, RuleSet, RulesConfig};
+use watcher::{WatcherHandle, WindowChange};
+use window_manager::{detect_backend, place_window, resize_window, DisplayTarget, OversizePolicy, ResizeOptions, ResizeOutcome, ScreenInfo, SizeMode, SharedBackend, WindowInfo, ResizeRequest};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 // State to store the current tray icon (kept for future tray state management)
 #[allow(dead_code)]
@@ -15,131 +44,869 @@ struct TrayState {
 
 const TRAY_ID: &str = "main_tray";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Preset {
-    pub name: String,
-    pub width: i32,
-    pub height: i32,
+/// The default presets followed by the custom ones from `presets.json`, or
+/// just the default ones when those can't be read.
+fn load_presets(app_handle: &tauri::AppHandle) -> Vec<Preset> {
+    let custom_presets = match app_handle.store(STORE_FILE) {
+        Ok(store) => store.get(presets::STORE_KEY),
+        Err(e) => {
+            eprintln!("Failed to open presets store: {}", e);
+            None
+        }
+    };
+    presets::with_custom_presets(custom_presets)
+}
+
+/// The custom presets, for changing them. Fails instead of falling back to
+/// none, so presets saved by a newer FrameFit aren't overwritten.
+fn load_custom_presets(app_handle: &tauri::AppHandle) -> Result<Vec<Preset>, FrameFitError> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| FrameFitError::backend(format!("Failed to open presets store: {}", e)))?;
+
+    match store.get(presets::STORE_KEY) {
+        Some(value) => presets::migrate(value)
+            .map(|migrated| migrated.presets)
+            .map_err(|e| FrameFitError::backend(format!("Failed to read custom presets: {}", e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn save_custom_presets(app_handle: &tauri::AppHandle, custom_presets: &[Preset]) -> Result<(), FrameFitError> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| FrameFitError::backend(format!("Failed to open presets store: {}", e)))?;
+    store.set(presets::STORE_KEY, presets::to_store_value(custom_presets));
+    store
+        .save()
+        .map_err(|e| FrameFitError::backend(format!("Failed to save presets: {}", e)))
+}
+
+/// Applies `change` to the custom presets and saves them, then updates what
+/// is built from them: the tray menu and the hotkeys bound to presets.
+fn change_custom_presets<T>(
+    app_handle: &tauri::AppHandle,
+    change: impl FnOnce(&mut Vec<Preset>) -> Result<T, FrameFitError>,
+) -> Result<T, FrameFitError> {
+    let mut custom_presets = load_custom_presets(app_handle)?;
+    let result = change(&mut custom_presets)?;
+    save_custom_presets(app_handle, &custom_presets)?;
+
+    if let Err(e) = rebuild_tray_menu(app_handle) {
+        eprintln!("Failed to rebuild tray menu: {}", e);
+    }
+    register_hotkeys(app_handle, &load_hotkeys(app_handle));
+    Ok(result)
+}
+
+/// Saves custom presets from older versions in the current format, so they
+/// keep their ids. Presets from newer versions are left alone.
+fn migrate_stored_presets(app_handle: &tauri::AppHandle) {
+    let Some(value) = app_handle.store(STORE_FILE).ok().and_then(|store| store.get(presets::STORE_KEY)) else {
+        return;
+    };
+
+    match presets::migrate(value) {
+        Ok(migrated) if migrated.upgraded => {
+            eprintln!("Upgrading {} custom presets to version {}", migrated.presets.len(), presets::SCHEMA_VERSION);
+            if let Err(e) = save_custom_presets(app_handle, &migrated.presets) {
+                eprintln!("{}", e);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Not migrating custom presets: {}", e),
+    }
+}
+
+/// The saved hotkeys. Bindings to preset names from older versions are
+/// saved again with preset ids.
+fn load_hotkeys(app_handle: &tauri::AppHandle) -> Vec<HotkeyBinding> {
+    let Ok(store) = app_handle.store(STORE_FILE) else {
+        return Vec::new();
+    };
+
+    match store.get("hotkeys").map(|value| hotkeys::migrate(value, &load_presets(app_handle))) {
+        Some(Ok(migrated)) => {
+            if migrated.upgraded {
+                if let Err(e) = save_hotkeys(app_handle, &migrated.bindings) {
+                    eprintln!("{}", e);
+                }
+            }
+            migrated.bindings
+        }
+        Some(Err(e)) => {
+            eprintln!("Ignoring invalid hotkeys: {}", e);
+            Vec::new()
+        }
+        None => Vec::new(),
+    }
+}
+
+fn save_hotkeys(app_handle: &tauri::AppHandle, bindings: &[HotkeyBinding]) -> Result<(), FrameFitError> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| FrameFitError::backend(format!("Failed to open presets store: {}", e)))?;
+    store.set("hotkeys", serde_json::to_value(bindings).unwrap());
+    store
+        .save()
+        .map_err(|e| FrameFitError::backend(format!("Failed to save hotkeys: {}", e)))
+}
+
+fn load_layouts(app_handle: &tauri::AppHandle) -> Vec<Layout> {
+    let Ok(store) = app_handle.store(STORE_FILE) else {
+        return Vec::new();
+    };
+
+    match store.get(layouts::STORE_KEY).map(serde_json::from_value::<Vec<Layout>>) {
+        Some(Ok(layouts)) => layouts,
+        Some(Err(e)) => {
+            eprintln!("Ignoring invalid layouts: {}", e);
+            Vec::new()
+        }
+        None => Vec::new(),
+    }
+}
+
+fn save_layouts(app_handle: &tauri::AppHandle, layouts: &[Layout]) -> Result<(), FrameFitError> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| FrameFitError::backend(format!("Failed to open presets store: {}", e)))?;
+    store.set(layouts::STORE_KEY, serde_json::to_value(layouts).unwrap());
+    store
+        .save()
+        .map_err(|e| FrameFitError::backend(format!("Failed to save layouts: {}", e)))
+}
+
+/// The saved rules. Rules naming their preset, from older versions or
+/// written by hand, are saved again with preset ids.
+fn load_rules(app_handle: &tauri::AppHandle) -> RulesConfig {
+    let Ok(store) = app_handle.store(STORE_FILE) else {
+        return RulesConfig::default();
+    };
+
+    match store.get(rules::STORE_KEY).map(|value| rules::migrate(value, &load_presets(app_handle))) {
+        Some(Ok(migrated)) => {
+            if migrated.upgraded {
+                if let Err(e) = save_rules(app_handle, &migrated.config) {
+                    eprintln!("{}", e);
+                }
+            }
+            migrated.config
+        }
+        Some(Err(e)) => {
+            eprintln!("Ignoring invalid rules: {}", e);
+            RulesConfig::default()
+        }
+        None => RulesConfig::default(),
+    }
+}
+
+fn save_rules(app_handle: &tauri::AppHandle, config: &RulesConfig) -> Result<(), FrameFitError> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| FrameFitError::backend(format!("Failed to open presets store: {}", e)))?;
+    store.set(rules::STORE_KEY, serde_json::to_value(config).unwrap());
+    store
+        .save()
+        .map_err(|e| FrameFitError::backend(format!("Failed to save rules: {}", e)))
+}
+
+fn load_watch_interval(app_handle: &tauri::AppHandle) -> std::time::Duration {
+    let interval_ms = app_handle
+        .store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(watcher::STORE_KEY))
+        .and_then(|value| value.as_u64());
+    interval_ms.map_or(watcher::DEFAULT_INTERVAL, std::time::Duration::from_millis)
+}
+
+/// Payload of the `preset_applied` event, for presets applied from the tray
+/// menu or a link, so the UI can show the result.
+#[derive(Clone, Serialize)]
+struct PresetApplied {
+    preset: Preset,
+    outcome: Option<ResizeOutcome>,
+    error: Option<FrameFitError>,
+}
+
+/// Resizes the frontmost window and records it for undo.
+fn apply_size(
+    app_handle: &tauri::AppHandle,
+    width: i32,
+    height: i32,
+    placement: Placement,
+) -> Result<ResizeOutcome, FrameFitError> {
+    let backend = app_handle.state::<SharedBackend>();
+    let options = ResizeOptions { placement, ..Default::default() };
+    history::resize_frontmost(&**backend, &app_handle.state::<ResizeHistory>(), width, height, &options)
+}
+
+/// Resizes the frontmost window to `preset`, centered unless the preset has
+/// a placement, like the hotkeys do.
+fn apply_preset(app_handle: &tauri::AppHandle, preset: &Preset) -> Result<ResizeOutcome, FrameFitError> {
+    apply_size(app_handle, preset.width, preset.height, preset.placement.unwrap_or(Placement::CENTER))
+}
+
+/// Applies a preset without going through the UI, which may be hidden, and
+/// reports the result in a `preset_applied` event.
+fn apply_preset_and_report(app_handle: &tauri::AppHandle, preset: &Preset) {
+    let (outcome, error) = match apply_preset(app_handle, preset) {
+        Ok(outcome) => (Some(outcome), None),
+        Err(e) => {
+            eprintln!("Failed to apply preset {}: {}", preset.name, e);
+            (None, Some(e))
+        }
+    };
+    let _ = app_handle.emit("preset_applied", PresetApplied { preset: preset.clone(), outcome, error });
+}
+
+/// Payload of the `deep_link` event, reporting size links, which the
+/// backend applies like the tray menu does, and links that failed.
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum DeepLinkEvent {
+    Resized { outcome: ResizeOutcome },
+    Error { url: String, message: String },
+}
+
+/// Links that arrived before the UI was listening, e.g. the one that
+/// launched the app. `None` once the UI is ready.
+struct PendingDeepLinks(Mutex<Option<Vec<String>>>);
+
+fn handle_deep_link(app_handle: &tauri::AppHandle, url: &str) {
+    if let Some(pending) = app_handle.state::<PendingDeepLinks>().0.lock().unwrap().as_mut() {
+        pending.push(url.to_string());
+        return;
+    }
+
+    let event = match deep_link::parse(url) {
+        Ok(DeepLink::Preset { name }) => {
+            let presets = load_presets(app_handle);
+            match presets::find(&presets, &name) {
+                Some(preset) => return apply_preset_and_report(app_handle, preset),
+                None => DeepLinkEvent::Error { url: url.to_string(), message: format!("Unknown preset '{}'", name) },
+            }
+        }
+        Ok(DeepLink::Resize { width, height, placement }) => match apply_size(app_handle, width, height, placement) {
+            Ok(outcome) => DeepLinkEvent::Resized { outcome },
+            Err(e) => DeepLinkEvent::Error { url: url.to_string(), message: e.to_string() },
+        },
+        Err(e) => DeepLinkEvent::Error { url: url.to_string(), message: e.to_string() },
+    };
+
+    if let DeepLinkEvent::Error { message, .. } = &event {
+        eprintln!("Ignoring link {}: {}", url, message);
+    }
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.emit("deep_link", event);
+    }
+}
+
+/// The saved rules, read by the window watcher on every poll.
+struct ActiveRules(Mutex<RulesConfig>);
+
+/// Payload of the `rule_matched` event. In dry runs nothing is resized, so
+/// there's neither an outcome nor an error.
+#[derive(Clone, Serialize)]
+struct RuleResult {
+    #[serde(flatten)]
+    rule_match: RuleMatch,
+    dry_run: bool,
+    outcome: Option<ResizeOutcome>,
+    error: Option<FrameFitError>,
+}
+
+/// Applies the rules to the windows the watcher saw open.
+fn run_rules(app_handle: &tauri::AppHandle, runner: &mut RuleRunner, opened: &[WindowInfo], windows: &[WindowInfo]) {
+    let config = app_handle.state::<ActiveRules>().0.lock().unwrap().clone();
+    let rules = RuleSet::new(&config.rules);
+    if rules.is_empty() {
+        return;
+    }
+
+    let backend = app_handle.state::<SharedBackend>();
+    let screens = match backend.list_screens() {
+        Ok(screens) => screens,
+        Err(e) => {
+            eprintln!("Skipping rules, can't list screens: {}", e);
+            return;
+        }
+    };
+
+    for rule_match in runner.poll(&rules, opened, windows, &screens, &load_presets(app_handle)) {
+        let (outcome, error) = if config.dry_run {
+            eprintln!("Rule {} matches {} (dry run)", rule_match.rule, rule_match.window.title);
+            (None, None)
+        } else {
+            let options = ResizeOptions { placement: rule_match.placement, ..Default::default() };
+            let size = rule_match.size;
+            match resize_window(&**backend, &rule_match.window, size.width, size.height, &options) {
+                Ok(outcome) => {
+                    app_handle.state::<ResizeHistory>().record(&outcome);
+                    (Some(outcome), None)
+                }
+                Err(e) => {
+                    eprintln!("Rule {} failed for window {}: {}", rule_match.rule, rule_match.window.id, e);
+                    (None, Some(e))
+                }
+            }
+        };
+
+        let _ = app_handle.emit("rule_matched", RuleResult { rule_match, dry_run: config.dry_run, outcome, error });
+    }
+}
+
+/// Payload of the `windows_changed` event, with the full list so the UI
+/// doesn't have to apply the changes itself.
+#[derive(Clone, Serialize)]
+struct WindowsChanged<'a> {
+    changes: &'a [WindowChange],
+    windows: &'a [WindowInfo],
+}
+
+/// Whether anything uses the window changes: the UI while it's shown, or
+/// enabled rules. Otherwise the watcher doesn't poll, as listing windows
+/// isn't free, e.g. a script round trip on KWin.
+fn windows_watched(app_handle: &tauri::AppHandle) -> bool {
+    let visible = app_handle
+        .get_webview_window("main")
+        .is_some_and(|window| window.is_visible().unwrap_or(false));
+    visible || app_handle.state::<ActiveRules>().0.lock().unwrap().rules.iter().any(|rule| rule.enabled)
+}
+
+fn on_windows_polled(
+    app_handle: &tauri::AppHandle,
+    runner: &mut RuleRunner,
+    changes: &[WindowChange],
+    windows: &[WindowInfo],
+) {
+    if !changes.is_empty() {
+        let _ = app_handle.emit("windows_changed", WindowsChanged { changes, windows });
+    }
+
+    let opened: Vec<WindowInfo> = changes
+        .iter()
+        .filter_map(|change| match change {
+            WindowChange::Added { after } => Some(after.clone()),
+            _ => None,
+        })
+        .collect();
+    run_rules(app_handle, runner, &opened, windows);
+}
+
+/// Index of the preset applied last by a hotkey, for cycling.
+#[derive(Default)]
+struct PresetCycle(Mutex<Option<usize>>);
+
+/// Payload of the `hotkey_action` event, so the UI can report the result.
+#[derive(Clone, Serialize)]
+struct HotkeyResult {
+    #[serde(flatten)]
+    action: HotkeyAction,
+    outcome: Option<ResizeOutcome>,
+    error: Option<FrameFitError>,
+}
+
+/// (Re)registers the hotkeys saved in `presets.json` and returns the ones
+/// that couldn't be registered.
+fn register_hotkeys(app_handle: &tauri::AppHandle, bindings: &[HotkeyBinding]) -> Vec<HotkeyConflict> {
+    let preset_ids: Vec<Uuid> = load_presets(app_handle).iter().map(|preset| preset.id).collect();
+    let (resolved, mut conflicts) = hotkeys::resolve(bindings, &preset_ids);
+
+    let global_shortcut = app_handle.global_shortcut();
+    if let Err(e) = global_shortcut.unregister_all() {
+        eprintln!("Failed to unregister hotkeys: {}", e);
+    }
+
+    for (shortcut, action) in resolved {
+        let result = global_shortcut.on_shortcut(shortcut, move |app_handle, _shortcut, event| {
+            if event.state != ShortcutState::Pressed {
+                return;
+            }
+
+            // Resizing can take a while, keep it off the event loop
+            let app_handle = app_handle.clone();
+            let action = action.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                let result = run_hotkey_action(&app_handle, &action);
+                if let Err(e) = &result {
+                    eprintln!("Hotkey action {:?} failed: {}", action, e);
+                }
+
+                let (outcome, error) = match result {
+                    Ok(outcome) => (outcome, None),
+                    Err(e) => (None, Some(e)),
+                };
+                let _ = app_handle.emit("hotkey_action", HotkeyResult { action, outcome, error });
+            });
+        });
+
+        if let Err(e) = result {
+            conflicts.push(HotkeyConflict::Unavailable {
+                shortcut: shortcut.into_string(),
+                message: e.to_string(),
+            });
+        }
+    }
+
+    for conflict in &conflicts {
+        eprintln!("Skipping hotkey: {:?}", conflict);
+    }
+    conflicts
+}
+
+fn run_hotkey_action(app_handle: &tauri::AppHandle, action: &HotkeyAction) -> Result<Option<ResizeOutcome>, FrameFitError> {
+    let backend = app_handle.state::<SharedBackend>();
+    let history = app_handle.state::<ResizeHistory>();
+    let presets = load_presets(app_handle);
+    let mut cycle = app_handle.state::<PresetCycle>().0.lock().unwrap();
+
+    let index = match action {
+        HotkeyAction::Preset { id } => presets.iter().position(|preset| preset.id == *id),
+        HotkeyAction::CyclePresets => Some(cycle.map_or(0, |index| (index + 1) % presets.len())),
+        HotkeyAction::CenterFrontmost => {
+            let window = backend.get_frontmost_window()?;
+            let options = ResizeOptions { placement: Placement::CENTER, ..Default::default() };
+            let outcome = place_window(&**backend, &window, &options)?;
+            history.record(&outcome);
+            return Ok(Some(outcome));
+        }
+        HotkeyAction::UndoLastResize => {
+            history::undo(&**backend, &history)?;
+            return Ok(None);
+        }
+        HotkeyAction::RedoResize => {
+            history::redo(&**backend, &history)?;
+            return Ok(None);
+        }
+        HotkeyAction::RestoreOriginal => {
+            let window = backend.get_frontmost_window()?;
+            history::restore_original(&**backend, &history, Some(window.id))?;
+            return Ok(None);
+        }
+    };
+
+    // A preset that was deleted since the hotkeys were registered
+    let Some(preset) = index.and_then(|index| presets.get(index)) else {
+        return Ok(None);
+    };
+
+    let outcome = apply_preset(app_handle, preset)?;
+    *cycle = index;
+
+    Ok(Some(outcome))
+}
+
+/// Serves the control socket with the app's backend, presets and undo.
+#[cfg(unix)]
+struct ControlContext(tauri::AppHandle);
+
+#[cfg(unix)]
+impl control::Context for ControlContext {
+    fn backend(&self) -> &dyn window_manager::WindowBackend {
+        &**self.0.state::<SharedBackend>().inner()
+    }
+
+    fn presets(&self) -> Vec<Preset> {
+        load_presets(&self.0)
+    }
+
+    fn resized(&self, outcome: &ResizeOutcome) {
+        self.0.state::<ResizeHistory>().record(outcome);
+    }
 }
 
 #[tauri::command]
-fn get_windows() -> Result<Vec<WindowInfo>, String> {
-    list_windows()
+fn get_windows(backend: State<'_, SharedBackend>) -> Result<Vec<WindowInfo>, FrameFitError> {
+    backend.list_windows()
 }
 
 #[tauri::command]
-fn resize_frontmost_window(width: i32, height: i32, center: bool) -> Result<(), String> {
-    let window_info = get_frontmost_window()?;
-    resize_window(&window_info, width, height, center)
+fn get_screens(backend: State<'_, SharedBackend>) -> Result<Vec<ScreenInfo>, FrameFitError> {
+    backend.list_screens()
 }
 
 #[tauri::command]
-fn resize_specific_window(window_id: u32, width: i32, height: i32, center: bool) -> Result<(), String> {
-    eprintln!("Resizing window ID: {} to {}x{}, center: {}", window_id, width, height, center);
+fn resize_frontmost_window(
+    backend: State<'_, SharedBackend>,
+    history: State<'_, ResizeHistory>,
+    width: i32,
+    height: i32,
+    placement: Placement,
+    display: Option<DisplayTarget>,
+    oversize: Option<OversizePolicy>,
+    size_mode: Option<SizeMode>,
+) -> Result<ResizeOutcome, FrameFitError> {
+    let options = ResizeOptions {
+        placement,
+        display,
+        oversize: oversize.unwrap_or_default(),
+        size_mode: size_mode.unwrap_or_default(),
+    };
+    history::resize_frontmost(&**backend, &history, width, height, &options)
+}
+
+#[tauri::command]
+fn resize_specific_window(
+    backend: State<'_, SharedBackend>,
+    history: State<'_, ResizeHistory>,
+    window_id: u32,
+    width: i32,
+    height: i32,
+    placement: Placement,
+    display: Option<DisplayTarget>,
+    oversize: Option<OversizePolicy>,
+    size_mode: Option<SizeMode>,
+) -> Result<ResizeOutcome, FrameFitError> {
     let request = ResizeRequest {
         window_id,
         width,
         height,
     };
-    resize_window_by_id(&request, center)
+    let options = ResizeOptions {
+        placement,
+        display,
+        oversize: oversize.unwrap_or_default(),
+        size_mode: size_mode.unwrap_or_default(),
+    };
+    history::resize_window(&**backend, &history, &request, &options)
+}
+
+#[tauri::command]
+fn undo_last_resize(
+    backend: State<'_, SharedBackend>,
+    history: State<'_, ResizeHistory>,
+) -> Result<Option<HistoryEntry>, FrameFitError> {
+    history::undo(&**backend, &history)
+}
+
+#[tauri::command]
+fn redo_resize(
+    backend: State<'_, SharedBackend>,
+    history: State<'_, ResizeHistory>,
+) -> Result<Option<HistoryEntry>, FrameFitError> {
+    history::redo(&**backend, &history)
+}
+
+/// Returns a window, by default the one changed last, to the size and
+/// position it had before FrameFit first resized it.
+#[tauri::command]
+fn restore_original_size(
+    backend: State<'_, SharedBackend>,
+    history: State<'_, ResizeHistory>,
+    window_id: Option<u32>,
+) -> Result<Option<HistoryEntry>, FrameFitError> {
+    history::restore_original(&**backend, &history, window_id)
+}
+
+#[tauri::command]
+fn get_history_state(history: State<'_, ResizeHistory>) -> HistoryState {
+    history.state()
+}
+
+#[tauri::command]
+fn check_permissions(backend: State<'_, SharedBackend>) -> bool {
+    backend.check_permissions()
 }
 
 #[tauri::command]
-fn check_permissions() -> bool {
-    check_accessibility_permissions()
+fn get_hotkeys(app_handle: tauri::AppHandle) -> Vec<HotkeyBinding> {
+    load_hotkeys(&app_handle)
 }
 
+/// Saves and registers `bindings`. Bindings that conflict with each other or
+/// refer to unknown presets are returned without saving anything.
 #[tauri::command]
-async fn rebuild_tray_menu(app_handle: tauri::AppHandle, custom_presets: Vec<Preset>) -> Result<(), String> {
+fn set_hotkeys(app_handle: tauri::AppHandle, bindings: Vec<HotkeyBinding>) -> Result<Vec<HotkeyConflict>, FrameFitError> {
+    let preset_ids: Vec<Uuid> = load_presets(&app_handle).iter().map(|preset| preset.id).collect();
+    let (_, conflicts) = hotkeys::resolve(&bindings, &preset_ids);
+    if !conflicts.is_empty() {
+        return Ok(conflicts);
+    }
+
+    save_hotkeys(&app_handle, &bindings)?;
+    Ok(register_hotkeys(&app_handle, &bindings))
+}
+
+/// The built-in presets followed by the custom ones.
+#[tauri::command]
+fn list_presets(app_handle: tauri::AppHandle) -> Vec<Preset> {
+    load_presets(&app_handle)
+}
+
+/// Adds a custom preset, with a new id unless it has one. Returns all presets.
+#[tauri::command]
+fn add_preset(app_handle: tauri::AppHandle, preset: Preset) -> Result<Vec<Preset>, FrameFitError> {
+    change_custom_presets(&app_handle, |custom_presets| presets::add(custom_presets, preset))?;
+    Ok(load_presets(&app_handle))
+}
+
+/// Replaces the custom preset with the same id. Returns all presets.
+#[tauri::command]
+fn update_preset(app_handle: tauri::AppHandle, preset: Preset) -> Result<Vec<Preset>, FrameFitError> {
+    change_custom_presets(&app_handle, |custom_presets| presets::update(custom_presets, preset))?;
+    Ok(load_presets(&app_handle))
+}
+
+#[tauri::command]
+fn delete_preset(app_handle: tauri::AppHandle, id: Uuid) -> Result<Vec<Preset>, FrameFitError> {
+    change_custom_presets(&app_handle, |custom_presets| presets::delete(custom_presets, id))?;
+    Ok(load_presets(&app_handle))
+}
+
+/// Orders the custom presets by `ids`, which has to list all of them.
+#[tauri::command]
+fn reorder_presets(app_handle: tauri::AppHandle, ids: Vec<Uuid>) -> Result<Vec<Preset>, FrameFitError> {
+    change_custom_presets(&app_handle, |custom_presets| presets::reorder(custom_presets, &ids))?;
+    Ok(load_presets(&app_handle))
+}
+
+/// Deletes all custom presets.
+#[tauri::command]
+fn reset_presets(app_handle: tauri::AppHandle) -> Result<Vec<Preset>, FrameFitError> {
+    change_custom_presets(&app_handle, |custom_presets| {
+        custom_presets.clear();
+        Ok(())
+    })?;
+    Ok(load_presets(&app_handle))
+}
+
+/// Adds the presets in a file exported by FrameFit, DevTools, Playwright,
+/// Responsively or as CSV. The format is detected when not given; presets
+/// that are already there are reported as duplicates.
+#[tauri::command]
+fn import_presets(
+    app_handle: tauri::AppHandle,
+    text: String,
+    format: Option<ImportFormat>,
+) -> Result<ImportReport, FrameFitError> {
+    change_custom_presets(&app_handle, |custom_presets| {
+        let mut existing = presets::default_presets();
+        existing.extend(custom_presets.iter().cloned());
+
+        let report = importers::import(&text, format, &existing).map_err(FrameFitError::backend)?;
+        custom_presets.extend(report.presets.iter().cloned());
+        Ok(report)
+    })
+}
+
+/// Writes the custom presets to `path`, in the format `import_presets` reads.
+#[tauri::command]
+fn export_presets(app_handle: tauri::AppHandle, path: std::path::PathBuf) -> Result<(), FrameFitError> {
+    let custom_presets = load_custom_presets(&app_handle)?;
+    let json = serde_json::to_string_pretty(&presets::to_store_value(&custom_presets)).unwrap();
+    std::fs::write(&path, json)
+        .map_err(|e| FrameFitError::backend(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Searches the built-in device catalog.
+#[tauri::command]
+fn search_devices(query: String, filters: Option<DeviceFilters>) -> Vec<Device> {
+    devices::search(devices::catalog(), &query, &filters.unwrap_or_default()).into_iter().cloned().collect()
+}
+
+/// A custom preset for a catalog device, for adding it to the presets.
+#[tauri::command]
+fn device_preset(id: String) -> Result<Preset, FrameFitError> {
+    devices::find(devices::catalog(), &id)
+        .map(Device::to_preset)
+        .ok_or_else(|| FrameFitError::backend(format!("Unknown device \"{}\"", id)))
+}
+
+#[tauri::command]
+fn get_layouts(app_handle: tauri::AppHandle) -> Vec<Layout> {
+    load_layouts(&app_handle)
+}
+
+/// Saves the windows with `window_ids`, or all windows, as the layout `name`,
+/// replacing a layout with the same name.
+#[tauri::command]
+fn capture_layout(
+    app_handle: tauri::AppHandle,
+    backend: State<'_, SharedBackend>,
+    name: String,
+    window_ids: Option<Vec<u32>>,
+) -> Result<Layout, FrameFitError> {
+    let mut windows = backend.list_windows()?;
+    if let Some(ids) = window_ids {
+        windows.retain(|window| ids.contains(&window.id));
+    }
+    let layout = layouts::capture(&name, &windows, &backend.list_screens()?);
+    layouts::validate(&layout).map_err(FrameFitError::backend)?;
+
+    let mut saved = load_layouts(&app_handle);
+    saved.retain(|existing| !existing.name.eq_ignore_ascii_case(&layout.name));
+    saved.push(layout.clone());
+    save_layouts(&app_handle, &saved)?;
+
+    Ok(layout)
+}
+
+#[tauri::command]
+fn delete_layout(app_handle: tauri::AppHandle, name: String) -> Result<Vec<Layout>, FrameFitError> {
+    let mut saved = load_layouts(&app_handle);
+    saved.retain(|layout| !layout.name.eq_ignore_ascii_case(&name));
+    save_layouts(&app_handle, &saved)?;
+    Ok(saved)
+}
+
+#[tauri::command]
+fn restore_layout(
+    app_handle: tauri::AppHandle,
+    backend: State<'_, SharedBackend>,
+    history: State<'_, ResizeHistory>,
+    name: String,
+) -> Result<LayoutOutcome, FrameFitError> {
+    let layout = load_layouts(&app_handle)
+        .into_iter()
+        .find(|layout| layout.name.eq_ignore_ascii_case(&name))
+        .ok_or_else(|| FrameFitError::backend(format!("No layout named '{}'", name)))?;
+
+    let outcome = layouts::restore(&**backend, &layout)?;
+    for restored in &outcome.restored {
+        history.record(restored);
+    }
+    Ok(outcome)
+}
+
+#[tauri::command]
+fn get_rules(active: State<'_, ActiveRules>) -> RulesConfig {
+    active.0.lock().unwrap().clone()
+}
+
+/// Saves and activates `config`. When any rule is invalid nothing is saved
+/// and the problems are returned instead.
+#[tauri::command]
+fn set_rules(
+    app_handle: tauri::AppHandle,
+    active: State<'_, ActiveRules>,
+    config: RulesConfig,
+) -> Result<Vec<String>, FrameFitError> {
+    let problems = rules::validate(&config.rules, &load_presets(&app_handle));
+    if !problems.is_empty() {
+        return Ok(problems);
+    }
+
+    save_rules(&app_handle, &config)?;
+    *active.0.lock().unwrap() = config;
+    Ok(Vec::new())
+}
+
+/// What `rules`, or the saved rules, would do to the windows that are open
+/// now. Nothing is resized.
+#[tauri::command]
+fn dry_run_rules(
+    app_handle: tauri::AppHandle,
+    backend: State<'_, SharedBackend>,
+    active: State<'_, ActiveRules>,
+    rules: Option<Vec<Rule>>,
+) -> Result<Vec<RuleMatch>, FrameFitError> {
+    let rules = rules.unwrap_or_else(|| active.0.lock().unwrap().rules.clone());
+    let rule_set = RuleSet::new(&rules);
+    Ok(rule_set.dry_run(&backend.list_windows()?, &backend.list_screens()?, &load_presets(&app_handle)))
+}
+
+/// The polling interval of the window watcher, in milliseconds.
+#[tauri::command]
+fn get_watch_interval(handle: State<'_, WatcherHandle>) -> u64 {
+    handle.interval().as_millis() as u64
+}
+
+/// Changes and saves the polling interval, returning the one used after
+/// clamping.
+#[tauri::command]
+fn set_watch_interval(
+    app_handle: tauri::AppHandle,
+    handle: State<'_, WatcherHandle>,
+    interval_ms: u64,
+) -> Result<u64, FrameFitError> {
+    let interval_ms = handle.set_interval(std::time::Duration::from_millis(interval_ms)).as_millis() as u64;
+
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| FrameFitError::backend(format!("Failed to open presets store: {}", e)))?;
+    store.set(watcher::STORE_KEY, interval_ms);
+    store
+        .save()
+        .map_err(|e| FrameFitError::backend(format!("Failed to save the watch interval: {}", e)))?;
+
+    Ok(interval_ms)
+}
+
+/// Called once the UI listens for link events, handles the links that
+/// arrived before.
+#[tauri::command]
+fn deep_links_ready(app_handle: tauri::AppHandle) {
+    let pending = app_handle.state::<PendingDeepLinks>().0.lock().unwrap().take();
+    for url in pending.unwrap_or_default() {
+        handle_deep_link(&app_handle, &url);
+    }
+}
+
+/// Registers the saved hotkeys again, e.g. after presets were deleted.
+#[tauri::command]
+fn reload_hotkeys(app_handle: tauri::AppHandle) -> Vec<HotkeyConflict> {
+    register_hotkeys(&app_handle, &load_hotkeys(&app_handle))
+}
+
+/// The tray menu: the presets grouped by category, then the window and quit items.
+#[cfg(target_os = "macos")]
+fn build_tray_menu<R: tauri::Runtime, M: Manager<R>>(
+    manager: &M,
+    presets: &[Preset],
+) -> tauri::Result<tauri::menu::Submenu<R>> {
+    let mut presets_menu = SubmenuBuilder::new(manager, "Presets");
+    for (index, preset) in presets.iter().enumerate() {
+        if index > 0 && presets[index - 1].category != preset.category {
+            presets_menu = presets_menu.separator();
+        }
+        let label = format!("{} ({}×{})", preset.name, preset.width, preset.height);
+        let item = tauri::menu::MenuItem::with_id(manager, preset.menu_id(), &label, true, None::<&str>)?;
+        presets_menu = presets_menu.item(&item);
+    }
+    let presets_menu = presets_menu.build()?;
+
+    let show_window_item = tauri::menu::MenuItem::with_id(manager, "show_window", "Show Window", true, None::<&str>)?;
+    let quit_item = tauri::menu::MenuItem::with_id(manager, "quit_tray", "Quit", true, None::<&str>)?;
+
+    SubmenuBuilder::new(manager, "FrameFit")
+        .item(&presets_menu)
+        .separator()
+        .item(&show_window_item)
+        .item(&quit_item)
+        .build()
+}
+
+/// Rebuilds the tray menu from the saved presets.
+fn rebuild_tray_menu(app_handle: &tauri::AppHandle) -> Result<(), String> {
     #[cfg(target_os = "macos")]
     {
-        eprintln!("Rebuilding tray menu with {} custom presets", custom_presets.len());
-        
-        // Get the existing tray
         let tray = app_handle.tray_by_id(TRAY_ID)
             .ok_or("Tray not found")?;
-        
-        // Build default presets submenu with consistent IDs
-        let preset_iphone_se = tauri::menu::MenuItem::with_id(&app_handle, "preset_iphone_se", "iPhone SE (375×667)", true, None::<&str>)
-            .map_err(|e| e.to_string())?;
-        let preset_iphone_14 = tauri::menu::MenuItem::with_id(&app_handle, "preset_iphone_14", "iPhone 14 (390×844)", true, None::<&str>)
-            .map_err(|e| e.to_string())?;
-        let preset_ipad = tauri::menu::MenuItem::with_id(&app_handle, "preset_ipad", "iPad (768×1024)", true, None::<&str>)
-            .map_err(|e| e.to_string())?;
-        let preset_hd = tauri::menu::MenuItem::with_id(&app_handle, "preset_hd", "HD (1280×720)", true, None::<&str>)
-            .map_err(|e| e.to_string())?;
-        let preset_fhd = tauri::menu::MenuItem::with_id(&app_handle, "preset_fhd", "FHD (1920×1080)", true, None::<&str>)
-            .map_err(|e| e.to_string())?;
-
-        // Build custom presets with consistent IDs based on preset name
-        let mut custom_items = Vec::new();
-        for preset in custom_presets.iter() {
-            let label = format!("{} ({}×{})", preset.name, preset.width, preset.height);
-            let safe_id = format!("custom_preset_{}", preset.name.to_lowercase().replace(" ", "_"));
-            eprintln!("Adding custom preset: {} with id: {}", label, safe_id);
-            let item = tauri::menu::MenuItem::with_id(&app_handle, &safe_id, &label, true, None::<&str>)
-                .map_err(|e| e.to_string())?;
-            custom_items.push(item);
-        }
-
-        // Build presets submenu
-        let presets_menu = if custom_items.is_empty() {
-            SubmenuBuilder::new(&app_handle, "Presets")
-                .item(&preset_iphone_se)
-                .item(&preset_iphone_14)
-                .item(&preset_ipad)
-                .item(&preset_hd)
-                .item(&preset_fhd)
-                .build()
-                .map_err(|e| e.to_string())?
-        } else {
-            let mut builder = SubmenuBuilder::new(&app_handle, "Presets")
-                .item(&preset_iphone_se)
-                .item(&preset_iphone_14)
-                .item(&preset_ipad)
-                .item(&preset_hd)
-                .item(&preset_fhd)
-                .separator();
-            for item in &custom_items {
-                builder = builder.item(item);
-            }
-            builder.build().map_err(|e| e.to_string())?
-        };
-
-        let show_window_item = tauri::menu::MenuItem::with_id(&app_handle, "show_window", "Show Window", true, None::<&str>)
-            .map_err(|e| e.to_string())?;
-        let quit_item = tauri::menu::MenuItem::with_id(&app_handle, "quit_tray", "Quit", true, None::<&str>)
-            .map_err(|e| e.to_string())?;
-
-        // Build new tray menu
-        let tray_menu = SubmenuBuilder::new(&app_handle, "FrameFit")
-            .item(&presets_menu)
-            .separator()
-            .item(&show_window_item)
-            .item(&quit_item)
-            .build()
-            .map_err(|e| e.to_string())?;
-
-        // Update the tray menu
+        let tray_menu = build_tray_menu(app_handle, &load_presets(app_handle)).map_err(|e| e.to_string())?;
         tray.set_menu(Some(tray_menu))
             .map_err(|e| format!("Failed to update tray menu: {}", e))?;
-
-        eprintln!("Successfully updated tray menu");
-        Ok(())
     }
 
     #[cfg(not(target_os = "macos"))]
-    {
-        Ok(())
-    }
+    let _ = app_handle;
+
+    Ok(())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        // Must come first; forwards links opened while running to the deep link plugin
+        .plugin(tauri_plugin_single_instance::init(|_app_handle, _args, _cwd| {}))
+        .plugin(tauri_plugin_deep_link::init())
         .manage(TrayState {
             tray_id: None,
         })
+        .manage(detect_backend())
+        .manage(ResizeHistory::default())
+        .manage(PresetCycle::default())
+        .manage(PendingDeepLinks(Mutex::new(Some(Vec::new()))))
         .on_menu_event(|app_handle, event| {
             match event.id.0.as_str() {
                 "quit" | "quit_tray" => std::process::exit(0),
@@ -149,17 +916,21 @@ pub fn run() {
                         let _ = window.set_focus();
                     }
                 }
-                // Preset handlers - emit any preset menu action to frontend
                 id => {
-                    if id.starts_with("preset_") || id.starts_with("custom_preset_") {
-                        if let Some(window) = app_handle.get_webview_window("main") {
-                            let _ = window.emit("tray_menu_action", id);
-                        }
+                    let Some(preset_id) = presets::id_from_menu_id(id) else {
+                        return;
+                    };
+                    match load_presets(app_handle).iter().find(|preset| preset.id == preset_id) {
+                        Some(preset) => apply_preset_and_report(app_handle, preset),
+                        // The menu is rebuilt on every change, so this is a race at most
+                        None => eprintln!("Tray menu preset {} no longer exists", preset_id),
                     }
                 }
             }
         })
         .setup(|app| {
+            migrate_stored_presets(app.handle());
+
             // Build menu only on macOS
             #[cfg(target_os = "macos")]
             {
@@ -189,67 +960,7 @@ pub fn run() {
 
                 app.set_menu(menu)?;
 
-                // Create tray menu with presets - build dynamically based on custom presets
-                // Get custom presets from store
-                let mut custom_presets = Vec::new();
-                
-                if let Ok(store_instance) = app.store("presets.json") {
-                    if let Some(presets_value) = store_instance.get("customPresets") {
-                        if let Ok(presets) = serde_json::from_value::<Vec<Preset>>(presets_value) {
-                            custom_presets = presets;
-                        }
-                    }
-                }
-
-                // Build default presets submenu
-                let preset_iphone_se = tauri::menu::MenuItem::with_id(app, "preset_iphone_se", "iPhone SE (375×667)", true, None::<&str>)?;
-                let preset_iphone_14 = tauri::menu::MenuItem::with_id(app, "preset_iphone_14", "iPhone 14 (390×844)", true, None::<&str>)?;
-                let preset_ipad = tauri::menu::MenuItem::with_id(app, "preset_ipad", "iPad (768×1024)", true, None::<&str>)?;
-                let preset_hd = tauri::menu::MenuItem::with_id(app, "preset_hd", "HD (1280×720)", true, None::<&str>)?;
-                let preset_fhd = tauri::menu::MenuItem::with_id(app, "preset_fhd", "FHD (1920×1080)", true, None::<&str>)?;
-
-                // Build custom presets
-                let mut custom_items = Vec::new();
-                for preset in custom_presets.iter() {
-                    let label = format!("{} ({}×{})", preset.name, preset.width, preset.height);
-                    let safe_id = format!("custom_preset_{}", preset.name.to_lowercase().replace(" ", "_"));
-                    let item = tauri::menu::MenuItem::with_id(app, &safe_id, &label, true, None::<&str>)?;
-                    custom_items.push(item);
-                }
-
-                // Build presets submenu
-                let presets_menu = if custom_items.is_empty() {
-                    SubmenuBuilder::new(app, "Presets")
-                        .item(&preset_iphone_se)
-                        .item(&preset_iphone_14)
-                        .item(&preset_ipad)
-                        .item(&preset_hd)
-                        .item(&preset_fhd)
-                        .build()?
-                } else {
-                    let mut builder = SubmenuBuilder::new(app, "Presets")
-                        .item(&preset_iphone_se)
-                        .item(&preset_iphone_14)
-                        .item(&preset_ipad)
-                        .item(&preset_hd)
-                        .item(&preset_fhd)
-                        .separator();
-                    for item in &custom_items {
-                        builder = builder.item(item);
-                    }
-                    builder.build()?
-                };
-
-                let show_window_item = tauri::menu::MenuItem::with_id(app, "show_window", "Show Window", true, None::<&str>)?;
-                let quit_item = tauri::menu::MenuItem::with_id(app, "quit_tray", "Quit", true, None::<&str>)?;
-
-                // Build tray menu
-                let tray_menu = SubmenuBuilder::new(app, "FrameFit")
-                    .item(&presets_menu)
-                    .separator()
-                    .item(&show_window_item)
-                    .item(&quit_item)
-                    .build()?;
+                let tray_menu = build_tray_menu(app, &load_presets(app.handle()))?;
 
                 let _tray = TrayIconBuilder::with_id(TRAY_ID)
                     .icon(app.default_window_icon().unwrap().clone())
@@ -257,6 +968,53 @@ pub fn run() {
                     .build(app)?;
             }
 
+            register_hotkeys(app.handle(), &load_hotkeys(app.handle()));
+
+            // Bundles register the scheme on install, dev builds do it here
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            if let Err(e) = app.deep_link().register_all() {
+                eprintln!("Failed to register framefit:// links: {}", e);
+            }
+
+            let app_handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    handle_deep_link(&app_handle, url.as_str());
+                }
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    handle_deep_link(app.handle(), url.as_str());
+                }
+            }
+
+            // Kept in the app state, dropping it closes the socket
+            #[cfg(unix)]
+            {
+                let context = std::sync::Arc::new(ControlContext(app.handle().clone()));
+                let server = control::socket_path().and_then(|path| control::ControlServer::bind(&path, context));
+                match server {
+                    Ok(server) => {
+                        app.manage(server);
+                    }
+                    Err(e) => eprintln!("Control socket unavailable: {}", e),
+                }
+            }
+
+            // Reports window changes to the UI and applies the rules to windows as they open
+            app.manage(ActiveRules(Mutex::new(load_rules(app.handle()))));
+            let app_handle = app.handle().clone();
+            let watched_handle = app.handle().clone();
+            let backend = app.state::<SharedBackend>().inner().clone();
+            let mut runner = RuleRunner::default();
+            let handle = watcher::spawn(
+                backend,
+                load_watch_interval(app.handle()),
+                move || windows_watched(&watched_handle),
+                move |changes, windows| on_windows_polled(&app_handle, &mut runner, changes, windows),
+            );
+            app.manage(handle);
+
             let win_builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::default())
                 .title("FrameFit")
                 .inner_size(600.0, 850.0)
@@ -307,12 +1065,40 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .invoke_handler(tauri::generate_handler![
             get_windows,
+            get_screens,
             resize_frontmost_window,
             resize_specific_window,
+            undo_last_resize,
+            redo_resize,
+            restore_original_size,
+            get_history_state,
             check_permissions,
-            rebuild_tray_menu
+            list_presets,
+            add_preset,
+            update_preset,
+            delete_preset,
+            reorder_presets,
+            reset_presets,
+            import_presets,
+            export_presets,
+            search_devices,
+            device_preset,
+            get_hotkeys,
+            set_hotkeys,
+            reload_hotkeys,
+            deep_links_ready,
+            get_layouts,
+            capture_layout,
+            delete_layout,
+            restore_layout,
+            get_rules,
+            set_rules,
+            dry_run_rules,
+            get_watch_interval,
+            set_watch_interval
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
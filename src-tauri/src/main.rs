@@ -2,5 +2,11 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // `FrameFit list`, `FrameFit resize 390x844`, ... run the CLI instead of the app
+    let args: Vec<String> = std::env::args().collect();
+    if framefit_lib::cli::is_requested(&args) {
+        std::process::exit(framefit_lib::cli::run(args));
+    }
+
     framefit_lib::run()
 }
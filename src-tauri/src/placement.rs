@@ -0,0 +1,190 @@
+use crate::window_manager::{Rect, Size};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Where a window goes after it has been resized.
+///
+/// Shared by the resize commands, presets, the tray menu and the CLI. In JSON
+/// it is an object tagged with `mode`, e.g.
+/// `{ "mode": "bottom-right", "margin": 20 }` or `{ "mode": "absolute", "x": 0, "y": 0 }`.
+/// As a string it is written `bottom-right:20`, `center` or `absolute:100,50`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Placement {
+    #[serde(flatten)]
+    pub anchor: Anchor,
+    /// Distance in pixels kept from the edges of the work area.
+    #[serde(default)]
+    pub margin: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum Anchor {
+    /// Leave the window where it is.
+    #[default]
+    Keep,
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+    /// Relative to the top-left corner of the display's work area.
+    Absolute { x: i32, y: i32 },
+}
+
+const ANCHOR_NAMES: [(&str, Anchor); 10] = [
+    ("keep", Anchor::Keep),
+    ("top-left", Anchor::TopLeft),
+    ("top", Anchor::Top),
+    ("top-right", Anchor::TopRight),
+    ("left", Anchor::Left),
+    ("center", Anchor::Center),
+    ("right", Anchor::Right),
+    ("bottom-left", Anchor::BottomLeft),
+    ("bottom", Anchor::Bottom),
+    ("bottom-right", Anchor::BottomRight),
+];
+
+impl Placement {
+    pub const KEEP: Placement = Placement { anchor: Anchor::Keep, margin: 0 };
+    pub const CENTER: Placement = Placement { anchor: Anchor::Center, margin: 0 };
+
+    pub fn new(anchor: Anchor, margin: i32) -> Self {
+        Self { anchor, margin }
+    }
+
+    /// Maps the old `center` flag of the resize commands.
+    pub fn from_center(center: bool) -> Self {
+        if center {
+            Self::CENTER
+        } else {
+            Self::KEEP
+        }
+    }
+
+    /// Top-left position for a window of `size` inside the work area `area`,
+    /// or `None` when the window should stay where it is.
+    pub fn position(&self, area: &Rect, size: Size) -> Option<(i32, i32)> {
+        let margin = self.margin;
+        let left = area.x + margin;
+        let right = area.x + area.width - size.width - margin;
+        let top = area.y + margin;
+        let bottom = area.y + area.height - size.height - margin;
+        let center_x = area.x + (area.width - size.width) / 2;
+        let center_y = area.y + (area.height - size.height) / 2;
+
+        let (x, y) = match self.anchor {
+            Anchor::Keep => return None,
+            Anchor::TopLeft => (left, top),
+            Anchor::Top => (center_x, top),
+            Anchor::TopRight => (right, top),
+            Anchor::Left => (left, center_y),
+            Anchor::Center => (center_x, center_y),
+            Anchor::Right => (right, center_y),
+            Anchor::BottomLeft => (left, bottom),
+            Anchor::Bottom => (center_x, bottom),
+            Anchor::BottomRight => (right, bottom),
+            Anchor::Absolute { x, y } => (left + x, top + y),
+        };
+
+        Some((x, y))
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.anchor {
+            Anchor::Absolute { x, y } => write!(f, "absolute:{},{}", x, y)?,
+            anchor => {
+                let name = ANCHOR_NAMES.iter().find(|(_, a)| *a == anchor).map_or("keep", |(name, _)| *name);
+                write!(f, "{}", name)?
+            }
+        }
+
+        if self.margin != 0 {
+            write!(f, ":{}", self.margin)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Placement {
+    type Err = String;
+
+    /// Parses `anchor[:margin]` or `absolute:x,y[:margin]`, where `absolute:`
+    /// may be left out.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid placement '{}'", value);
+        let mut parts: Vec<&str> = value.split(':').map(str::trim).collect();
+
+        if parts[0].eq_ignore_ascii_case("absolute") {
+            parts.remove(0);
+        } else if !parts[0].contains(',') {
+            let anchor = ANCHOR_NAMES
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(parts[0]))
+                .map(|(_, anchor)| *anchor)
+                .ok_or_else(invalid)?;
+            return match parts[1..] {
+                [] => Ok(Self::new(anchor, 0)),
+                [margin] => Ok(Self::new(anchor, margin.parse().map_err(|_| invalid())?)),
+                _ => Err(invalid()),
+            };
+        }
+
+        let (coordinates, margin) = match parts[..] {
+            [coordinates] => (coordinates, 0),
+            [coordinates, margin] => (coordinates, margin.parse().map_err(|_| invalid())?),
+            _ => return Err(invalid()),
+        };
+        let (x, y) = coordinates.split_once(',').ok_or_else(invalid)?;
+        let x = x.trim().parse().map_err(|_| invalid())?;
+        let y = y.trim().parse().map_err(|_| invalid())?;
+
+        Ok(Self::new(Anchor::Absolute { x, y }, margin))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_placements() {
+        assert_eq!("center".parse::<Placement>().unwrap(), Placement::CENTER);
+        assert_eq!("Bottom-Right:20".parse::<Placement>().unwrap(), Placement::new(Anchor::BottomRight, 20));
+        assert_eq!("absolute:10,20".parse::<Placement>().unwrap(), Placement::new(Anchor::Absolute { x: 10, y: 20 }, 0));
+        assert_eq!("10, 20:5".parse::<Placement>().unwrap(), Placement::new(Anchor::Absolute { x: 10, y: 20 }, 5));
+        assert!("middle".parse::<Placement>().is_err());
+        assert!("top:wide".parse::<Placement>().is_err());
+
+        let placement = Placement::new(Anchor::TopLeft, 8);
+        assert_eq!(placement.to_string().parse::<Placement>().unwrap(), placement);
+        assert_eq!(
+            serde_json::from_str::<Placement>(r#"{ "mode": "bottom-right", "margin": 20 }"#).unwrap(),
+            Placement::new(Anchor::BottomRight, 20)
+        );
+        assert_eq!(
+            serde_json::to_value(Placement::new(Anchor::Absolute { x: 1, y: 2 }, 0)).unwrap(),
+            serde_json::json!({ "mode": "absolute", "x": 1, "y": 2, "margin": 0 })
+        );
+    }
+
+    #[test]
+    fn positions_every_anchor_inside_the_margin() {
+        let area = Rect::new(0, 25, 1000, 800);
+        let size = Size { width: 400, height: 300 };
+        let at = |anchor| Placement::new(anchor, 10).position(&area, size).unwrap();
+
+        assert_eq!(at(Anchor::TopLeft), (10, 35));
+        assert_eq!(at(Anchor::Top), (300, 35));
+        assert_eq!(at(Anchor::Right), (590, 275));
+        assert_eq!(at(Anchor::Bottom), (300, 515));
+        assert_eq!(Placement::KEEP.position(&area, size), None);
+    }
+}
@@ -0,0 +1,493 @@
+use crate::error::FrameFitError;
+use crate::placement::Placement;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// The store file holding the custom presets and hotkeys, relative to the
+/// app data directory. Shared by the app and the CLI.
+pub const STORE_FILE: &str = "presets.json";
+
+/// Key of the custom presets in the store.
+pub const STORE_KEY: &str = "customPresets";
+
+/// Version of the stored presets. Version 1 was a bare array of
+/// `{ name, width, height, placement }` objects.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Bounds of preset widths and heights, the same as the size inputs of the UI.
+pub const MIN_DIMENSION: i32 = 100;
+pub const MAX_DIMENSION: i32 = 8192;
+
+/// Namespace of the ids derived from names, for the built-in presets,
+/// presets migrated from version 1 and presets made from catalog devices.
+const ID_NAMESPACE: Uuid = Uuid::from_u128(0x6f1c_2e8a_4d3b_4f7e_9a65_0c1d_8e2b_7f40);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    /// Stays the same when the preset is renamed. Left out for new presets,
+    /// which get a random one when added.
+    #[serde(default)]
+    pub id: Uuid,
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    /// Group in the UI and the tray menu, e.g. "Phone".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default = "default_pixel_ratio")]
+    pub device_pixel_ratio: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<Placement>,
+    /// Set on the presets from [`default_presets`], which can't be changed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub builtin: bool,
+}
+
+pub(crate) fn default_pixel_ratio() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+impl Preset {
+    /// A preset with an id derived from its name.
+    pub fn new(name: &str, width: i32, height: i32) -> Self {
+        Self {
+            id: derived_id(&name.to_lowercase()),
+            name: name.to_string(),
+            width,
+            height,
+            category: None,
+            device_pixel_ratio: default_pixel_ratio(),
+            tags: Vec::new(),
+            notes: None,
+            placement: None,
+            builtin: false,
+        }
+    }
+
+    /// Square sizes count as landscape.
+    pub fn orientation(&self) -> Orientation {
+        if self.height > self.width {
+            Orientation::Portrait
+        } else {
+            Orientation::Landscape
+        }
+    }
+
+    /// The same preset turned a quarter, e.g. a phone held sideways.
+    pub fn rotated(&self) -> Self {
+        Self { width: self.height, height: self.width, ..self.clone() }
+    }
+}
+
+/// A stable id for `key`, the same on every machine.
+pub(crate) fn derived_id(key: &str) -> Uuid {
+    Uuid::new_v5(&ID_NAMESPACE, key.as_bytes())
+}
+
+fn builtin(name: &str, width: i32, height: i32, category: &str, device_pixel_ratio: f64) -> Preset {
+    Preset {
+        category: Some(category.to_string()),
+        device_pixel_ratio,
+        builtin: true,
+        ..Preset::new(name, width, height)
+    }
+}
+
+/// The built-in presets, in the order the UI shows them.
+pub fn default_presets() -> Vec<Preset> {
+    vec![
+        builtin("iPhone SE", 375, 667, "Phone", 2.0),
+        builtin("iPhone 14", 390, 844, "Phone", 3.0),
+        builtin("iPad", 768, 1024, "Tablet", 2.0),
+        builtin("HD", 1280, 720, "Desktop", 1.0),
+        builtin("FHD", 1920, 1080, "Desktop", 1.0),
+    ]
+}
+
+/// The custom presets as stored under [`STORE_KEY`].
+#[derive(Debug, Serialize, Deserialize)]
+struct PresetFile {
+    version: u32,
+    presets: Vec<Preset>,
+}
+
+/// A preset as stored by version 1.
+#[derive(Debug, Deserialize)]
+struct PresetV1 {
+    name: String,
+    width: i32,
+    height: i32,
+    #[serde(default)]
+    placement: Option<Placement>,
+}
+
+#[derive(Debug)]
+pub struct Migrated {
+    pub presets: Vec<Preset>,
+    /// Whether the value was in an older format, or had missing or duplicate
+    /// ids, and should be saved again.
+    pub upgraded: bool,
+}
+
+/// Reads stored custom presets of any version up to [`SCHEMA_VERSION`].
+/// Migrated presets, and presets without an id of their own, get ids
+/// derived from their position and name, so migrating the same file twice
+/// gives the same ids.
+pub fn migrate(value: serde_json::Value) -> Result<Migrated, String> {
+    if value.is_array() {
+        let presets: Vec<PresetV1> = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        let presets = presets
+            .into_iter()
+            .enumerate()
+            .map(|(index, preset)| Preset {
+                id: derived_id(&format!("custom:{}:{}", index, preset.name)),
+                placement: preset.placement,
+                ..Preset::new(&preset.name, preset.width, preset.height)
+            })
+            .collect();
+        return Ok(Migrated { presets, upgraded: true });
+    }
+
+    let version = value.get("version").and_then(serde_json::Value::as_u64).ok_or("Missing schema version")?;
+    if version > u64::from(SCHEMA_VERSION) {
+        return Err(format!("Presets have version {}, this FrameFit supports up to {}", version, SCHEMA_VERSION));
+    }
+
+    let file: PresetFile = serde_json::from_value(value).map_err(|e| e.to_string())?;
+
+    // Entries edited by hand may lack an id or copy another one, which would
+    // leave hotkeys, rules and the tray unable to tell them apart
+    let mut ids: HashSet<Uuid> = default_presets().iter().map(|preset| preset.id).collect();
+    let mut upgraded = false;
+    let presets = file
+        .presets
+        .into_iter()
+        .enumerate()
+        .map(|(index, preset)| {
+            let mut preset = Preset { builtin: false, ..preset };
+            if preset.id.is_nil() || !ids.insert(preset.id) {
+                preset.id = derived_id(&format!("custom:{}:{}", index, preset.name));
+                ids.insert(preset.id);
+                upgraded = true;
+            }
+            preset
+        })
+        .collect();
+    Ok(Migrated { presets, upgraded })
+}
+
+/// The value to store under [`STORE_KEY`].
+pub fn to_store_value(custom_presets: &[Preset]) -> serde_json::Value {
+    serde_json::to_value(PresetFile { version: SCHEMA_VERSION, presets: custom_presets.to_vec() }).unwrap()
+}
+
+/// The default presets followed by the `customPresets` value of the store,
+/// which is ignored when it can't be read.
+pub fn with_custom_presets(custom_presets: Option<serde_json::Value>) -> Vec<Preset> {
+    let mut presets = default_presets();
+
+    if let Some(value) = custom_presets {
+        match migrate(value) {
+            Ok(migrated) => presets.extend(migrated.presets),
+            Err(e) => eprintln!("Ignoring invalid custom presets: {}", e),
+        }
+    }
+
+    presets
+}
+
+/// Checks that `preset` can be saved among `presets`, the other built-in and
+/// custom presets, which may include an older version of it.
+pub fn validate(preset: &Preset, presets: &[Preset]) -> Result<(), FrameFitError> {
+    let invalid = |reason: String| Err(FrameFitError::InvalidPreset { reason });
+
+    if preset.name.trim().is_empty() {
+        return invalid("Missing name".to_string());
+    }
+    for (dimension, value) in [("Width", preset.width), ("Height", preset.height)] {
+        if value <= 0 {
+            return invalid(format!("{} must be positive, not {}", dimension, value));
+        }
+        if !(MIN_DIMENSION..=MAX_DIMENSION).contains(&value) {
+            return invalid(format!("{} {} is outside {}–{}", dimension, value, MIN_DIMENSION, MAX_DIMENSION));
+        }
+    }
+    if !(preset.device_pixel_ratio.is_finite() && preset.device_pixel_ratio > 0.0) {
+        return invalid(format!("Invalid pixel ratio {}", preset.device_pixel_ratio));
+    }
+
+    let name = preset.name.trim();
+    let same_name = |other: &&Preset| other.id != preset.id && other.name.trim().eq_ignore_ascii_case(name);
+    if let Some(other) = presets.iter().find(same_name) {
+        return invalid(format!("There already is a preset named \"{}\"", other.name));
+    }
+    Ok(())
+}
+
+/// The index of a custom preset; built-in presets can't be changed.
+fn position(custom_presets: &[Preset], id: Uuid) -> Result<usize, FrameFitError> {
+    if default_presets().iter().any(|preset| preset.id == id) {
+        return Err(FrameFitError::InvalidPreset { reason: "Built-in presets can't be changed".to_string() });
+    }
+    custom_presets
+        .iter()
+        .position(|preset| preset.id == id)
+        .ok_or_else(|| FrameFitError::PresetNotFound { id: id.to_string() })
+}
+
+/// `preset` as it is saved: trimmed, and never built-in.
+fn normalized(preset: Preset) -> Preset {
+    Preset { name: preset.name.trim().to_string(), builtin: false, ..preset }
+}
+
+/// Adds a custom preset, with a random id unless it has one, and returns it.
+pub fn add(custom_presets: &mut Vec<Preset>, preset: Preset) -> Result<Preset, FrameFitError> {
+    let mut preset = normalized(preset);
+    if preset.id.is_nil() {
+        preset.id = Uuid::new_v4();
+    }
+
+    let mut presets = default_presets();
+    presets.extend(custom_presets.iter().cloned());
+    if presets.iter().any(|other| other.id == preset.id) {
+        return Err(FrameFitError::InvalidPreset { reason: format!("{} is already a preset", preset.name) });
+    }
+    validate(&preset, &presets)?;
+
+    custom_presets.push(preset.clone());
+    Ok(preset)
+}
+
+/// Replaces the custom preset with the same id.
+pub fn update(custom_presets: &mut [Preset], preset: Preset) -> Result<(), FrameFitError> {
+    let preset = normalized(preset);
+    let index = position(custom_presets, preset.id)?;
+
+    let mut presets = default_presets();
+    presets.extend(custom_presets.iter().cloned());
+    validate(&preset, &presets)?;
+
+    custom_presets[index] = preset;
+    Ok(())
+}
+
+pub fn delete(custom_presets: &mut Vec<Preset>, id: Uuid) -> Result<Preset, FrameFitError> {
+    let index = position(custom_presets, id)?;
+    Ok(custom_presets.remove(index))
+}
+
+/// Puts the custom presets in the order of `ids`, which has to list each of
+/// them once. The built-in presets always come first.
+pub fn reorder(custom_presets: &mut Vec<Preset>, ids: &[Uuid]) -> Result<(), FrameFitError> {
+    let mut remaining = custom_presets.clone();
+    let mut reordered = Vec::with_capacity(ids.len());
+    for &id in ids {
+        let index = position(&remaining, id)?;
+        reordered.push(remaining.remove(index));
+    }
+    if let Some(missing) = remaining.first() {
+        return Err(FrameFitError::InvalidPreset { reason: format!("{} is missing from the new order", missing.name) });
+    }
+
+    *custom_presets = reordered;
+    Ok(())
+}
+
+/// Prefix of the tray menu items of presets, followed by the preset id.
+const MENU_ID_PREFIX: &str = "preset:";
+
+impl Preset {
+    /// Id of the preset's tray menu item. Unique even for names that differ
+    /// only in case or punctuation.
+    pub fn menu_id(&self) -> String {
+        format!("{}{}", MENU_ID_PREFIX, self.id)
+    }
+}
+
+/// The preset id in a tray menu item id, `None` for other items.
+pub fn id_from_menu_id(menu_id: &str) -> Option<Uuid> {
+    menu_id.strip_prefix(MENU_ID_PREFIX).and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// Looks a preset up by name, ignoring case. Only for names people type,
+/// like links and the CLI; settings refer to presets by id.
+pub fn find<'a>(presets: &'a [Preset], name: &str) -> Option<&'a Preset> {
+    presets.iter().find(|preset| preset.name.eq_ignore_ascii_case(name))
+}
+
+pub fn find_by_id(presets: &[Preset], id: Uuid) -> Option<&Preset> {
+    presets.iter().find(|preset| preset.id == id)
+}
+
+/// Hotkeys and rules referred to presets by name before presets had ids.
+/// Returns the id of the preset named `name`, or the nil id, which matches
+/// no preset, so the reference is reported like one to a deleted preset.
+pub fn id_for_name(presets: &[Preset], name: &str) -> Uuid {
+    find(presets, name).map_or(Uuid::nil(), |preset| preset.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::placement::Anchor;
+    use serde_json::json;
+
+    #[test]
+    fn migrates_version_1_arrays() {
+        let stored = json!([
+            { "name": "Blog", "width": 1200, "height": 800, "placement": { "mode": "top-left", "margin": 10 } },
+            { "name": "Blog", "width": 600, "height": 800 },
+        ]);
+
+        let migrated = migrate(stored.clone()).unwrap();
+
+        assert!(migrated.upgraded);
+        assert_eq!(migrated.presets.len(), 2);
+        assert_eq!(migrated.presets[0].placement, Some(Placement::new(Anchor::TopLeft, 10)));
+        assert_eq!((migrated.presets[1].width, migrated.presets[1].height), (600, 800));
+        assert_ne!(migrated.presets[0].id, migrated.presets[1].id);
+        assert_eq!(migrate(stored).unwrap().presets[1].id, migrated.presets[1].id);
+    }
+
+    #[test]
+    fn round_trips_the_current_version() {
+        let mut preset = Preset::new("Pixel 8", 412, 915);
+        preset.category = Some("Phone".to_string());
+        preset.device_pixel_ratio = 2.625;
+        preset.tags = vec!["android".to_string()];
+        preset.notes = Some("Chrome on Android".to_string());
+
+        let migrated = migrate(to_store_value(std::slice::from_ref(&preset))).unwrap();
+
+        assert!(!migrated.upgraded);
+        assert_eq!(migrated.presets, [preset]);
+    }
+
+    #[test]
+    fn gives_missing_and_duplicate_ids_their_own() {
+        let copied = Preset::new("Copy", 800, 600).id;
+        let stored = json!({ "version": SCHEMA_VERSION, "presets": [
+            { "name": "No id", "width": 800, "height": 600 },
+            { "name": "No id either", "width": 800, "height": 600 },
+            { "id": copied, "name": "Copy", "width": 800, "height": 600 },
+            { "id": copied, "name": "Copy", "width": 800, "height": 600 },
+        ] });
+
+        let migrated = migrate(stored.clone()).unwrap();
+
+        assert!(migrated.upgraded);
+        let ids: HashSet<Uuid> = migrated.presets.iter().map(|preset| preset.id).collect();
+        assert_eq!(ids.len(), 4);
+        assert!(!ids.contains(&Uuid::nil()));
+        assert_eq!(migrated.presets[2].id, copied);
+        assert_eq!(migrate(stored).unwrap().presets, migrated.presets);
+        assert!(!migrate(to_store_value(&migrated.presets)).unwrap().upgraded);
+    }
+
+    #[test]
+    fn refuses_newer_versions() {
+        let stored = json!({ "version": SCHEMA_VERSION + 1, "presets": [] });
+
+        assert!(migrate(stored).unwrap_err().contains("supports up to"));
+        assert_eq!(with_custom_presets(Some(json!({ "presets": [] }))).len(), default_presets().len());
+    }
+
+    #[test]
+    fn rotates_presets() {
+        let ipad = find(&default_presets(), "ipad").unwrap().clone();
+
+        assert_eq!(ipad.orientation(), Orientation::Portrait);
+        assert_eq!((ipad.rotated().width, ipad.rotated().height, ipad.rotated().orientation()), (1024, 768, Orientation::Landscape));
+        assert_eq!(ipad.rotated().id, ipad.id);
+    }
+
+    fn invalid_reason(result: Result<impl std::fmt::Debug, FrameFitError>) -> String {
+        match result.unwrap_err() {
+            FrameFitError::InvalidPreset { reason } => reason,
+            error => panic!("Expected an invalid preset, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn validates_new_presets() {
+        let mut custom_presets = Vec::new();
+        let mut draft = |name: &str, width: i32, height: i32| {
+            let preset = Preset { id: Uuid::nil(), ..Preset::new(name, width, height) };
+            add(&mut custom_presets, preset)
+        };
+
+        let blog = draft(" Blog ", 1200, 800).unwrap();
+        assert_eq!(blog.name, "Blog");
+        assert!(!blog.id.is_nil());
+        assert_eq!(invalid_reason(draft("Zero", 0, 800)), "Width must be positive, not 0");
+        assert_eq!(invalid_reason(draft("Negative", 800, -1)), "Height must be positive, not -1");
+        assert_eq!(invalid_reason(draft("Tiny", 50, 800)), "Width 50 is outside 100–8192");
+        assert_eq!(invalid_reason(draft("  ", 800, 800)), "Missing name");
+        assert_eq!(invalid_reason(draft("BLOG", 800, 600)), "There already is a preset named \"Blog\"");
+        assert_eq!(invalid_reason(draft("ipad", 800, 600)), "There already is a preset named \"iPad\"");
+        assert_eq!(custom_presets, std::slice::from_ref(&blog));
+        // Presets with an id keep it, and can't be added twice
+        assert_eq!(invalid_reason(add(&mut custom_presets, blog)), "Blog is already a preset");
+    }
+
+    #[test]
+    fn updates_and_deletes_custom_presets_only() {
+        let mut custom_presets = vec![Preset::new("Blog", 1200, 800), Preset::new("Docs", 1000, 800)];
+        let blog_id = custom_presets[0].id;
+
+        let renamed = Preset { name: "Blog post".to_string(), ..custom_presets[0].clone() };
+        update(&mut custom_presets, renamed.clone()).unwrap();
+        assert_eq!(custom_presets[0].name, "Blog post");
+        // Keeping its own name is fine, taking another one isn't
+        update(&mut custom_presets, renamed.clone()).unwrap();
+        let taken = Preset { name: "docs".to_string(), ..renamed };
+        assert!(invalid_reason(update(&mut custom_presets, taken)).contains("Docs"));
+
+        let ipad = find(&default_presets(), "iPad").unwrap().clone();
+        assert_eq!(invalid_reason(update(&mut custom_presets, ipad.rotated())), "Built-in presets can't be changed");
+        assert_eq!(invalid_reason(delete(&mut custom_presets, ipad.id)), "Built-in presets can't be changed");
+        assert!(matches!(delete(&mut custom_presets, Uuid::nil()), Err(FrameFitError::PresetNotFound { .. })));
+
+        assert_eq!(delete(&mut custom_presets, blog_id).unwrap().name, "Blog post");
+        assert_eq!(custom_presets.len(), 1);
+    }
+
+    #[test]
+    fn reorders_every_custom_preset() {
+        let presets = [Preset::new("A", 800, 600), Preset::new("B", 800, 600), Preset::new("C", 800, 600)];
+        let mut custom_presets = presets.to_vec();
+
+        reorder(&mut custom_presets, &[presets[2].id, presets[0].id, presets[1].id]).unwrap();
+        assert_eq!(custom_presets, [presets[2].clone(), presets[0].clone(), presets[1].clone()]);
+
+        let incomplete = [presets[0].id, presets[1].id];
+        assert_eq!(invalid_reason(reorder(&mut custom_presets, &incomplete)), "C is missing from the new order");
+        assert!(reorder(&mut custom_presets, &[presets[0].id, presets[0].id, presets[1].id]).is_err());
+        assert_eq!(custom_presets[0], presets[2]);
+    }
+
+    #[test]
+    fn menu_ids_are_unique_per_preset() {
+        // Both were `custom_preset_my_preset` before
+        let presets = [Preset::new("My Preset", 800, 600), Preset::new("my_preset", 800, 600)];
+
+        assert_ne!(presets[0].menu_id(), presets[1].menu_id());
+        for preset in &presets {
+            assert_eq!(id_from_menu_id(&preset.menu_id()), Some(preset.id));
+        }
+        assert_eq!(id_from_menu_id("show_window"), None);
+        assert_eq!(id_from_menu_id("preset:not-a-uuid"), None);
+    }
+}
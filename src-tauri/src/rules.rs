@@ -0,0 +1,444 @@
+use crate::placement::Placement;
+use crate::presets::{self, Preset};
+use crate::window_manager::{find_screen, screen_for_window, DisplayTarget, ScreenInfo, Size, WindowInfo};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Key of the rules in the presets store.
+pub const STORE_KEY: &str = "rules";
+
+/// How many watcher polls a new window stays a candidate for rules, so apps
+/// that set their title after opening the window still match.
+pub const PENDING_POLLS: u32 = 5;
+
+/// The value stored under [`STORE_KEY`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulesConfig {
+    /// Report matches with a `rule_matched` event instead of resizing.
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+/// Resizes or moves windows as they appear, e.g.
+/// `{ "name": "Storybook", "match": { "app_name": "Google Chrome", "title_pattern": "^Storybook" },
+///    "action": { "preset": "1b4e28ba-…", "placement": { "mode": "center" } } }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// Higher priorities are tried first; on ties the earlier rule wins.
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(rename = "match")]
+    pub matcher: RuleMatcher,
+    pub action: RuleAction,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Every condition that is set has to hold.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleMatcher {
+    /// Compared ignoring case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    /// Regular expression for the window title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_size: Option<Size>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<Size>,
+    /// The display showing most of the window, by index or name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<DisplayTarget>,
+}
+
+/// A preset, a placement or both. Without a placement presets go where they
+/// would from a hotkey; without a preset the window keeps its size.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleAction {
+    /// The preset id, which stays the same when it's renamed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<Placement>,
+}
+
+/// What a rule does to a window, as reported by dry runs and events.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleMatch {
+    pub rule: String,
+    pub window: WindowInfo,
+    pub size: Size,
+    pub placement: Placement,
+}
+
+/// Rules as read from the store by [`migrate`].
+#[derive(Debug)]
+pub struct Migrated {
+    pub config: RulesConfig,
+    /// Whether preset names were replaced by ids and the rules should be
+    /// saved again.
+    pub upgraded: bool,
+}
+
+/// Reads the stored rules. Rules from before presets had ids, and rules
+/// written by hand, name their preset, which is looked up in `presets`.
+/// Unknown names are reported by [`validate`], and the stored names are kept
+/// until they're fixed.
+pub fn migrate(mut value: serde_json::Value, presets: &[Preset]) -> Result<Migrated, String> {
+    let mut renamed = false;
+    let mut unknown = false;
+
+    let rules = value.get_mut("rules").and_then(serde_json::Value::as_array_mut);
+    for action in rules.into_iter().flatten().filter_map(|rule| rule.get_mut("action")) {
+        let Some(preset) = action.get_mut("preset") else {
+            continue;
+        };
+        let Some(name) = preset.as_str().filter(|name| Uuid::parse_str(name).is_err()) else {
+            continue;
+        };
+
+        let id = presets::id_for_name(presets, name);
+        unknown |= id.is_nil();
+        renamed = true;
+        *preset = id.to_string().into();
+    }
+
+    let config = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    Ok(Migrated { config, upgraded: renamed && !unknown })
+}
+
+/// Checks the rules against each other and the existing presets, returning
+/// one message per problem.
+pub fn validate(rules: &[Rule], presets: &[Preset]) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for (index, rule) in rules.iter().enumerate() {
+        let name = if rule.name.trim().is_empty() { format!("Rule {}", index + 1) } else { rule.name.clone() };
+
+        if rules[..index].iter().any(|other| other.name.eq_ignore_ascii_case(&rule.name)) {
+            problems.push(format!("{}: the name is used more than once", name));
+        }
+        if let Some(pattern) = &rule.matcher.title_pattern {
+            if let Err(e) = Regex::new(pattern) {
+                problems.push(format!("{}: invalid title pattern: {}", name, e));
+            }
+        }
+        if let (Some(min), Some(max)) = (rule.matcher.min_size, rule.matcher.max_size) {
+            if min.width > max.width || min.height > max.height {
+                problems.push(format!("{}: the minimum size is larger than the maximum size", name));
+            }
+        }
+        match rule.action.preset {
+            Some(preset) if presets::find_by_id(presets, preset).is_none() => {
+                problems.push(format!("{}: the preset doesn't exist", name))
+            }
+            None if rule.action.placement.is_none() => problems.push(format!("{}: no preset or placement", name)),
+            _ => {}
+        }
+    }
+
+    problems
+}
+
+/// Enabled rules, highest priority first, with their title patterns
+/// compiled. Rules with invalid patterns are left out.
+pub struct RuleSet {
+    rules: Vec<(Rule, Option<Regex>)>,
+}
+
+impl RuleSet {
+    pub fn new(rules: &[Rule]) -> Self {
+        let mut rules: Vec<(Rule, Option<Regex>)> = rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| {
+                let title = match rule.matcher.title_pattern.as_deref().map(Regex::new) {
+                    Some(Ok(title)) => Some(title),
+                    Some(Err(e)) => {
+                        eprintln!("Skipping rule {}: {}", rule.name, e);
+                        return None;
+                    }
+                    None => None,
+                };
+                Some((rule.clone(), title))
+            })
+            .collect();
+
+        // Stable, so equal priorities keep their order
+        rules.sort_by_key(|(rule, _)| std::cmp::Reverse(rule.priority));
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// What the first matching rule would do to `window`. Rules whose preset
+    /// no longer exists are skipped.
+    pub fn find(&self, window: &WindowInfo, screens: &[ScreenInfo], presets: &[Preset]) -> Option<RuleMatch> {
+        self.rules.iter().find_map(|(rule, title)| {
+            if !matches(&rule.matcher, title.as_ref(), window, screens) {
+                return None;
+            }
+
+            let (size, preset_placement) = match rule.action.preset {
+                Some(id) => {
+                    let preset = presets::find_by_id(presets, id)?;
+                    (Size { width: preset.width, height: preset.height }, preset.placement.unwrap_or(Placement::CENTER))
+                }
+                None => (Size { width: window.width, height: window.height }, Placement::KEEP),
+            };
+
+            Some(RuleMatch {
+                rule: rule.name.clone(),
+                window: window.clone(),
+                size,
+                placement: rule.action.placement.unwrap_or(preset_placement),
+            })
+        })
+    }
+
+    /// What the rules would do to each of `windows`.
+    pub fn dry_run(&self, windows: &[WindowInfo], screens: &[ScreenInfo], presets: &[Preset]) -> Vec<RuleMatch> {
+        windows.iter().filter_map(|window| self.find(window, screens, presets)).collect()
+    }
+}
+
+fn matches(matcher: &RuleMatcher, title: Option<&Regex>, window: &WindowInfo, screens: &[ScreenInfo]) -> bool {
+    let app_matches = matcher.app_name.as_ref().is_none_or(|app| window.app_name.eq_ignore_ascii_case(app));
+    let title_matches = title.is_none_or(|title| title.is_match(&window.title));
+    let big_enough = matcher.min_size.is_none_or(|min| window.width >= min.width && window.height >= min.height);
+    let small_enough = matcher.max_size.is_none_or(|max| window.width <= max.width && window.height <= max.height);
+    let display_matches = matcher.display.as_ref().is_none_or(|display| {
+        let target = find_screen(screens, display).ok().map(|screen| screen.id);
+        target.is_some() && screen_for_window(screens, &window.frame()).map(|screen| screen.id) == target
+    });
+
+    app_matches && title_matches && big_enough && small_enough && display_matches
+}
+
+/// Tracks new windows until a rule matched them or they've been around for
+/// [`PENDING_POLLS`], so each window is acted on at most once.
+#[derive(Debug, Default)]
+pub struct RuleRunner {
+    pending: HashMap<u32, u32>,
+}
+
+impl RuleRunner {
+    /// Takes the windows that opened since the last poll and all current
+    /// windows, returns what to do to the pending ones.
+    pub fn poll(
+        &mut self,
+        rules: &RuleSet,
+        opened: &[WindowInfo],
+        windows: &[WindowInfo],
+        screens: &[ScreenInfo],
+        presets: &[Preset],
+    ) -> Vec<RuleMatch> {
+        for window in opened {
+            self.pending.insert(window.id, PENDING_POLLS);
+        }
+
+        let mut matched = Vec::new();
+        for window in windows {
+            let Some(polls_left) = self.pending.get_mut(&window.id) else {
+                continue;
+            };
+
+            if let Some(rule_match) = rules.find(window, screens, presets) {
+                matched.push(rule_match);
+                self.pending.remove(&window.id);
+            } else if *polls_left <= 1 {
+                self.pending.remove(&window.id);
+            } else {
+                *polls_left -= 1;
+            }
+        }
+
+        // Windows that closed before anything matched
+        self.pending.retain(|id, _| windows.iter().any(|window| window.id == *id));
+        matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::placement::Anchor;
+    use crate::window_manager::Rect;
+
+    fn window(id: u32, app_name: &str, title: &str, x: i32, width: i32, height: i32) -> WindowInfo {
+        WindowInfo { id, title: title.to_string(), app_name: app_name.to_string(), x, y: 100, width, height }
+    }
+
+    fn rule(name: &str, priority: i32, matcher: RuleMatcher, preset: Option<&str>) -> Rule {
+        Rule {
+            name: name.to_string(),
+            enabled: true,
+            priority,
+            matcher,
+            action: RuleAction {
+                preset: preset.map(|name| presets::id_for_name(&presets::default_presets(), name)),
+                placement: None,
+            },
+        }
+    }
+
+    fn screens() -> Vec<ScreenInfo> {
+        let screen = |id: u32, name: &str, x| ScreenInfo {
+            id,
+            name: name.to_string(),
+            frame: Rect::new(x, 0, 1920, 1080),
+            work_area: Rect::new(x, 0, 1920, 1080),
+            scale_factor: 1.0,
+            is_main: id == 0,
+        };
+        vec![screen(0, "Built-in", 0), screen(1, "DP-2", 1920)]
+    }
+
+    fn storybook_rules() -> Vec<Rule> {
+        vec![
+            rule(
+                "Storybook",
+                0,
+                RuleMatcher {
+                    app_name: Some("google chrome".to_string()),
+                    title_pattern: Some("^Storybook".to_string()),
+                    ..Default::default()
+                },
+                Some("iPad"),
+            ),
+            rule(
+                "Small windows on the external display",
+                10,
+                RuleMatcher {
+                    max_size: Some(Size { width: 800, height: 800 }),
+                    display: Some(DisplayTarget::Name("DP-2".to_string())),
+                    ..Default::default()
+                },
+                Some("HD"),
+            ),
+            Rule {
+                action: RuleAction { preset: None, placement: Some(Placement::new(Anchor::TopRight, 20)) },
+                ..rule("Simulator", 0, RuleMatcher { app_name: Some("Simulator".to_string()), ..Default::default() }, None)
+            },
+        ]
+    }
+
+    #[test]
+    fn matches_by_app_title_size_and_display() {
+        let rules = RuleSet::new(&storybook_rules());
+        let presets = presets::default_presets();
+        let find = |window: &WindowInfo| rules.find(window, &screens(), &presets).map(|m| (m.rule, m.size, m.placement));
+
+        assert_eq!(
+            find(&window(1, "Google Chrome", "Storybook - Button", 100, 1200, 900)),
+            Some(("Storybook".to_string(), Size { width: 768, height: 1024 }, Placement::CENTER))
+        );
+        assert_eq!(find(&window(2, "Google Chrome", "GitHub", 100, 1200, 900)), None);
+        assert_eq!(
+            find(&window(3, "Simulator", "iPhone 15", 100, 400, 860)),
+            Some(("Simulator".to_string(), Size { width: 400, height: 860 }, Placement::new(Anchor::TopRight, 20)))
+        );
+        assert_eq!(find(&window(4, "Notes", "Todo", 2000, 600, 600)).unwrap().0, "Small windows on the external display");
+        assert_eq!(find(&window(5, "Notes", "Todo", 2000, 900, 600)), None);
+    }
+
+    #[test]
+    fn higher_priorities_win_and_disabled_rules_are_skipped() {
+        let mut rules = storybook_rules();
+        let chrome = window(1, "Google Chrome", "Storybook", 2000, 600, 600);
+        let presets = presets::default_presets();
+
+        assert_eq!(RuleSet::new(&rules).find(&chrome, &screens(), &presets).unwrap().rule, "Small windows on the external display");
+
+        rules[1].enabled = false;
+        assert_eq!(RuleSet::new(&rules).find(&chrome, &screens(), &presets).unwrap().rule, "Storybook");
+    }
+
+    #[test]
+    fn validates_patterns_presets_and_actions() {
+        let mut rules = storybook_rules();
+        rules[0].matcher.title_pattern = Some("(Storybook".to_string());
+        rules[1].action.preset = Some(Uuid::new_v4());
+        rules[2].action.placement = None;
+        rules.push(rules[2].clone());
+
+        let problems = validate(&rules, &presets::default_presets());
+
+        assert_eq!(problems.len(), 5);
+        assert!(problems[0].starts_with("Storybook: invalid title pattern"));
+        assert_eq!(problems[1], "Small windows on the external display: the preset doesn't exist");
+        assert_eq!(problems[2], "Simulator: no preset or placement");
+        assert_eq!(problems[3], "Simulator: the name is used more than once");
+        assert!(validate(&storybook_rules(), &presets::default_presets()).is_empty());
+    }
+
+    #[test]
+    fn migrates_preset_names_to_ids() {
+        let mut presets = presets::default_presets();
+        let stored = serde_json::json!({
+            "rules": [
+                { "name": "Storybook", "match": {}, "action": { "preset": "ipad" } },
+                { "name": "Simulator", "match": {}, "action": { "placement": { "mode": "center" } } },
+            ]
+        });
+
+        let migrated = migrate(stored.clone(), &presets).unwrap();
+        assert!(migrated.upgraded);
+        assert_eq!(migrated.config.rules[0].action.preset, Some(presets[2].id));
+        assert_eq!(migrate(serde_json::to_value(&migrated.config).unwrap(), &presets).unwrap().config, migrated.config);
+
+        // Renaming the preset afterwards keeps the rule working
+        presets[2].name = "iPad mini".to_string();
+        let chrome = window(1, "Google Chrome", "Storybook", 100, 1200, 900);
+        let rule_match = RuleSet::new(&migrated.config.rules).find(&chrome, &screens(), &presets).unwrap();
+        assert_eq!(rule_match.size, Size { width: 768, height: 1024 });
+
+        // Unknown names are reported but not saved over
+        let migrated = migrate(stored, &presets).unwrap();
+        assert!(!migrated.upgraded);
+        assert_eq!(validate(&migrated.config.rules, &presets), ["Storybook: the preset doesn't exist"]);
+    }
+
+    #[test]
+    fn runner_waits_for_titles_and_acts_once() {
+        let rules = RuleSet::new(&storybook_rules());
+        let presets = presets::default_presets();
+        let mut runner = RuleRunner::default();
+        let loading = [window(1, "Google Chrome", "New Tab", 100, 1200, 900)];
+        let loaded = [window(1, "Google Chrome", "Storybook", 100, 1200, 900)];
+
+        assert!(runner.poll(&rules, &loading, &loading, &screens(), &presets).is_empty());
+        assert_eq!(runner.poll(&rules, &[], &loaded, &screens(), &presets).len(), 1);
+        assert!(runner.poll(&rules, &[], &loaded, &screens(), &presets).is_empty());
+    }
+
+    #[test]
+    fn runner_gives_up_on_windows_that_never_match() {
+        let rules = RuleSet::new(&storybook_rules());
+        let presets = presets::default_presets();
+        let mut runner = RuleRunner::default();
+        let other = [window(1, "Google Chrome", "GitHub", 100, 1200, 900)];
+        let storybook = [window(1, "Google Chrome", "Storybook", 100, 1200, 900)];
+
+        runner.poll(&rules, &other, &other, &screens(), &presets);
+        for _ in 1..PENDING_POLLS {
+            runner.poll(&rules, &[], &other, &screens(), &presets);
+        }
+
+        assert!(runner.poll(&rules, &[], &storybook, &screens(), &presets).is_empty());
+    }
+}
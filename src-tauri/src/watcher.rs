@@ -0,0 +1,261 @@
+use crate::window_manager::{SharedBackend, WindowInfo};
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How often the watcher lists the windows unless configured otherwise.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Bounds of the configurable interval; listing windows isn't free.
+pub const MIN_INTERVAL: Duration = Duration::from_millis(250);
+pub const MAX_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Key of the polling interval, in milliseconds, in the presets store.
+pub const STORE_KEY: &str = "watchIntervalMs";
+
+/// One difference between two snapshots, as sent in `windows_changed` events.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WindowChange {
+    Added { after: WindowInfo },
+    Removed { before: WindowInfo },
+    /// Same size, different position.
+    Moved { before: WindowInfo, after: WindowInfo },
+    /// Different size, and possibly position.
+    Resized { before: WindowInfo, after: WindowInfo },
+    /// Another window is frontmost. `before` is `None` when the previous
+    /// one was closed or there was none.
+    Focused { before: Option<WindowInfo>, after: WindowInfo },
+}
+
+/// The windows at one poll. The frontmost window comes from the backend, as
+/// backends list windows in different orders.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    pub windows: Vec<WindowInfo>,
+    pub frontmost: Option<WindowInfo>,
+}
+
+/// The changes from `before` to `after`: removed windows first, then added,
+/// moved and resized ones in the order of `after`, then a change of focus.
+pub fn diff(before: &Snapshot, after: &Snapshot) -> Vec<WindowChange> {
+    let previous: HashMap<u32, &WindowInfo> = before.windows.iter().map(|window| (window.id, window)).collect();
+    let current: HashMap<u32, &WindowInfo> = after.windows.iter().map(|window| (window.id, window)).collect();
+
+    let mut changes: Vec<WindowChange> = before
+        .windows
+        .iter()
+        .filter(|window| !current.contains_key(&window.id))
+        .map(|window| WindowChange::Removed { before: window.clone() })
+        .collect();
+
+    for window in &after.windows {
+        let change = match previous.get(&window.id) {
+            None => WindowChange::Added { after: window.clone() },
+            Some(old) if (old.width, old.height) != (window.width, window.height) => {
+                WindowChange::Resized { before: (*old).clone(), after: window.clone() }
+            }
+            Some(old) if (old.x, old.y) != (window.x, window.y) => {
+                WindowChange::Moved { before: (*old).clone(), after: window.clone() }
+            }
+            Some(_) => continue,
+        };
+        changes.push(change);
+    }
+
+    if let Some(front) = &after.frontmost {
+        if before.frontmost.as_ref().map(|window| window.id) != Some(front.id) {
+            let before = before.frontmost.clone().filter(|window| current.contains_key(&window.id));
+            changes.push(WindowChange::Focused { before, after: front.clone() });
+        }
+    }
+
+    changes
+}
+
+/// Diffs successive snapshots.
+#[derive(Debug, Default)]
+pub struct WindowWatcher {
+    /// `None` until the first snapshot, so windows that were already open
+    /// don't count as added.
+    previous: Option<Snapshot>,
+}
+
+impl WindowWatcher {
+    /// Takes the current windows and returns what changed since the previous
+    /// snapshot.
+    pub fn update(&mut self, snapshot: Snapshot) -> Vec<WindowChange> {
+        let changes = match &self.previous {
+            Some(previous) => diff(previous, &snapshot),
+            None => Vec::new(),
+        };
+
+        self.previous = Some(snapshot);
+        changes
+    }
+}
+
+/// Changes the polling interval of a running watcher.
+#[derive(Debug, Clone)]
+pub struct WatcherHandle {
+    interval_ms: Arc<AtomicU64>,
+}
+
+impl WatcherHandle {
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms.load(Ordering::Relaxed))
+    }
+
+    /// Takes effect after the current wait. Clamped to [`MIN_INTERVAL`] and
+    /// [`MAX_INTERVAL`], returns the interval that is used.
+    pub fn set_interval(&self, interval: Duration) -> Duration {
+        let interval = interval.clamp(MIN_INTERVAL, MAX_INTERVAL);
+        self.interval_ms.store(interval.as_millis() as u64, Ordering::Relaxed);
+        interval
+    }
+}
+
+/// Polls `backend` on a background thread, calling `on_poll` with the
+/// changes and all current windows. Failed polls are skipped, e.g. while the
+/// accessibility permission is missing.
+///
+/// Polls are also skipped while `wanted` returns false, e.g. when nobody
+/// looks at the changes. The first poll after that only takes a new
+/// snapshot, so windows opened in between don't count as added.
+pub fn spawn<W, F>(backend: SharedBackend, interval: Duration, wanted: W, mut on_poll: F) -> WatcherHandle
+where
+    W: Fn() -> bool + Send + 'static,
+    F: FnMut(&[WindowChange], &[WindowInfo]) + Send + 'static,
+{
+    let handle = WatcherHandle { interval_ms: Arc::new(AtomicU64::new(0)) };
+    handle.set_interval(interval);
+
+    let interval_ms = handle.interval_ms.clone();
+    thread::spawn(move || {
+        let mut watcher = WindowWatcher::default();
+        let mut last_error = None;
+
+        loop {
+            if !wanted() {
+                watcher = WindowWatcher::default();
+                thread::sleep(Duration::from_millis(interval_ms.load(Ordering::Relaxed)));
+                continue;
+            }
+
+            match backend.list_windows_with_frontmost() {
+                Ok((windows, frontmost)) => {
+                    last_error = None;
+                    let changes = watcher.update(Snapshot { windows: windows.clone(), frontmost });
+                    on_poll(&changes, &windows);
+                }
+                Err(e) => {
+                    // Once per kind of failure, not every poll
+                    if last_error.as_ref() != Some(&e) {
+                        eprintln!("Window watcher can't list windows: {}", e);
+                    }
+                    last_error = Some(e);
+                }
+            }
+
+            thread::sleep(Duration::from_millis(interval_ms.load(Ordering::Relaxed)));
+        }
+    });
+
+    handle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(id: u32, x: i32, y: i32, width: i32, height: i32) -> WindowInfo {
+        WindowInfo { id, title: format!("Window {}", id), app_name: "Safari".to_string(), x, y, width, height }
+    }
+
+    fn snapshot(windows: &[WindowInfo]) -> Snapshot {
+        Snapshot { windows: windows.to_vec(), frontmost: windows.first().cloned() }
+    }
+
+    #[test]
+    fn reports_added_and_removed_windows() {
+        let before = [window(1, 0, 0, 800, 600), window(2, 0, 0, 800, 600)];
+        let after = [window(1, 0, 0, 800, 600), window(3, 0, 0, 800, 600)];
+
+        assert_eq!(
+            diff(&snapshot(&before), &snapshot(&after)),
+            [
+                WindowChange::Removed { before: before[1].clone() },
+                WindowChange::Added { after: after[1].clone() },
+            ]
+        );
+    }
+
+    #[test]
+    fn tells_moves_from_resizes() {
+        let before = [window(1, 0, 0, 800, 600), window(2, 0, 0, 800, 600), window(3, 0, 0, 800, 600)];
+        let after = [window(1, 40, 20, 800, 600), window(2, 40, 20, 390, 844), window(3, 0, 0, 800, 600)];
+
+        assert_eq!(
+            diff(&snapshot(&before), &snapshot(&after)),
+            [
+                WindowChange::Moved { before: before[0].clone(), after: after[0].clone() },
+                WindowChange::Resized { before: before[1].clone(), after: after[1].clone() },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_focus_changes() {
+        let windows = [window(1, 0, 0, 800, 600), window(2, 0, 0, 800, 600)];
+        let focused = |id: usize| Snapshot { windows: windows.to_vec(), frontmost: Some(windows[id].clone()) };
+
+        assert_eq!(
+            diff(&focused(0), &focused(1)),
+            [WindowChange::Focused { before: Some(windows[0].clone()), after: windows[1].clone() }]
+        );
+        // Closing the frontmost window focuses the next one
+        assert_eq!(
+            diff(&focused(0), &snapshot(&windows[1..])),
+            [
+                WindowChange::Removed { before: windows[0].clone() },
+                WindowChange::Focused { before: None, after: windows[1].clone() },
+            ]
+        );
+    }
+
+    #[test]
+    fn list_order_is_not_focus() {
+        // X11 and sway list windows in mapping or layout order
+        let windows = [window(1, 0, 0, 800, 600), window(2, 0, 0, 800, 600)];
+        let before = Snapshot { windows: windows.to_vec(), frontmost: Some(windows[1].clone()) };
+        let after = Snapshot { windows: vec![windows[1].clone(), windows[0].clone()], ..before.clone() };
+
+        assert!(diff(&before, &after).is_empty());
+        assert!(diff(&before, &Snapshot { frontmost: None, ..before.clone() }).is_empty());
+    }
+
+    #[test]
+    fn the_first_snapshot_is_the_baseline() {
+        let mut watcher = WindowWatcher::default();
+        let windows = [window(1, 0, 0, 800, 600), window(2, 0, 0, 800, 600)];
+
+        assert!(watcher.update(snapshot(&windows)).is_empty());
+        assert!(watcher.update(snapshot(&windows)).is_empty());
+        assert_eq!(watcher.update(snapshot(&windows[1..])).len(), 2);
+        assert!(diff(&Snapshot::default(), &Snapshot::default()).is_empty());
+    }
+
+    #[test]
+    fn clamps_the_interval() {
+        let handle = WatcherHandle { interval_ms: Arc::new(AtomicU64::new(0)) };
+
+        assert_eq!(handle.set_interval(Duration::from_millis(10)), MIN_INTERVAL);
+        assert_eq!(handle.set_interval(Duration::from_secs(2)), Duration::from_secs(2));
+        assert_eq!(handle.interval(), Duration::from_secs(2));
+        assert_eq!(handle.set_interval(Duration::from_secs(600)), MAX_INTERVAL);
+    }
+}
@@ -1,6 +1,26 @@
+use crate::error::FrameFitError;
+use crate::placement::{Anchor, Placement};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "linux")]
+mod x11;
+#[cfg(target_os = "linux")]
+mod sway;
+#[cfg(target_os = "linux")]
+mod hyprland;
+#[cfg(target_os = "linux")]
+mod kwin;
+#[cfg(all(test, unix))]
+mod fake_ipc;
+pub mod applescript;
+mod chrome;
+pub mod fake;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct WindowInfo {
     pub id: u32,
     pub title: String,
@@ -18,392 +38,1096 @@ pub struct ResizeRequest {
     pub height: i32,
 }
 
-#[cfg(target_os = "macos")]
-#[allow(deprecated)]
-use cocoa::base::id;
-
-#[cfg(target_os = "macos")]
-use core_foundation::{
-    array::{CFArrayGetCount, CFArrayGetValueAtIndex},
-    base::TCFType,
-    dictionary::{CFDictionaryGetValueIfPresent, CFDictionaryRef},
-    number::{CFNumberGetValue, CFNumberRef},
-    string::{CFString, CFStringGetCString, CFStringRef},
-};
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
 
-#[cfg(target_os = "macos")]
-use core_graphics::window::{kCGWindowListOptionOnScreenOnly, CGWindowListCopyWindowInfo};
+impl Rect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self { x, y, width, height }
+    }
 
-#[cfg(target_os = "macos")]
-use objc::{msg_send, sel, sel_impl};
+    /// Area shared with `other`, zero when they don't overlap.
+    pub fn overlap(&self, other: &Rect) -> i64 {
+        let width = (self.x + self.width).min(other.x + other.width) - self.x.max(other.x);
+        let height = (self.y + self.height).min(other.y + other.height) - self.y.max(other.y);
 
-#[cfg(target_os = "macos")]
-pub fn check_accessibility_permissions() -> bool {
-    use core_graphics::event::CGEvent;
+        if width > 0 && height > 0 {
+            i64::from(width) * i64::from(height)
+        } else {
+            0
+        }
+    }
+}
 
-    let event = CGEvent::new(core_graphics::event_source::CGEventSource::new(
-        core_graphics::event_source::CGEventSourceStateID::CombinedSessionState,
-    ).unwrap());
+/// A display in global desktop coordinates (logical pixels, origin at the
+/// top-left of the main display).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScreenInfo {
+    pub id: u32,
+    pub name: String,
+    /// Full bounds of the display.
+    pub frame: Rect,
+    /// The part of the display not covered by menu bars, docks and panels.
+    pub work_area: Rect,
+    /// Physical pixels per logical pixel.
+    pub scale_factor: f64,
+    pub is_main: bool,
+}
 
-    event.is_ok()
+/// Selects a display by its position in `list_screens` or by name.
+///
+/// Deserializes from either a number or a string, e.g. `1` or `"DP-2"`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum DisplayTarget {
+    Index(usize),
+    Name(String),
 }
 
-#[cfg(target_os = "macos")]
-pub fn list_windows() -> Result<Vec<WindowInfo>, String> {
-    unsafe {
-        let window_list = CGWindowListCopyWindowInfo(kCGWindowListOptionOnScreenOnly, 0);
+impl std::str::FromStr for DisplayTarget {
+    type Err = std::convert::Infallible;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value.parse() {
+            Ok(index) => Self::Index(index),
+            Err(_) => Self::Name(value.to_string()),
+        })
+    }
+}
 
-        if window_list.is_null() {
-            return Err("Failed to get window list".to_string());
+impl std::fmt::Display for DisplayTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "{}", index),
+            Self::Name(name) => write!(f, "{}", name),
         }
+    }
+}
 
-        let count = CFArrayGetCount(window_list);
-        let mut windows = Vec::new();
-        let excluded_apps = vec!["Dock", "Window Server", "FrameFit", "framefit"];
+/// Distances from the edges of a window frame to its content.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Insets {
+    pub top: i32,
+    pub left: i32,
+    pub bottom: i32,
+    pub right: i32,
+}
 
-        for i in 0..count {
-            let window_info = CFArrayGetValueAtIndex(window_list, i) as CFDictionaryRef;
+impl Insets {
+    pub fn horizontal(&self) -> i32 {
+        self.left + self.right
+    }
 
-            if let Some(window) = parse_window_info(window_info) {
-                if window.width > 50 
-                    && window.height > 50 
-                    && !excluded_apps.iter().any(|app| app.eq_ignore_ascii_case(&window.app_name))
-                {
-                    windows.push(window);
-                }
-            }
+    pub fn vertical(&self) -> i32 {
+        self.top + self.bottom
+    }
+}
+
+impl std::ops::Add for Insets {
+    type Output = Insets;
+
+    fn add(self, other: Insets) -> Insets {
+        Insets {
+            top: self.top + other.top,
+            left: self.left + other.left,
+            bottom: self.bottom + other.bottom,
+            right: self.right + other.right,
         }
+    }
+}
+
+/// What a backend knows about where a window's content starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentArea {
+    /// Measured all the way to the viewport, e.g. a browser's web view.
+    Viewport(Insets),
+    /// Only the window manager's decorations; toolbars drawn by the app
+    /// itself are not included.
+    Client(Insets),
+}
 
-        Ok(windows)
+impl WindowInfo {
+    pub fn frame(&self) -> Rect {
+        Rect::new(self.x, self.y, self.width, self.height)
     }
 }
 
-#[cfg(target_os = "macos")]
-unsafe fn parse_window_info(window_dict: CFDictionaryRef) -> Option<WindowInfo> {
-    let mut window_id: u32 = 0;
-    let mut x: i32 = 0;
-    let mut y: i32 = 0;
-    let mut width: i32 = 0;
-    let mut height: i32 = 0;
-    let mut title = String::new();
-    let mut app_name = String::new();
-
-    let key_window_id = CFString::new("kCGWindowNumber");
-    let key_bounds = CFString::new("kCGWindowBounds");
-    let key_name = CFString::new("kCGWindowName");
-    let key_owner = CFString::new("kCGWindowOwnerName");
-
-    let mut value: *const std::ffi::c_void = std::ptr::null();
-
-    if CFDictionaryGetValueIfPresent(
-        window_dict,
-        key_window_id.as_CFTypeRef() as *const _,
-        &mut value,
-    ) != 0
-    {
-        let num = value as CFNumberRef;
-        CFNumberGetValue(
-            num,
-            core_foundation::number::kCFNumberSInt32Type,
-            &mut window_id as *mut _ as *mut _,
-        );
+/// The platform specific part of window management.
+///
+/// Backends only implement the primitive operations; the resize and
+/// centering logic in this module is shared between all of them.
+pub trait WindowBackend: Send + Sync {
+    /// Short identifier used in logs, e.g. `"macos"`.
+    fn name(&self) -> &'static str;
+
+    fn check_permissions(&self) -> bool;
+
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, FrameFitError>;
+
+    fn get_frontmost_window(&self) -> Result<WindowInfo, FrameFitError>;
+
+    /// The windows and the frontmost one, which the window watcher needs on
+    /// every poll. Backends that learn both from one query override this.
+    fn list_windows_with_frontmost(&self) -> Result<(Vec<WindowInfo>, Option<WindowInfo>), FrameFitError> {
+        let windows = self.list_windows()?;
+        // No frontmost window isn't a failure, e.g. on an empty desktop
+        Ok((windows, self.get_frontmost_window().ok()))
     }
 
-    if CFDictionaryGetValueIfPresent(
-        window_dict,
-        key_name.as_CFTypeRef() as *const _,
-        &mut value,
-    ) != 0
-    {
-        let str_ref = value as CFStringRef;
-        let mut buffer = vec![0u8; 256];
-        if CFStringGetCString(
-            str_ref,
-            buffer.as_mut_ptr() as *mut _,
-            256,
-            core_foundation::string::kCFStringEncodingUTF8,
-        ) != 0
-        {
-            if let Ok(s) = std::ffi::CStr::from_ptr(buffer.as_ptr() as *const _).to_str() {
-                title = s.to_string();
-            }
-        }
+    fn focus_window(&self, window: &WindowInfo) -> Result<(), FrameFitError>;
+
+    fn move_window(&self, window: &WindowInfo, x: i32, y: i32) -> Result<(), FrameFitError>;
+
+    fn resize_window(&self, window: &WindowInfo, width: i32, height: i32) -> Result<(), FrameFitError>;
+
+    fn list_screens(&self) -> Result<Vec<ScreenInfo>, FrameFitError>;
+
+    /// Where the content of `window` sits within the frame that
+    /// `resize_window` sizes, or `None` when the backend can't tell.
+    fn content_area(&self, _window: &WindowInfo) -> Result<Option<ContentArea>, FrameFitError> {
+        Ok(None)
     }
 
-    if CFDictionaryGetValueIfPresent(
-        window_dict,
-        key_owner.as_CFTypeRef() as *const _,
-        &mut value,
-    ) != 0
-    {
-        let str_ref = value as CFStringRef;
-        let mut buffer = vec![0u8; 256];
-        if CFStringGetCString(
-            str_ref,
-            buffer.as_mut_ptr() as *mut _,
-            256,
-            core_foundation::string::kCFStringEncodingUTF8,
-        ) != 0
-        {
-            if let Ok(s) = std::ffi::CStr::from_ptr(buffer.as_ptr() as *const _).to_str() {
-                app_name = s.to_string();
-            }
-        }
+    /// The current frame of `window`, read back from the window system.
+    fn window_frame(&self, window: &WindowInfo) -> Result<Rect, FrameFitError> {
+        self.list_windows()?
+            .into_iter()
+            .find(|w| w.id == window.id)
+            .map(|w| w.frame())
+            .ok_or(FrameFitError::WindowNotFound { id: window.id })
     }
 
-    if CFDictionaryGetValueIfPresent(
-        window_dict,
-        key_bounds.as_CFTypeRef() as *const _,
-        &mut value,
-    ) != 0
-    {
-        let bounds_dict = value as CFDictionaryRef;
-
-        let key_x = CFString::new("X");
-        let key_y = CFString::new("Y");
-        let key_width = CFString::new("Width");
-        let key_height = CFString::new("Height");
-
-        let mut bound_value: *const std::ffi::c_void = std::ptr::null();
-
-        if CFDictionaryGetValueIfPresent(
-            bounds_dict,
-            key_x.as_CFTypeRef() as *const _,
-            &mut bound_value,
-        ) != 0
-        {
-            let num = bound_value as CFNumberRef;
-            let mut val: f64 = 0.0;
-            CFNumberGetValue(
-                num,
-                core_foundation::number::kCFNumberFloat64Type,
-                &mut val as *mut _ as *mut _,
-            );
-            x = val as i32;
+    /// Size constraints the app declares for `window`, as frame sizes.
+    fn size_hints(&self, _window: &WindowInfo) -> Result<SizeHints, FrameFitError> {
+        Ok(SizeHints::default())
+    }
+
+    /// How long to wait before reading a window back, for window systems
+    /// that apply requests asynchronously.
+    fn settle_delay(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+pub type SharedBackend = Arc<dyn WindowBackend>;
+
+/// Picks the backend for the platform the app is running on.
+///
+/// `FRAMEFIT_BACKEND=fake` swaps in a simulated desktop, which is handy for
+/// working on the frontend without a supported window system.
+pub fn detect_backend() -> SharedBackend {
+    if std::env::var("FRAMEFIT_BACKEND").as_deref() == Ok("fake") {
+        eprintln!("Using window backend: fake");
+        return Arc::new(fake::FakeWindowServer::demo());
+    }
+
+    #[cfg(target_os = "macos")]
+    let backend: SharedBackend = Arc::new(macos::MacOsBackend);
+
+    #[cfg(target_os = "linux")]
+    let backend: SharedBackend = detect_linux_backend();
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    let backend: SharedBackend = Arc::new(UnsupportedBackend);
+
+    eprintln!("Using window backend: {}", backend.name());
+    backend
+}
+
+#[cfg(target_os = "linux")]
+fn detect_linux_backend() -> SharedBackend {
+    // Wayland compositors only let their own IPC move windows; XWayland's
+    // DISPLAY would just expose the X11 clients, so check those first.
+    if let Some(backend) = sway::SwayBackend::from_env() {
+        return Arc::new(backend);
+    }
+    if let Some(backend) = hyprland::HyprlandBackend::from_env() {
+        return Arc::new(backend);
+    }
+    if let Some(backend) = kwin::KWinBackend::from_env() {
+        return Arc::new(backend);
+    }
+
+    if std::env::var_os("DISPLAY").is_some() {
+        match x11::X11Backend::connect() {
+            Ok(backend) => return Arc::new(backend),
+            Err(e) => eprintln!("Skipping X11 backend: {}", e),
         }
+    }
+
+    Arc::new(UnsupportedBackend)
+}
+
+/// Whether a window belongs to FrameFit itself, which must never be resized.
+pub(crate) fn is_framefit(app_name: &str) -> bool {
+    app_name.eq_ignore_ascii_case("framefit") || app_name.eq_ignore_ascii_case("com.eliostruyf.framefit")
+}
+
+/// Fallback for platforms without a window backend.
+pub struct UnsupportedBackend;
 
-        if CFDictionaryGetValueIfPresent(
-            bounds_dict,
-            key_y.as_CFTypeRef() as *const _,
-            &mut bound_value,
-        ) != 0
-        {
-            let num = bound_value as CFNumberRef;
-            let mut val: f64 = 0.0;
-            CFNumberGetValue(
-                num,
-                core_foundation::number::kCFNumberFloat64Type,
-                &mut val as *mut _ as *mut _,
-            );
-            y = val as i32;
+impl WindowBackend for UnsupportedBackend {
+    fn name(&self) -> &'static str {
+        "unsupported"
+    }
+
+    fn check_permissions(&self) -> bool {
+        false
+    }
+
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, FrameFitError> {
+        Err(FrameFitError::Unsupported)
+    }
+
+    fn get_frontmost_window(&self) -> Result<WindowInfo, FrameFitError> {
+        Err(FrameFitError::Unsupported)
+    }
+
+    fn focus_window(&self, _window: &WindowInfo) -> Result<(), FrameFitError> {
+        Err(FrameFitError::Unsupported)
+    }
+
+    fn move_window(&self, _window: &WindowInfo, _x: i32, _y: i32) -> Result<(), FrameFitError> {
+        Err(FrameFitError::Unsupported)
+    }
+
+    fn resize_window(&self, _window: &WindowInfo, _width: i32, _height: i32) -> Result<(), FrameFitError> {
+        Err(FrameFitError::Unsupported)
+    }
+
+    fn list_screens(&self) -> Result<Vec<ScreenInfo>, FrameFitError> {
+        Err(FrameFitError::Unsupported)
+    }
+}
+
+/// Finds the display a target refers to; names match case-insensitively.
+pub fn find_screen<'a>(screens: &'a [ScreenInfo], target: &DisplayTarget) -> Result<&'a ScreenInfo, FrameFitError> {
+    let screen = match target {
+        DisplayTarget::Index(index) => screens.get(*index),
+        DisplayTarget::Name(name) => screens.iter().find(|screen| screen.name.eq_ignore_ascii_case(name)),
+    };
+
+    screen.ok_or_else(|| FrameFitError::DisplayNotFound { display: target.to_string() })
+}
+
+/// The display showing most of the window, falling back to the main display
+/// for windows that are entirely off-screen.
+pub fn screen_for_window<'a>(screens: &'a [ScreenInfo], window: &Rect) -> Option<&'a ScreenInfo> {
+    screens
+        .iter()
+        .filter(|screen| screen.frame.overlap(window) > 0)
+        .max_by_key(|screen| screen.frame.overlap(window))
+        .or_else(|| screens.iter().find(|screen| screen.is_main))
+        .or_else(|| screens.first())
+}
+
+/// What to do when the requested size doesn't fit the display's work area.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OversizePolicy {
+    /// Shrink to the work area and report a warning.
+    #[default]
+    Clamp,
+    /// Fail with `FrameFitError::ExceedsWorkArea` without touching the window.
+    Refuse,
+}
+
+/// Which part of the window the requested size applies to.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeMode {
+    /// The outer window frame, including title bar and borders.
+    #[default]
+    Frame,
+    /// The area the app draws into; for browsers, the viewport.
+    Content,
+}
+
+/// Options for where a resized window ends up.
+#[derive(Debug, Clone, Default)]
+pub struct ResizeOptions {
+    /// Where the window goes within the work area of its display.
+    pub placement: Placement,
+    /// Move the window to this display instead of keeping it on its current one.
+    pub display: Option<DisplayTarget>,
+    pub oversize: OversizePolicy,
+    pub size_mode: SizeMode,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Size {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl std::str::FromStr for Size {
+    type Err = String;
+
+    /// Parses `WIDTHxHEIGHT`, e.g. `390x844` or `390×844`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid size '{}', expected WIDTHxHEIGHT", value);
+        let (width, height) = value
+            .split_once(['x', 'X', '×'])
+            .ok_or_else(invalid)?;
+        let width: i32 = width.trim().parse().map_err(|_| invalid())?;
+        let height: i32 = height.trim().parse().map_err(|_| invalid())?;
+
+        if width <= 0 || height <= 0 {
+            return Err(invalid());
         }
+        Ok(Self { width, height })
+    }
+}
 
-        if CFDictionaryGetValueIfPresent(
-            bounds_dict,
-            key_width.as_CFTypeRef() as *const _,
-            &mut bound_value,
-        ) != 0
-        {
-            let num = bound_value as CFNumberRef;
-            let mut val: f64 = 0.0;
-            CFNumberGetValue(
-                num,
-                core_foundation::number::kCFNumberFloat64Type,
-                &mut val as *mut _ as *mut _,
-            );
-            width = val as i32;
+impl std::fmt::Display for Size {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}×{}", self.width, self.height)
+    }
+}
+
+/// Size constraints of a window, as found in X11's `WM_NORMAL_HINTS`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SizeHints {
+    pub min: Option<Size>,
+    pub max: Option<Size>,
+    /// Steps the size changes in, e.g. a terminal's character cell.
+    pub increment: Option<Size>,
+}
+
+/// Why a window didn't end up at the size it was asked for.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SizeMismatch {
+    MinSize,
+    MaxSize,
+    Increments,
+    /// The app refused without declaring why.
+    Other,
+}
+
+impl SizeMismatch {
+    fn for_axis(target: i32, achieved: i32, min: Option<i32>, max: Option<i32>, increment: Option<i32>) -> Option<Self> {
+        if achieved == target {
+            None
+        } else if achieved > target && min.is_some_and(|min| achieved <= min) {
+            Some(Self::MinSize)
+        } else if achieved < target && max.is_some_and(|max| achieved >= max) {
+            Some(Self::MaxSize)
+        } else if increment.is_some_and(|step| step > 1 && (achieved - target).abs() < step) {
+            Some(Self::Increments)
+        } else {
+            Some(Self::Other)
         }
+    }
+
+    /// Explains the difference between `target` and `achieved` with the
+    /// window's size hints, preferring a declared constraint over `Other`.
+    pub fn between(target: Size, achieved: Size, hints: &SizeHints) -> Option<Self> {
+        let width = Self::for_axis(
+            target.width,
+            achieved.width,
+            hints.min.map(|s| s.width),
+            hints.max.map(|s| s.width),
+            hints.increment.map(|s| s.width),
+        );
+        let height = Self::for_axis(
+            target.height,
+            achieved.height,
+            hints.min.map(|s| s.height),
+            hints.max.map(|s| s.height),
+            hints.increment.map(|s| s.height),
+        );
 
-        if CFDictionaryGetValueIfPresent(
-            bounds_dict,
-            key_height.as_CFTypeRef() as *const _,
-            &mut bound_value,
-        ) != 0
-        {
-            let num = bound_value as CFNumberRef;
-            let mut val: f64 = 0.0;
-            CFNumberGetValue(
-                num,
-                core_foundation::number::kCFNumberFloat64Type,
-                &mut val as *mut _ as *mut _,
-            );
-            height = val as i32;
+        match (width, height) {
+            (Some(Self::Other), Some(reason)) | (Some(reason), _) | (None, Some(reason)) => Some(reason),
+            (None, None) => None,
         }
     }
+}
 
-    Some(WindowInfo {
-        id: window_id,
-        title,
-        app_name,
-        x,
-        y,
-        width,
-        height,
-    })
+/// The result of a successful resize. Sizes are in terms of `size_mode`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResizeOutcome {
+    pub window_id: u32,
+    /// The window's frame before the resize, for undoing it.
+    pub previous: Rect,
+    /// The window's frame after the resize, for redoing it.
+    pub frame: Rect,
+    pub size_mode: SizeMode,
+    pub requested: Size,
+    /// The size asked for after clamping to the work area.
+    pub applied: Size,
+    /// The size read back from the window after resizing.
+    pub achieved: Size,
+    /// Why `achieved` differs from `applied`, if it does.
+    pub mismatch: Option<SizeMismatch>,
+    /// Extra resize requests sent to get closer to `applied`.
+    pub corrections: u32,
+    /// Frame to content insets used in content mode.
+    pub insets: Option<Insets>,
+    pub warnings: Vec<String>,
 }
 
-#[cfg(target_os = "macos")]
-pub fn get_frontmost_window() -> Result<WindowInfo, String> {
-    let window_list = list_windows()?;
-    
-    let excluded_apps = vec!["framefit", "Dock", "Window Server"];
-    
-    for window in window_list {
-        if !excluded_apps.contains(&window.app_name.as_str()) {
-            return Ok(window);
+/// Upper bound on the extra resize requests after the first one.
+const MAX_CORRECTIONS: u32 = 2;
+
+fn read_size(backend: &dyn WindowBackend, window: &WindowInfo) -> Result<Size, FrameFitError> {
+    std::thread::sleep(backend.settle_delay());
+    let frame = backend.window_frame(window)?;
+    Ok(Size { width: frame.width, height: frame.height })
+}
+
+/// Reads the window back after a resize and, as long as the difference isn't
+/// explained by the app's size hints, asks again: unchanged as long as the
+/// window kept its old size, otherwise with the error compensated.
+/// Returns the frame size the window ended up with and the number of retries.
+fn verify_size(
+    backend: &dyn WindowBackend,
+    window: &WindowInfo,
+    target: Size,
+    hints: &SizeHints,
+) -> Result<(Size, u32), FrameFitError> {
+    let before = Size { width: window.width, height: window.height };
+    let mut request = target;
+    let mut achieved = read_size(backend, window)?;
+    let mut corrections = 0;
+
+    while SizeMismatch::between(target, achieved, hints) == Some(SizeMismatch::Other) && corrections < MAX_CORRECTIONS {
+        if achieved != before {
+            request = Size {
+                width: request.width + target.width - achieved.width,
+                height: request.height + target.height - achieved.height,
+            };
+        }
+        backend.resize_window(window, request.width, request.height)?;
+        corrections += 1;
+
+        // A window that doesn't budge won't on the next try either
+        let previous = achieved;
+        achieved = read_size(backend, window)?;
+        if achieved == previous {
+            break;
         }
     }
-    
-    Err("No suitable window found. Please open another application.".to_string())
+
+    Ok((achieved, corrections))
 }
 
-#[cfg(target_os = "macos")]
-#[allow(dead_code, unexpected_cfgs, deprecated)]
-unsafe fn nsstring_to_string(ns_string: id) -> String {
-    let utf8: *const u8 = msg_send![ns_string, UTF8String];
-    let len: usize = msg_send![ns_string, lengthOfBytesUsingEncoding: 4];
-    let bytes = std::slice::from_raw_parts(utf8, len);
-    String::from_utf8_lossy(bytes).to_string()
+/// Frame to content insets for `window`, combining what the backend measures
+/// with the known toolbar heights of browsers.
+pub fn content_insets(backend: &dyn WindowBackend, window: &WindowInfo) -> Result<Option<Insets>, FrameFitError> {
+    let toolbars = chrome::toolbar_insets(&window.app_name);
+
+    Ok(match backend.content_area(window)? {
+        Some(ContentArea::Viewport(insets)) => Some(insets),
+        Some(ContentArea::Client(insets)) => Some(insets + toolbars.unwrap_or_default()),
+        None => None,
+    })
 }
 
-#[cfg(target_os = "macos")]
-pub fn resize_window(window: &WindowInfo, width: i32, height: i32, center: bool) -> Result<(), String> {
-    if window.app_name == "framefit" {
-        return Err("Cannot resize the FrameFit app itself".to_string());
-    }
-
-    let escaped_app = window.app_name.replace("\"", "\\\"");
-    
-    // Get actual screen dimensions using Core Graphics
-    let screen_bounds = if center {
-        #[cfg(target_os = "macos")]
-        unsafe {
-            use core_graphics::display::{CGMainDisplayID, CGDisplayBounds};
-            let main_display = CGMainDisplayID();
-            let bounds = CGDisplayBounds(main_display);
-            Some((bounds.size.width as i32, bounds.size.height as i32))
+/// Position for a window of the given size on the work area `to`. Without a
+/// placement a window moving displays keeps its offset from the work area's
+/// corner on the old display `from`.
+fn place_on_screen(
+    placement: &Placement,
+    current: &Rect,
+    from: &Rect,
+    to: &Rect,
+    size: Size,
+    moves_display: bool,
+) -> Option<(i32, i32)> {
+    let (x, y) = match placement.position(to, size) {
+        Some(position) => position,
+        None if moves_display => (to.x + current.x - from.x, to.y + current.y - from.y),
+        None => return None,
+    };
+
+    // Keep the top-left corner (and so the title bar) inside the work area
+    let x = x.min(to.x + to.width - size.width).max(to.x);
+    let y = y.min(to.y + to.height - size.height).max(to.y);
+
+    Some((x, y))
+}
+
+pub fn resize_window(
+    backend: &dyn WindowBackend,
+    window: &WindowInfo,
+    width: i32,
+    height: i32,
+    options: &ResizeOptions,
+) -> Result<ResizeOutcome, FrameFitError> {
+    if is_framefit(&window.app_name) {
+        return Err(FrameFitError::SelfResizeRefused);
+    }
+
+    // Placement needs the displays; a plain resize can do without them
+    let needs_screens = options.placement.anchor != Anchor::Keep || options.display.is_some();
+    let screens = match backend.list_screens() {
+        Ok(screens) => screens,
+        Err(e) if !needs_screens => {
+            eprintln!("Could not list displays, skipping work area checks: {}", e);
+            Vec::new()
         }
-        #[cfg(not(target_os = "macos"))]
-        None
-    } else {
-        None
+        Err(e) => return Err(e),
     };
-    
-    let script = if center {
-        let (screen_width, screen_height) = screen_bounds.unwrap_or((1920, 1080));
-        format!(
-            r#"
-            activate application "{}"
-            tell application "System Events"
-                tell application process "{}"
-                    if (count of windows) > 0 then
-                        set frontWindow to first window
-                        -- First resize the window
-                        set size of frontWindow to {{{}, {}}}
-                        
-                        -- Then center it
-                        -- Use actual screen dimensions from Rust
-                        set screenWidth to {}
-                        set screenHeight to {}
-
-                        -- Add a pause to ensure the window has resized
-                        delay 0.1
-                        
-                        -- Try again to ensure correct screen dimensions
-                        set screenWidth to {}
-                        set screenHeight to {}
-                        
-                        -- Calculate center position
-                        set xPos to (screenWidth - {}) / 2
-                        set yPos to (screenHeight - {}) / 2 + 50
-                        
-                        -- Ensure positive coordinates
-                        if xPos < 0 then set xPos to 50
-                        if yPos < 0 then set yPos to 100
-                        
-                        set position of frontWindow to {{xPos, yPos}}
-                    else
-                        error "No windows found"
-                    end if
-                end tell
-            end tell
-            "#,
-            escaped_app, escaped_app, width, height, width, height, screen_width, screen_height, width, height
-        )
-    } else {
-        format!(
-            r#"
-            activate application "{}"
-            tell application "System Events"
-                tell application process "{}"
-                    if (count of windows) > 0 then
-                        set frontWindow to first window
-                        set size of frontWindow to {{{}, {}}}
-                    else
-                        error "No windows found"
-                    end if
-                end tell
-            end tell
-            "#,
-            escaped_app, escaped_app, width, height
-        )
+
+    // Resolve the target display first so a typo doesn't leave a half-done resize
+    let current = screen_for_window(&screens, &window.frame());
+    let target = match &options.display {
+        Some(display) => Some(find_screen(&screens, display)?),
+        None => current,
     };
 
-    execute_applescript(&script)
+    let requested = Size { width, height };
+    let mut warnings = Vec::new();
+
+    // In content mode, grow the frame by the chrome around the content
+    let insets = match options.size_mode {
+        SizeMode::Frame => None,
+        SizeMode::Content => {
+            let insets = content_insets(backend, window)?;
+            if insets.is_none() {
+                warnings.push(format!(
+                    "Couldn't measure the window chrome of {}, sized the whole window instead",
+                    window.app_name
+                ));
+            }
+            insets
+        }
+    };
+    let chrome = insets.unwrap_or_default();
+    let (width, height) = (width + chrome.horizontal(), height + chrome.vertical());
+
+    let (width, height) = match target {
+        Some(screen) if width > screen.work_area.width || height > screen.work_area.height => {
+            let area = screen.work_area;
+            if options.oversize == OversizePolicy::Refuse {
+                return Err(FrameFitError::ExceedsWorkArea {
+                    width,
+                    height,
+                    max_width: area.width,
+                    max_height: area.height,
+                });
+            }
+
+            warnings.push(format!(
+                "{}×{} doesn't fit the work area of {} ({}×{}), clamped to fit",
+                width, height, screen.name, area.width, area.height
+            ));
+            (width.min(area.width), height.min(area.height))
+        }
+        _ => (width, height),
+    };
+
+    backend.focus_window(window)?;
+    backend.resize_window(window, width, height)?;
+
+    // Apps may snap to their own constraints; place the size they settled on
+    let frame_target = Size { width, height };
+    let hints = backend.size_hints(window).unwrap_or_else(|e| {
+        eprintln!("Could not read size hints of window {}: {}", window.id, e);
+        SizeHints::default()
+    });
+    let (size, corrections) = verify_size(backend, window, frame_target, &hints)?;
+
+    let moves_display = options.display.is_some() && current.map(|s| s.id) != target.map(|s| s.id);
+    let mut position = (window.x, window.y);
+    match target {
+        Some(target) => {
+            let from = current.map_or(target.work_area, |screen| screen.work_area);
+            if let Some((x, y)) =
+                place_on_screen(&options.placement, &window.frame(), &from, &target.work_area, size, moves_display)
+            {
+                backend.move_window(window, x, y)?;
+                position = (x, y);
+            }
+        }
+        None if needs_screens => eprintln!("No displays reported, leaving window {} in place", window.id),
+        None => {}
+    }
+
+    let without_chrome = |size: Size| Size {
+        width: size.width - chrome.horizontal(),
+        height: size.height - chrome.vertical(),
+    };
+
+    Ok(ResizeOutcome {
+        window_id: window.id,
+        previous: window.frame(),
+        frame: Rect::new(position.0, position.1, size.width, size.height),
+        size_mode: options.size_mode,
+        requested,
+        applied: without_chrome(frame_target),
+        achieved: without_chrome(size),
+        mismatch: SizeMismatch::between(frame_target, size, &hints),
+        corrections,
+        insets,
+        warnings,
+    })
 }
 
-#[cfg(target_os = "macos")]
-pub fn resize_window_by_id(request: &ResizeRequest, center: bool) -> Result<(), String> {
-    let windows = list_windows()?;
+pub fn resize_frontmost(
+    backend: &dyn WindowBackend,
+    width: i32,
+    height: i32,
+    options: &ResizeOptions,
+) -> Result<ResizeOutcome, FrameFitError> {
+    let window = backend.get_frontmost_window()?;
+    resize_window(backend, &window, width, height, options)
+}
+
+pub fn resize_window_by_id(
+    backend: &dyn WindowBackend,
+    request: &ResizeRequest,
+    options: &ResizeOptions,
+) -> Result<ResizeOutcome, FrameFitError> {
+    let windows = backend.list_windows()?;
 
     for window in windows {
         if window.id == request.window_id {
-            return resize_window(&window, request.width, request.height, center);
+            return resize_window(backend, &window, request.width, request.height, options);
         }
     }
 
-    Err("Window not found".to_string())
+    Err(FrameFitError::WindowNotFound { id: request.window_id })
 }
 
-#[cfg(target_os = "macos")]
-fn execute_applescript(script: &str) -> Result<(), String> {
-    use std::process::Command;
+/// Moves `window` where `options` place it, e.g. centered, without resizing
+/// it, even when it's larger than the work area. Size options don't apply.
+pub fn place_window(
+    backend: &dyn WindowBackend,
+    window: &WindowInfo,
+    options: &ResizeOptions,
+) -> Result<ResizeOutcome, FrameFitError> {
+    if is_framefit(&window.app_name) {
+        return Err(FrameFitError::SelfResizeRefused);
+    }
 
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()
-        .map_err(|e| format!("Failed to execute AppleScript: {}", e))?;
+    let screens = backend.list_screens()?;
+    let current = screen_for_window(&screens, &window.frame());
+    let target = match &options.display {
+        Some(display) => Some(find_screen(&screens, display)?),
+        None => current,
+    };
 
-    if output.status.success() {
-        Ok(())
-    } else {
-        let error = String::from_utf8_lossy(&output.stderr);
-        Err(format!("AppleScript error: {}", error))
+    let size = Size { width: window.width, height: window.height };
+    let moves_display = options.display.is_some() && current.map(|s| s.id) != target.map(|s| s.id);
+    let mut position = (window.x, window.y);
+    match target {
+        Some(target) => {
+            let from = current.map_or(target.work_area, |screen| screen.work_area);
+            if let Some((x, y)) =
+                place_on_screen(&options.placement, &window.frame(), &from, &target.work_area, size, moves_display)
+            {
+                backend.focus_window(window)?;
+                backend.move_window(window, x, y)?;
+                position = (x, y);
+            }
+        }
+        None => eprintln!("No displays reported, leaving window {} in place", window.id),
     }
-}
 
-#[cfg(not(target_os = "macos"))]
-pub fn check_accessibility_permissions() -> bool {
-    false
+    Ok(ResizeOutcome {
+        window_id: window.id,
+        previous: window.frame(),
+        frame: Rect::new(position.0, position.1, size.width, size.height),
+        size_mode: SizeMode::Frame,
+        requested: size,
+        applied: size,
+        achieved: size,
+        mismatch: None,
+        corrections: 0,
+        insets: None,
+        warnings: Vec::new(),
+    })
 }
 
-#[cfg(not(target_os = "macos"))]
-pub fn list_windows() -> Result<Vec<WindowInfo>, String> {
-    Err("Not supported on this platform".to_string())
-}
+/// Puts a window back at `frame`, e.g. where it was before a resize.
+pub fn restore_window(backend: &dyn WindowBackend, window_id: u32, frame: &Rect) -> Result<(), FrameFitError> {
+    let window = backend
+        .list_windows()?
+        .into_iter()
+        .find(|window| window.id == window_id)
+        .ok_or(FrameFitError::WindowNotFound { id: window_id })?;
 
-#[cfg(not(target_os = "macos"))]
-pub fn get_frontmost_window() -> Result<WindowInfo, String> {
-    Err("Not supported on this platform".to_string())
+    backend.focus_window(&window)?;
+    backend.resize_window(&window, frame.width, frame.height)?;
+    backend.move_window(&window, frame.x, frame.y)
 }
 
-#[cfg(not(target_os = "macos"))]
-pub fn resize_window(_window: &WindowInfo, _width: i32, _height: i32, _center: bool) -> Result<(), String> {
-    Err("Not supported on this platform".to_string())
-}
+#[cfg(test)]
+mod tests {
+    use super::fake::{Failure, FakeWindow, FakeWindowServer, Operation};
+    use super::*;
+
+    const CENTER: ResizeOptions = placed(Placement::CENTER);
+    const IN_PLACE: ResizeOptions = placed(Placement::KEEP);
+
+    const fn placed(placement: Placement) -> ResizeOptions {
+        ResizeOptions { placement, display: None, oversize: OversizePolicy::Clamp, size_mode: SizeMode::Frame }
+    }
+
+    fn on_display(display: DisplayTarget, center: bool) -> ResizeOptions {
+        ResizeOptions { placement: Placement::from_center(center), display: Some(display), ..Default::default() }
+    }
+
+    fn desktop() -> FakeWindowServer {
+        FakeWindowServer::new()
+            .with_display("Built-in", Rect::new(0, 0, 1440, 900), Rect::new(0, 25, 1440, 875), 2.0)
+            .with_screen("DELL U2719D", 1440, 0, 2560, 1440)
+            .with_window(FakeWindow::new(1, "FrameFit", "FrameFit", 400, 25, 600, 850))
+            .with_window(FakeWindow::new(2, "Safari", "Apple", 100, 100, 1024, 768))
+            .with_window(FakeWindow::new(3, "Terminal", "zsh", 200, 200, 720, 480).min_size(400, 200))
+    }
+
+    fn content(options: ResizeOptions) -> ResizeOptions {
+        ResizeOptions { size_mode: SizeMode::Content, ..options }
+    }
+
+    #[test]
+    fn frontmost_window_skips_framefit() {
+        let server = desktop();
+
+        assert_eq!(server.get_frontmost_window().unwrap().app_name, "Safari");
+    }
+
+    #[test]
+    fn resizes_frontmost_window_in_place() {
+        let server = desktop();
+
+        resize_frontmost(&server, 390, 844, &IN_PLACE).unwrap();
+
+        let window = server.window(2).unwrap();
+        assert_eq!((window.x, window.y, window.width, window.height), (100, 100, 390, 844));
+    }
+
+    #[test]
+    fn centers_on_the_main_screen() {
+        let server = desktop();
+
+        resize_frontmost(&server, 800, 600, &CENTER).unwrap();
+
+        // Centered in the area below the menu bar
+        let window = server.window(2).unwrap();
+        assert_eq!((window.x, window.y), (320, 162));
+    }
+
+    #[test]
+    fn clamps_oversized_windows_to_the_work_area() {
+        let server = desktop();
+
+        let outcome = resize_frontmost(&server, 1920, 1080, &CENTER).unwrap();
+
+        let window = server.window(2).unwrap();
+        assert_eq!((window.x, window.y, window.width, window.height), (0, 25, 1440, 875));
+        assert_eq!(outcome.requested, Size { width: 1920, height: 1080 });
+        assert_eq!(outcome.applied, Size { width: 1440, height: 875 });
+        assert_eq!(outcome.warnings.len(), 1);
+    }
+
+    #[test]
+    fn refuses_oversized_windows_when_asked() {
+        let server = desktop();
+        let options = ResizeOptions { oversize: OversizePolicy::Refuse, ..CENTER };
+
+        assert_eq!(
+            resize_frontmost(&server, 1440, 900, &options).unwrap_err(),
+            FrameFitError::ExceedsWorkArea { width: 1440, height: 900, max_width: 1440, max_height: 875 }
+        );
+        assert_eq!(server.window(2).unwrap().width, 1024);
+    }
+
+    #[test]
+    fn checks_the_size_against_the_target_display() {
+        let server = desktop();
+        let options = ResizeOptions { oversize: OversizePolicy::Refuse, ..on_display(DisplayTarget::Index(1), true) };
+
+        let outcome = resize_frontmost(&server, 1920, 1080, &options).unwrap();
+
+        assert!(outcome.warnings.is_empty());
+        assert_eq!(server.window(2).unwrap().width, 1920);
+    }
+
+    #[test]
+    fn centers_on_the_display_the_window_is_on() {
+        let server = desktop().with_window(FakeWindow::new(4, "Figma", "Design", 1600, 200, 1200, 800));
+        let window = server.window(4).unwrap();
+
+        resize_window(&server, &window, 1000, 600, &CENTER).unwrap();
+
+        let window = server.window(4).unwrap();
+        assert_eq!((window.x, window.y), (1440 + 780, 420));
+    }
+
+    #[test]
+    fn moves_to_a_display_by_name() {
+        let server = desktop();
+
+        resize_frontmost(&server, 800, 600, &on_display(DisplayTarget::Name("dell u2719d".into()), true)).unwrap();
+
+        let window = server.window(2).unwrap();
+        assert_eq!((window.x, window.y), (1440 + 880, 420));
+    }
+
+    #[test]
+    fn moving_to_a_display_keeps_the_offset_in_the_work_area() {
+        let server = desktop();
+
+        resize_frontmost(&server, 800, 600, &on_display(DisplayTarget::Index(1), false)).unwrap();
+
+        let window = server.window(2).unwrap();
+        assert_eq!((window.x, window.y), (1440 + 100, 75));
+    }
+
+    #[test]
+    fn unknown_display_leaves_the_window_alone() {
+        let server = desktop();
+
+        assert_eq!(
+            resize_frontmost(&server, 800, 600, &on_display(DisplayTarget::Index(5), true)).unwrap_err(),
+            FrameFitError::DisplayNotFound { display: "5".to_string() }
+        );
+        assert_eq!(server.window(2).unwrap().width, 1024);
+    }
 
-#[cfg(not(target_os = "macos"))]
-pub fn resize_window_by_id(_request: &ResizeRequest, _center: bool) -> Result<(), String> {
-    Err("Not supported on this platform".to_string())
+    #[test]
+    fn anchors_to_a_corner_with_a_margin() {
+        let server = desktop();
+
+        resize_frontmost(&server, 800, 600, &placed(Placement::new(Anchor::BottomRight, 20))).unwrap();
+
+        let window = server.window(2).unwrap();
+        assert_eq!((window.x, window.y), (1440 - 800 - 20, 900 - 600 - 20));
+    }
+
+    #[test]
+    fn absolute_positions_are_relative_to_the_work_area() {
+        let server = desktop();
+        let options = ResizeOptions {
+            placement: Placement::new(Anchor::Absolute { x: 100, y: 50 }, 0),
+            ..on_display(DisplayTarget::Index(1), false)
+        };
+
+        resize_frontmost(&server, 800, 600, &options).unwrap();
+
+        let window = server.window(2).unwrap();
+        assert_eq!((window.x, window.y), (1440 + 100, 50));
+    }
+
+    #[test]
+    fn content_mode_grows_the_frame_by_the_chrome() {
+        let toolbar = Insets { top: 74, ..Insets::default() };
+        let server = desktop().with_window(
+            FakeWindow::new(4, "Google Chrome", "New Tab", 0, 25, 1200, 800).content_area(ContentArea::Viewport(toolbar)),
+        );
+        let window = server.window(4).unwrap();
+
+        let outcome = resize_window(&server, &window, 390, 664, &content(IN_PLACE)).unwrap();
+
+        let window = server.window(4).unwrap();
+        assert_eq!((window.width, window.height), (390, 738));
+        assert_eq!(outcome.applied, Size { width: 390, height: 664 });
+        assert_eq!(outcome.insets, Some(toolbar));
+    }
+
+    #[test]
+    fn content_mode_adds_known_browser_toolbars_to_decorations() {
+        let decorations = Insets { top: 30, left: 1, bottom: 1, right: 1 };
+        let server = desktop().with_window(
+            FakeWindow::new(4, "firefox", "Mozilla Firefox", 0, 25, 1200, 800)
+                .content_area(ContentArea::Client(decorations)),
+        );
+        let window = server.window(4).unwrap();
+
+        resize_window(&server, &window, 390, 600, &content(IN_PLACE)).unwrap();
+
+        let window = server.window(4).unwrap();
+        assert_eq!((window.width, window.height), (392, 600 + 31 + 85));
+    }
+
+    #[test]
+    fn content_mode_falls_back_to_the_frame() {
+        let server = desktop();
+
+        let outcome = resize_frontmost(&server, 390, 844, &content(IN_PLACE)).unwrap();
+
+        assert_eq!(server.window(2).unwrap().height, 844);
+        assert_eq!(outcome.insets, None);
+        assert_eq!(outcome.warnings.len(), 1);
+    }
+
+    #[test]
+    fn parses_display_targets() {
+        assert_eq!("1".parse::<DisplayTarget>().unwrap(), DisplayTarget::Index(1));
+        assert_eq!("DP-2".parse::<DisplayTarget>().unwrap(), DisplayTarget::Name("DP-2".to_string()));
+        assert_eq!(serde_json::from_str::<DisplayTarget>("0").unwrap(), DisplayTarget::Index(0));
+        assert_eq!(serde_json::from_str::<DisplayTarget>("\"eDP-1\"").unwrap(), DisplayTarget::Name("eDP-1".to_string()));
+    }
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!("390x844".parse::<Size>().unwrap(), Size { width: 390, height: 844 });
+        assert_eq!("1280 × 720".parse::<Size>().unwrap(), Size { width: 1280, height: 720 });
+        assert!("390".parse::<Size>().is_err());
+        assert!("0x844".parse::<Size>().is_err());
+    }
+
+    #[test]
+    fn resizing_by_id_focuses_the_window() {
+        let server = desktop();
+        let request = ResizeRequest { window_id: 3, width: 300, height: 100 };
+
+        let outcome = resize_window_by_id(&server, &request, &IN_PLACE).unwrap();
+
+        // The app enforces its minimum size
+        let window = server.window(3).unwrap();
+        assert_eq!((window.width, window.height), (400, 200));
+        assert_eq!(outcome.achieved, Size { width: 400, height: 200 });
+        assert_eq!(outcome.mismatch, Some(SizeMismatch::MinSize));
+        assert_eq!(outcome.corrections, 0);
+        assert_eq!(server.get_frontmost_window().unwrap().id, 3);
+    }
+
+    #[test]
+    fn centers_the_size_the_app_settled_on() {
+        let server = desktop();
+        let request = ResizeRequest { window_id: 3, width: 300, height: 100 };
+
+        resize_window_by_id(&server, &request, &CENTER).unwrap();
+
+        let window = server.window(3).unwrap();
+        assert_eq!((window.x, window.y, window.width, window.height), (520, 362, 400, 200));
+    }
+
+    #[test]
+    fn reports_snapping_to_size_increments() {
+        let server = FakeWindowServer::new()
+            .with_screen("Built-in", 0, 0, 1440, 900)
+            .with_window(FakeWindow::new(1, "kitty", "zsh", 0, 0, 800, 600).increments(8, 17));
+
+        let outcome = resize_frontmost(&server, 390, 844, &IN_PLACE).unwrap();
+
+        assert_eq!(outcome.applied, Size { width: 390, height: 844 });
+        assert_eq!(outcome.achieved, Size { width: 384, height: 833 });
+        assert_eq!(outcome.mismatch, Some(SizeMismatch::Increments));
+        assert_eq!(outcome.corrections, 0);
+    }
+
+    #[test]
+    fn reports_hitting_the_maximum_size() {
+        let server = FakeWindowServer::new()
+            .with_screen("Built-in", 0, 0, 1440, 900)
+            .with_window(FakeWindow::new(1, "Notes", "List", 0, 0, 500, 600).max_size(1000, 800));
+
+        let outcome = resize_frontmost(&server, 1280, 720, &IN_PLACE).unwrap();
+
+        assert_eq!(outcome.achieved, Size { width: 1000, height: 720 });
+        assert_eq!(outcome.mismatch, Some(SizeMismatch::MaxSize));
+    }
+
+    #[test]
+    fn corrects_windows_that_drift_from_the_requested_size() {
+        let server = FakeWindowServer::new()
+            .with_screen("Built-in", 0, 0, 1440, 900)
+            .with_window(FakeWindow::new(1, "xterm", "xterm", 0, 0, 500, 400).drift(4, 22));
+
+        let outcome = resize_frontmost(&server, 800, 600, &IN_PLACE).unwrap();
+
+        assert_eq!(outcome.achieved, Size { width: 800, height: 600 });
+        assert_eq!(outcome.mismatch, None);
+        assert_eq!(outcome.corrections, 1);
+    }
+
+    #[test]
+    fn retries_a_resize_that_was_ignored() {
+        let server = desktop();
+        server.fail_next(Operation::Resize, Failure::Ignored);
+
+        let outcome = resize_frontmost(&server, 800, 600, &IN_PLACE).unwrap();
+
+        assert_eq!(outcome.achieved, Size { width: 800, height: 600 });
+        assert_eq!(outcome.mismatch, None);
+        assert_eq!(outcome.corrections, 1);
+    }
+
+    #[test]
+    fn gives_up_on_windows_that_refuse_without_hints() {
+        let server = FakeWindowServer::new()
+            .with_screen("Built-in", 0, 0, 1440, 900)
+            .with_window(FakeWindow::new(1, "Calculator", "Calculator", 0, 0, 230, 400).fixed_size());
+
+        let outcome = resize_frontmost(&server, 800, 600, &IN_PLACE).unwrap();
+
+        assert_eq!(outcome.achieved, Size { width: 230, height: 400 });
+        assert_eq!(outcome.mismatch, Some(SizeMismatch::Other));
+        assert_eq!(outcome.corrections, 1);
+    }
+
+    #[test]
+    fn refuses_to_resize_framefit() {
+        let server = desktop();
+        let framefit = server.window(1).unwrap();
+
+        assert_eq!(
+            resize_window(&server, &framefit, 800, 600, &IN_PLACE).unwrap_err(),
+            FrameFitError::SelfResizeRefused
+        );
+        assert_eq!(server.window(1).unwrap().width, 600);
+    }
+
+    #[test]
+    fn resizing_an_unknown_window_fails() {
+        let server = desktop();
+        let request = ResizeRequest { window_id: 42, width: 800, height: 600 };
+
+        assert_eq!(
+            resize_window_by_id(&server, &request, &IN_PLACE).unwrap_err(),
+            FrameFitError::WindowNotFound { id: 42 }
+        );
+    }
+
+    #[test]
+    fn restores_the_frame_before_a_resize() {
+        let server = desktop();
+
+        let outcome = resize_frontmost(&server, 390, 844, &CENTER).unwrap();
+        assert_eq!(server.window(outcome.window_id).unwrap().frame(), outcome.frame);
+        restore_window(&server, outcome.window_id, &outcome.previous).unwrap();
+
+        let window = server.window(2).unwrap();
+        assert_eq!(window.frame(), Rect::new(100, 100, 1024, 768));
+    }
+
+    #[test]
+    fn reports_windows_that_vanish_mid_resize() {
+        let server = desktop();
+        server.fail_next(Operation::Resize, Failure::WindowVanished);
+
+        assert_eq!(
+            resize_frontmost(&server, 800, 600, &CENTER).unwrap_err(),
+            FrameFitError::WindowNotFound { id: 2 }
+        );
+        assert!(server.window(2).is_none());
+    }
+
+    #[test]
+    fn reports_denied_permissions() {
+        let server = desktop();
+        server.fail_next(Operation::ListWindows, Failure::PermissionDenied);
+
+        assert_eq!(server.list_windows().unwrap_err(), FrameFitError::PermissionDenied);
+        assert!(server.list_windows().is_ok());
+
+        server.deny_permissions();
+        assert!(!server.check_permissions());
+        assert_eq!(
+            resize_frontmost(&server, 800, 600, &IN_PLACE).unwrap_err(),
+            FrameFitError::PermissionDenied
+        );
+    }
 }
@@ -0,0 +1,290 @@
+//! Builds the System Events scripts of the macOS backend. Plain Rust, so the
+//! scripts are tested on every platform.
+
+use super::WindowInfo;
+
+/// Raised by the scripts when the targeted window isn't there.
+pub const WINDOW_NOT_FOUND: &str = "FrameFit: window not found";
+
+/// How many levels of containers the content insets script searches for the
+/// web area, with room to spare for deeply nested browser UIs.
+const MAX_CONTENT_DEPTH: usize = 10;
+
+/// Roles of the elements the web area can be nested in.
+const CONTAINER_ROLES: [&str; 4] = ["AXGroup", "AXSplitGroup", "AXTabGroup", "AXScrollArea"];
+
+/// Which of an app's windows a script acts on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowSelector {
+    /// The `index`th window (from 1, front to back) with this title.
+    Title { title: String, index: usize },
+    /// The `index`th window of the app, for windows without a known title.
+    Index(usize),
+}
+
+impl WindowSelector {
+    /// Selects `window` by its title, counting the windows of the same app
+    /// in front of it in `windows` (front to back, like `list_windows()`).
+    pub fn for_window(window: &WindowInfo, windows: &[WindowInfo]) -> Self {
+        let in_front = windows.iter().take_while(|other| other.id != window.id);
+        let same_app = |other: &&WindowInfo| other.app_name == window.app_name;
+
+        if window.title.is_empty() {
+            Self::Index(in_front.filter(same_app).count() + 1)
+        } else {
+            let index = in_front.filter(same_app).filter(|other| other.title == window.title).count() + 1;
+            Self::Title { title: window.title.clone(), index }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowAction {
+    Resize { width: i32, height: i32 },
+    Move { x: i32, y: i32 },
+    /// Brings the app and the window to the front.
+    Focus,
+    /// Prints the insets of the web area, `top,left,bottom,right`, or nothing
+    /// when the window doesn't have one.
+    ContentInsets,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowCommand {
+    /// The process name, as System Events knows it.
+    pub app_name: String,
+    pub window: WindowSelector,
+    pub action: WindowAction,
+}
+
+impl WindowCommand {
+    pub fn script(&self) -> String {
+        let mut script = Script::default();
+        script.open("tell application \"System Events\"");
+        script.open(&format!("tell application process {}", string(&self.app_name)));
+
+        if self.action == WindowAction::Focus {
+            script.line("set frontmost to true");
+        }
+        self.select_window(&mut script);
+
+        match self.action {
+            WindowAction::Resize { width, height } => {
+                script.line(&format!("set size of w to {{{}, {}}}", width, height));
+            }
+            WindowAction::Move { x, y } => script.line(&format!("set position of w to {{{}, {}}}", x, y)),
+            WindowAction::Focus => script.line("perform action \"AXRaise\" of w"),
+            WindowAction::ContentInsets => {
+                // Browsers expose the rendered page as an AXWebArea in a
+                // scroll area. Search level by level through the containers
+                // only, as `entire contents` also walks every page element.
+                script.line("set {wx, wy} to position of w");
+                script.line("set {ww, wh} to size of w");
+                script.line("set containers to {w}");
+                script.open(&format!("repeat {} times", MAX_CONTENT_DEPTH));
+                script.line("set children to {}");
+                script.open("repeat with c in containers");
+                script.line("set webAreas to UI elements of (contents of c) whose role is \"AXWebArea\"");
+                script.open("if webAreas is not {} then");
+                script.line("set e to item 1 of webAreas");
+                script.line("set {cx, cy} to position of e");
+                script.line("set {cw, ch} to size of e");
+                script.line(
+                    "return ((cy - wy) as text) & \",\" & ((cx - wx) as text) & \",\" & \
+                     ((wy + wh - cy - ch) as text) & \",\" & ((wx + ww - cx - cw) as text)",
+                );
+                script.close("end if");
+                script.line(&format!(
+                    "set children to children & (UI elements of (contents of c) whose {})",
+                    CONTAINER_ROLES.iter().map(|role| format!("role is \"{}\"", role)).collect::<Vec<_>>().join(" or ")
+                ));
+                script.close("end repeat");
+                script.line("if children is {} then exit repeat");
+                script.line("set containers to children");
+                script.close("end repeat");
+                script.line("return \"\"");
+            }
+        }
+
+        script.close("end tell");
+        script.close("end tell");
+        script.text
+    }
+
+    fn select_window(&self, script: &mut Script) {
+        let not_found = format!("error {}", string(WINDOW_NOT_FOUND));
+
+        match &self.window {
+            WindowSelector::Title { title, index } => {
+                script.line(&format!("set candidates to every window whose name is {}", string(title)));
+                script.line(&format!("if (count of candidates) < {} then {}", index, not_found));
+                script.line(&format!("set w to item {} of candidates", index));
+            }
+            WindowSelector::Index(index) => {
+                script.line(&format!("if (count of windows) < {} then {}", index, not_found));
+                script.line(&format!("set w to window {}", index));
+            }
+        }
+    }
+}
+
+/// An expression evaluating to `value`. Quotes and backslashes are escaped,
+/// tabs and line breaks written as escape sequences; other control
+/// characters have no escape and are joined in with `character id`, in
+/// parentheses so the result can go wherever a literal can.
+pub fn string(value: &str) -> String {
+    let mut parts = Vec::new();
+    let mut literal = String::from("\"");
+
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c.is_control() => {
+                literal.push('"');
+                parts.push(std::mem::replace(&mut literal, String::from("\"")));
+                parts.push(format!("(character id {})", c as u32));
+            }
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    parts.push(literal);
+
+    if parts.len() == 1 {
+        return parts.remove(0);
+    }
+    parts.retain(|part| part != "\"\"");
+    format!("({})", parts.join(" & "))
+}
+
+/// Script text with nested blocks indented.
+#[derive(Default)]
+struct Script {
+    text: String,
+    depth: usize,
+}
+
+impl Script {
+    fn line(&mut self, line: &str) {
+        self.text.push_str(&"    ".repeat(self.depth));
+        self.text.push_str(line);
+        self.text.push('\n');
+    }
+
+    fn open(&mut self, line: &str) {
+        self.line(line);
+        self.depth += 1;
+    }
+
+    fn close(&mut self, line: &str) {
+        self.depth -= 1;
+        self.line(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// App and window names that broke or could inject into the old scripts.
+    const TRICKY_NAMES: [(&str, &str, &str); 8] = [
+        ("plain", "Safari", "Apple"),
+        ("quotes", "\"Quoted\" App", "Say \"hi\""),
+        ("backslashes", "C:\\Tools\\", "ends with \\"),
+        ("injection", "Finder\" to quit\ntell application \"Terminal", "\" & (do shell script \"id\") & \""),
+        ("whitespace", "Tab\tApp", "line one\r\nline two"),
+        ("control_characters", "Bell\u{7}App", "\u{1b}[31mred"),
+        ("unicode", "Café ☕", "日本語 — ¬ « »"),
+        ("empty_title", "Simulator", ""),
+    ];
+
+    fn command(app_name: &str, window: WindowSelector, action: WindowAction) -> WindowCommand {
+        WindowCommand { app_name: app_name.to_string(), window, action }
+    }
+
+    fn title(title: &str) -> WindowSelector {
+        WindowSelector::Title { title: title.to_string(), index: 1 }
+    }
+
+    /// Compares against `tests/fixtures/applescript/NAME.applescript`, or
+    /// rewrites it when `UPDATE_GOLDEN` is set.
+    fn assert_golden(name: &str, actual: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/applescript")
+            .join(format!("{}.applescript", name));
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("Missing {}, run with UPDATE_GOLDEN=1: {}", path.display(), e));
+        assert_eq!(actual, expected, "{} differs from the golden file", name);
+    }
+
+    #[test]
+    fn escapes_string_literals() {
+        assert_eq!(string("Safari"), r#""Safari""#);
+        assert_eq!(string(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+        assert_eq!(string("one\ntwo\tthree"), r#""one\ntwo\tthree""#);
+        assert_eq!(string("a\u{0}b"), r#"("a" & (character id 0) & "b")"#);
+        assert_eq!(string("\u{7}"), "((character id 7))");
+        assert_eq!(string(""), r#""""#);
+    }
+
+    #[test]
+    fn selects_windows_by_title_and_index() {
+        let window = |id, app_name: &str, title: &str| WindowInfo {
+            id,
+            title: title.to_string(),
+            app_name: app_name.to_string(),
+            x: 0,
+            y: 0,
+            width: 800,
+            height: 600,
+        };
+        let windows = [
+            window(1, "Safari", "Apple"),
+            window(2, "Code", "Apple"),
+            window(3, "Safari", ""),
+            window(4, "Safari", "Apple"),
+            window(5, "Safari", ""),
+        ];
+
+        assert_eq!(WindowSelector::for_window(&windows[0], &windows), title("Apple"));
+        assert_eq!(
+            WindowSelector::for_window(&windows[3], &windows),
+            WindowSelector::Title { title: "Apple".to_string(), index: 2 }
+        );
+        assert_eq!(WindowSelector::for_window(&windows[4], &windows), WindowSelector::Index(4));
+    }
+
+    #[test]
+    fn generates_window_scripts() {
+        let resize = WindowAction::Resize { width: 1280, height: 720 };
+
+        assert_golden("resize", &command("Safari", title("Apple"), resize).script());
+        assert_golden("move", &command("Safari", title("Apple"), WindowAction::Move { x: -20, y: 40 }).script());
+        assert_golden("focus", &command("Safari", title("Apple"), WindowAction::Focus).script());
+        let content_insets = command("Google Chrome", title("Storybook"), WindowAction::ContentInsets);
+        assert_golden("content_insets", &content_insets.script());
+    }
+
+    #[test]
+    fn generates_scripts_for_tricky_names() {
+        let mut scripts = String::new();
+        for (case, app_name, window_title) in TRICKY_NAMES {
+            let selector = if window_title.is_empty() { WindowSelector::Index(1) } else { title(window_title) };
+            let script = command(app_name, selector, WindowAction::Resize { width: 390, height: 844 }).script();
+            scripts.push_str(&format!("-- {}\n{}\n", case, script));
+        }
+
+        assert_golden("tricky_names", &scripts);
+    }
+}
@@ -0,0 +1,26 @@
+use super::Insets;
+
+/// Toolbar heights of common browsers at their default settings, keyed by
+/// X11 class / Wayland app id. Toolbars are drawn by the app inside its own
+/// window, so window managers can't report them like decorations.
+const BROWSER_TOOLBARS: &[(&str, i32)] = &[
+    ("firefox", 85),
+    ("firefox-esr", 85),
+    ("librewolf", 85),
+    ("google-chrome", 87),
+    ("chromium", 87),
+    ("chromium-browser", 87),
+    ("brave-browser", 87),
+    ("microsoft-edge", 87),
+    ("vivaldi-stable", 83),
+    ("org.gnome.epiphany", 46),
+];
+
+/// Space taken by the app's own toolbars above its content, if it is a
+/// known browser.
+pub fn toolbar_insets(app_name: &str) -> Option<Insets> {
+    BROWSER_TOOLBARS
+        .iter()
+        .find(|(app, _)| app.eq_ignore_ascii_case(app_name))
+        .map(|(_, top)| Insets { top: *top, ..Insets::default() })
+}
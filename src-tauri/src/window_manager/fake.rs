@@ -0,0 +1,333 @@
+use super::{is_framefit, ContentArea, Insets, Rect, ScreenInfo, Size, SizeHints, WindowBackend, WindowInfo};
+use crate::error::FrameFitError;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// The backend operations a failure can be injected into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    ListWindows,
+    FrontmostWindow,
+    Focus,
+    Move,
+    Resize,
+    ListScreens,
+}
+
+/// Failures the fake server can simulate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// The window was closed between listing and acting on it.
+    WindowVanished,
+    PermissionDenied,
+    /// The request was accepted but had no effect, like a window manager
+    /// dropping a resize that arrived too early.
+    Ignored,
+}
+
+/// A scripted window, optionally with the size constraints real apps impose.
+#[derive(Debug, Clone)]
+pub struct FakeWindow {
+    pub info: WindowInfo,
+    pub min_size: Option<(i32, i32)>,
+    pub max_size: Option<(i32, i32)>,
+    /// Snaps the size down to multiples of these, like a terminal's cells.
+    pub increments: Option<(i32, i32)>,
+    /// Added to every requested size without being declared in the size
+    /// hints, like decorations a window manager forgets to account for.
+    pub drift: (i32, i32),
+    /// Ignores resizes without declaring why.
+    pub fixed_size: bool,
+    pub content_area: Option<ContentArea>,
+}
+
+impl FakeWindow {
+    pub fn new(id: u32, app_name: &str, title: &str, x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            info: WindowInfo {
+                id,
+                title: title.to_string(),
+                app_name: app_name.to_string(),
+                x,
+                y,
+                width,
+                height,
+            },
+            min_size: None,
+            max_size: None,
+            increments: None,
+            drift: (0, 0),
+            fixed_size: false,
+            content_area: None,
+        }
+    }
+
+    pub fn min_size(mut self, width: i32, height: i32) -> Self {
+        self.min_size = Some((width, height));
+        self
+    }
+
+    pub fn max_size(mut self, width: i32, height: i32) -> Self {
+        self.max_size = Some((width, height));
+        self
+    }
+
+    pub fn increments(mut self, width: i32, height: i32) -> Self {
+        self.increments = Some((width, height));
+        self
+    }
+
+    pub fn drift(mut self, width: i32, height: i32) -> Self {
+        self.drift = (width, height);
+        self
+    }
+
+    pub fn fixed_size(mut self) -> Self {
+        self.fixed_size = true;
+        self
+    }
+
+    pub fn content_area(mut self, content_area: ContentArea) -> Self {
+        self.content_area = Some(content_area);
+        self
+    }
+}
+
+#[derive(Default)]
+struct State {
+    // Front to back, like the window lists of the real backends
+    windows: Vec<FakeWindow>,
+    screens: Vec<ScreenInfo>,
+    failures: HashMap<Operation, Failure>,
+    permissions_denied: bool,
+}
+
+impl State {
+    /// Whether `operation` should take effect, or the injected error.
+    fn check(&mut self, operation: Operation, window_id: Option<u32>) -> Result<bool, FrameFitError> {
+        if self.permissions_denied {
+            return Err(FrameFitError::PermissionDenied);
+        }
+
+        match self.failures.remove(&operation) {
+            Some(Failure::PermissionDenied) => Err(FrameFitError::PermissionDenied),
+            Some(Failure::WindowVanished) => match window_id {
+                Some(id) => {
+                    self.windows.retain(|w| w.info.id != id);
+                    Err(FrameFitError::WindowNotFound { id })
+                }
+                None => Err(FrameFitError::NoWindows),
+            },
+            Some(Failure::Ignored) => Ok(false),
+            None => Ok(true),
+        }
+    }
+}
+
+/// An in-memory window server for deterministic tests of the shared
+/// window logic and the Tauri commands, on any platform.
+#[derive(Default)]
+pub struct FakeWindowServer {
+    state: Mutex<State>,
+}
+
+impl FakeWindowServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a display without panels; the first one added is the main display.
+    pub fn with_screen(self, name: &str, x: i32, y: i32, width: i32, height: i32) -> Self {
+        let frame = Rect::new(x, y, width, height);
+        self.with_display(name, frame, frame, 1.0)
+    }
+
+    pub fn with_display(self, name: &str, frame: Rect, work_area: Rect, scale_factor: f64) -> Self {
+        {
+            let mut state = self.state.lock().unwrap();
+            let id = state.screens.len() as u32;
+            state.screens.push(ScreenInfo {
+                id,
+                name: name.to_string(),
+                frame,
+                work_area,
+                scale_factor,
+                is_main: id == 0,
+            });
+        }
+        self
+    }
+
+    /// Adds a window behind all existing ones.
+    pub fn with_window(self, window: FakeWindow) -> Self {
+        self.state.lock().unwrap().windows.push(window);
+        self
+    }
+
+    /// Makes the next call of `operation` fail.
+    pub fn fail_next(&self, operation: Operation, failure: Failure) {
+        self.state.lock().unwrap().failures.insert(operation, failure);
+    }
+
+    pub fn deny_permissions(&self) {
+        self.state.lock().unwrap().permissions_denied = true;
+    }
+
+    pub fn window(&self, id: u32) -> Option<WindowInfo> {
+        let state = self.state.lock().unwrap();
+        state.windows.iter().find(|w| w.info.id == id).map(|w| w.info.clone())
+    }
+
+    /// A small desktop for running the app without a real window system.
+    pub fn demo() -> Self {
+        Self::new()
+            .with_display(
+                "Built-in Display",
+                Rect::new(0, 0, 1512, 982),
+                Rect::new(0, 33, 1512, 949),
+                2.0,
+            )
+            .with_window(
+                FakeWindow::new(1, "Safari", "Apple", 120, 80, 1024, 768)
+                    .content_area(ContentArea::Viewport(Insets { top: 52, ..Insets::default() })),
+            )
+            .with_window(
+                FakeWindow::new(2, "Terminal", "~ — zsh", 300, 200, 720, 480)
+                    .min_size(300, 150)
+                    .increments(8, 17),
+            )
+            .with_window(FakeWindow::new(3, "Notes", "Shopping list", 600, 120, 500, 600).max_size(1200, 900))
+    }
+
+    fn with_window_mut(
+        &self,
+        operation: Operation,
+        window: &WindowInfo,
+        action: impl FnOnce(&mut Vec<FakeWindow>, usize),
+    ) -> Result<(), FrameFitError> {
+        let mut state = self.state.lock().unwrap();
+        let applies = state.check(operation, Some(window.id))?;
+
+        let index = state
+            .windows
+            .iter()
+            .position(|w| w.info.id == window.id)
+            .ok_or(FrameFitError::WindowNotFound { id: window.id })?;
+
+        if applies {
+            action(&mut state.windows, index);
+        }
+        Ok(())
+    }
+}
+
+impl WindowBackend for FakeWindowServer {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn check_permissions(&self) -> bool {
+        !self.state.lock().unwrap().permissions_denied
+    }
+
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, FrameFitError> {
+        let mut state = self.state.lock().unwrap();
+        state.check(Operation::ListWindows, None)?;
+
+        Ok(state
+            .windows
+            .iter()
+            .map(|w| w.info.clone())
+            .filter(|w| !is_framefit(&w.app_name))
+            .collect())
+    }
+
+    fn get_frontmost_window(&self) -> Result<WindowInfo, FrameFitError> {
+        let mut state = self.state.lock().unwrap();
+        state.check(Operation::FrontmostWindow, None)?;
+
+        state
+            .windows
+            .iter()
+            .map(|w| w.info.clone())
+            .find(|w| !is_framefit(&w.app_name))
+            .ok_or(FrameFitError::NoWindows)
+    }
+
+    fn focus_window(&self, window: &WindowInfo) -> Result<(), FrameFitError> {
+        self.with_window_mut(Operation::Focus, window, |windows, index| {
+            let focused = windows.remove(index);
+            windows.insert(0, focused);
+        })
+    }
+
+    fn move_window(&self, window: &WindowInfo, x: i32, y: i32) -> Result<(), FrameFitError> {
+        self.with_window_mut(Operation::Move, window, |windows, index| {
+            windows[index].info.x = x;
+            windows[index].info.y = y;
+        })
+    }
+
+    fn resize_window(&self, window: &WindowInfo, width: i32, height: i32) -> Result<(), FrameFitError> {
+        self.with_window_mut(Operation::Resize, window, |windows, index| {
+            let window = &mut windows[index];
+            if window.fixed_size {
+                return;
+            }
+
+            let (mut width, mut height) = (width + window.drift.0, height + window.drift.1);
+
+            // Like real apps, silently snap to the size constraints
+            if let Some((step_width, step_height)) = window.increments {
+                width -= width % step_width;
+                height -= height % step_height;
+            }
+            if let Some((min_width, min_height)) = window.min_size {
+                width = width.max(min_width);
+                height = height.max(min_height);
+            }
+            if let Some((max_width, max_height)) = window.max_size {
+                width = width.min(max_width);
+                height = height.min(max_height);
+            }
+
+            window.info.width = width;
+            window.info.height = height;
+        })
+    }
+
+    fn list_screens(&self) -> Result<Vec<ScreenInfo>, FrameFitError> {
+        let mut state = self.state.lock().unwrap();
+        state.check(Operation::ListScreens, None)?;
+
+        Ok(state.screens.clone())
+    }
+
+    fn content_area(&self, window: &WindowInfo) -> Result<Option<ContentArea>, FrameFitError> {
+        let state = self.state.lock().unwrap();
+        let window = state
+            .windows
+            .iter()
+            .find(|w| w.info.id == window.id)
+            .ok_or(FrameFitError::WindowNotFound { id: window.id })?;
+
+        Ok(window.content_area)
+    }
+
+    fn size_hints(&self, window: &WindowInfo) -> Result<SizeHints, FrameFitError> {
+        let state = self.state.lock().unwrap();
+        let window = state
+            .windows
+            .iter()
+            .find(|w| w.info.id == window.id)
+            .ok_or(FrameFitError::WindowNotFound { id: window.id })?;
+        let size = |(width, height)| Size { width, height };
+
+        Ok(SizeHints {
+            min: window.min_size.map(size),
+            max: window.max_size.map(size),
+            increment: window.increments.map(size),
+        })
+    }
+}
@@ -0,0 +1,58 @@
+//! A tiny Unix socket server for replaying recorded compositor IPC replies
+//! in tests.
+
+use std::io::Write;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+static NEXT_SOCKET: AtomicUsize = AtomicUsize::new(0);
+
+pub struct FakeIpcServer {
+    pub path: PathBuf,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeIpcServer {
+    /// Serves every connection with `handler`, which reads one request and
+    /// returns it together with the raw reply to send back. Requests are
+    /// recorded before replying so tests can assert on them right away.
+    pub fn spawn<F>(name: &str, handler: F) -> Self
+    where
+        F: Fn(&mut UnixStream) -> Option<(String, Vec<u8>)> + Send + 'static,
+    {
+        let path = std::env::temp_dir().join(format!(
+            "framefit-{}-{}-{}.sock",
+            name,
+            std::process::id(),
+            NEXT_SOCKET.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let listener = UnixListener::bind(&path).expect("failed to bind fake IPC socket");
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                if let Some((request, reply)) = handler(&mut stream) {
+                    recorded.lock().unwrap().push(request);
+                    let _ = stream.write_all(&reply);
+                }
+            }
+        });
+
+        Self { path, requests }
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for FakeIpcServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
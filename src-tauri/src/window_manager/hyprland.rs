@@ -0,0 +1,338 @@
+use super::{is_framefit, ContentArea, Insets, Rect, ScreenInfo, WindowBackend, WindowInfo};
+use crate::error::FrameFitError;
+
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Client {
+    address: String,
+    #[serde(default = "default_true")]
+    mapped: bool,
+    #[serde(default)]
+    hidden: bool,
+    at: [i32; 2],
+    size: [i32; 2],
+    class: String,
+    title: String,
+    #[serde(default, rename = "focusHistoryID")]
+    focus_history_id: i32,
+}
+
+impl Client {
+    fn to_window_info(&self) -> Option<WindowInfo> {
+        Some(WindowInfo {
+            id: window_id(&self.address)?,
+            title: self.title.clone(),
+            app_name: self.class.clone(),
+            x: self.at[0],
+            y: self.at[1],
+            width: self.size[0],
+            height: self.size[1],
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Monitor {
+    name: String,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    #[serde(default = "default_scale")]
+    scale: f64,
+    #[serde(default)]
+    transform: i32,
+    /// Space taken by bars on the left, top, right and bottom edges
+    #[serde(default)]
+    reserved: [i32; 4],
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// Hyprland addresses are 64-bit pointers; `WindowInfo` ids are the lower
+/// 32 bits, which are unique in practice and mapped back on every call.
+fn window_id(address: &str) -> Option<u32> {
+    let hex = address.trim_start_matches("0x");
+    u64::from_str_radix(hex, 16).ok().map(|address| address as u32)
+}
+
+/// Window backend for Hyprland, talking to the `hyprctl` request socket of
+/// the instance named by `HYPRLAND_INSTANCE_SIGNATURE`.
+pub struct HyprlandBackend {
+    socket_path: PathBuf,
+}
+
+impl HyprlandBackend {
+    pub fn from_env() -> Option<Self> {
+        let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+
+        // Hyprland moved its sockets from /tmp to the runtime dir in 0.40
+        let runtime_socket = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(|dir| PathBuf::from(dir).join("hypr").join(&signature).join(".socket.sock"));
+        let socket_path = match runtime_socket {
+            Some(path) if path.exists() => path,
+            _ => PathBuf::from("/tmp/hypr").join(&signature).join(".socket.sock"),
+        };
+
+        Some(Self::new(socket_path))
+    }
+
+    pub fn new(socket_path: PathBuf) -> Self {
+        Self { socket_path }
+    }
+
+    fn request(&self, command: &str) -> Result<String, FrameFitError> {
+        let mut stream = UnixStream::connect(&self.socket_path)
+            .map_err(|e| FrameFitError::unavailable(format!("Failed to connect to Hyprland socket: {}", e)))?;
+        stream.write_all(command.as_bytes()).map_err(ipc_error)?;
+
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).map_err(ipc_error)?;
+
+        Ok(reply)
+    }
+
+    fn clients(&self) -> Result<Vec<Client>, FrameFitError> {
+        let reply = self.request("j/clients")?;
+        let mut clients: Vec<Client> = serde_json::from_str(&reply)
+            .map_err(|e| FrameFitError::backend(format!("Failed to parse Hyprland clients: {}", e)))?;
+
+        // Most recently focused first, like the macOS window list
+        clients.sort_by_key(|client| client.focus_history_id);
+        Ok(clients)
+    }
+
+    fn dispatch(&self, dispatcher: &str) -> Result<(), FrameFitError> {
+        let reply = self.request(&format!("dispatch {}", dispatcher))?;
+
+        if reply.trim() == "ok" {
+            Ok(())
+        } else {
+            Err(FrameFitError::backend(format!("Hyprland dispatch failed: {}", reply.trim())))
+        }
+    }
+
+    fn address_of(&self, window: &WindowInfo) -> Result<String, FrameFitError> {
+        self.clients()?
+            .into_iter()
+            .find(|client| window_id(&client.address) == Some(window.id))
+            .map(|client| client.address)
+            .ok_or(FrameFitError::WindowNotFound { id: window.id })
+    }
+}
+
+impl WindowBackend for HyprlandBackend {
+    fn name(&self) -> &'static str {
+        "hyprland"
+    }
+
+    fn check_permissions(&self) -> bool {
+        UnixStream::connect(&self.socket_path).is_ok()
+    }
+
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, FrameFitError> {
+        Ok(self
+            .clients()?
+            .iter()
+            .filter(|client| client.mapped && !client.hidden)
+            .filter_map(Client::to_window_info)
+            .filter(|window| window.width > 50 && window.height > 50 && !is_framefit(&window.app_name))
+            .collect())
+    }
+
+    fn get_frontmost_window(&self) -> Result<WindowInfo, FrameFitError> {
+        let reply = self.request("j/activewindow")?;
+
+        // Without a focused window Hyprland replies with an empty object
+        let active = serde_json::from_str::<Client>(&reply)
+            .ok()
+            .and_then(|client| client.to_window_info())
+            .ok_or(FrameFitError::NoWindows)?;
+        if !is_framefit(&active.app_name) {
+            return Ok(active);
+        }
+
+        // FrameFit itself is focused, e.g. after clicking its resize button,
+        // so take the window that had the focus before it
+        self.list_windows()?.into_iter().next().ok_or(FrameFitError::NoWindows)
+    }
+
+    fn focus_window(&self, window: &WindowInfo) -> Result<(), FrameFitError> {
+        let address = self.address_of(window)?;
+        self.dispatch(&format!("focuswindow address:{}", address))
+    }
+
+    fn move_window(&self, window: &WindowInfo, x: i32, y: i32) -> Result<(), FrameFitError> {
+        let address = self.address_of(window)?;
+        self.dispatch(&format!("setfloating address:{}", address))?;
+        self.dispatch(&format!("movewindowpixel exact {} {},address:{}", x, y, address))
+    }
+
+    fn resize_window(&self, window: &WindowInfo, width: i32, height: i32) -> Result<(), FrameFitError> {
+        // Tiled windows get their size from the layout, so float them first
+        let address = self.address_of(window)?;
+        self.dispatch(&format!("setfloating address:{}", address))?;
+        self.dispatch(&format!("resizewindowpixel exact {} {},address:{}", width, height, address))
+    }
+
+    fn list_screens(&self) -> Result<Vec<ScreenInfo>, FrameFitError> {
+        let reply = self.request("j/monitors")?;
+        let monitors: Vec<Monitor> = serde_json::from_str(&reply)
+            .map_err(|e| FrameFitError::backend(format!("Failed to parse Hyprland monitors: {}", e)))?;
+
+        Ok(monitors
+            .into_iter()
+            .enumerate()
+            .map(|(index, monitor)| {
+                // Monitor sizes are in physical pixels, window geometry is logical
+                let mut width = (f64::from(monitor.width) / monitor.scale).round() as i32;
+                let mut height = (f64::from(monitor.height) / monitor.scale).round() as i32;
+                if monitor.transform % 2 == 1 {
+                    std::mem::swap(&mut width, &mut height);
+                }
+
+                let [left, top, right, bottom] = monitor.reserved;
+                ScreenInfo {
+                    id: index as u32,
+                    name: monitor.name,
+                    frame: Rect::new(monitor.x, monitor.y, width, height),
+                    work_area: Rect::new(monitor.x + left, monitor.y + top, width - left - right, height - top - bottom),
+                    scale_factor: monitor.scale,
+                    is_main: index == 0,
+                }
+            })
+            .collect())
+    }
+
+    fn content_area(&self, _window: &WindowInfo) -> Result<Option<ContentArea>, FrameFitError> {
+        // Hyprland draws borders outside the window size and has no title bars
+        Ok(Some(ContentArea::Client(Insets::default())))
+    }
+}
+
+fn ipc_error(error: std::io::Error) -> FrameFitError {
+    FrameFitError::backend(format!("Hyprland IPC error: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::fake_ipc::FakeIpcServer;
+
+    const CLIENTS_REPLY: &str = include_str!("../../tests/fixtures/hyprland/clients.json");
+    const ACTIVE_WINDOW_REPLY: &str = include_str!("../../tests/fixtures/hyprland/activewindow.json");
+    const MONITORS_REPLY: &str = include_str!("../../tests/fixtures/hyprland/monitors.json");
+
+    fn fake_hyprland(active_window: &'static str) -> FakeIpcServer {
+        fake_hyprland_with_clients(CLIENTS_REPLY.to_string(), active_window.to_string())
+    }
+
+    fn fake_hyprland_with_clients(clients: String, active_window: String) -> FakeIpcServer {
+        FakeIpcServer::spawn("hyprland", move |stream| {
+            let mut buffer = [0u8; 1024];
+            let length = stream.read(&mut buffer).ok()?;
+            let request = String::from_utf8_lossy(&buffer[..length]).into_owned();
+
+            let reply = match request.as_str() {
+                "j/clients" => clients.as_str(),
+                "j/activewindow" => active_window.as_str(),
+                "j/monitors" => MONITORS_REPLY,
+                _ => "ok",
+            };
+
+            Some((request, reply.as_bytes().to_vec()))
+        })
+    }
+
+    #[test]
+    fn lists_mapped_windows_in_focus_order() {
+        let server = fake_hyprland(ACTIVE_WINDOW_REPLY);
+        let backend = HyprlandBackend::new(server.path.clone());
+
+        let windows = backend.list_windows().unwrap();
+        let apps: Vec<_> = windows.iter().map(|w| w.app_name.as_str()).collect();
+
+        // FrameFit itself and the hidden special-workspace window are skipped
+        assert_eq!(apps, ["firefox", "kitty"]);
+        assert_eq!(windows[0].id, 0xa1b2c3d0);
+        assert_eq!((windows[0].x, windows[0].y, windows[0].width, windows[0].height), (966, 48, 940, 1020));
+    }
+
+    #[test]
+    fn frontmost_window_is_the_active_window() {
+        let server = fake_hyprland(ACTIVE_WINDOW_REPLY);
+        let backend = HyprlandBackend::new(server.path.clone());
+
+        assert_eq!(backend.get_frontmost_window().unwrap().title, "Mozilla Firefox");
+    }
+
+    #[test]
+    fn frontmost_window_fails_without_focus() {
+        let server = fake_hyprland("{}");
+        let backend = HyprlandBackend::new(server.path.clone());
+
+        assert!(backend.get_frontmost_window().is_err());
+    }
+
+    #[test]
+    fn frontmost_window_skips_an_active_framefit() {
+        // FrameFit focused last, kitty before it and Firefox before that
+        let mut clients: Vec<serde_json::Value> = serde_json::from_str(CLIENTS_REPLY).unwrap();
+        for client in &mut clients {
+            let focus_history_id = match client["class"].as_str().unwrap() {
+                "FrameFit" => 0,
+                "kitty" => 1,
+                "firefox" => 2,
+                _ => 3,
+            };
+            client["focusHistoryID"] = focus_history_id.into();
+        }
+        let framefit = clients.iter().find(|client| client["class"] == "FrameFit").unwrap().to_string();
+        let server = fake_hyprland_with_clients(serde_json::to_string(&clients).unwrap(), framefit);
+        let backend = HyprlandBackend::new(server.path.clone());
+
+        assert_eq!(backend.get_frontmost_window().unwrap().app_name, "kitty");
+    }
+
+    #[test]
+    fn resize_floats_the_window_and_uses_its_full_address() {
+        let server = fake_hyprland(ACTIVE_WINDOW_REPLY);
+        let backend = HyprlandBackend::new(server.path.clone());
+        let window = backend.get_frontmost_window().unwrap();
+
+        backend.resize_window(&window, 390, 844).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[requests.len() - 2..],
+            [
+                "dispatch setfloating address:0x55e4a1b2c3d0".to_string(),
+                "dispatch resizewindowpixel exact 390 844,address:0x55e4a1b2c3d0".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn monitor_sizes_are_logical() {
+        let server = fake_hyprland(ACTIVE_WINDOW_REPLY);
+        let backend = HyprlandBackend::new(server.path.clone());
+
+        let screens = backend.list_screens().unwrap();
+        assert_eq!(screens[0].name, "eDP-1");
+        assert_eq!(screens[0].frame, Rect::new(0, 0, 1920, 1200));
+        assert_eq!(screens[0].work_area, Rect::new(0, 40, 1920, 1160));
+        assert_eq!(screens[0].scale_factor, 1.5);
+        assert_eq!(screens[1].frame, Rect::new(1920, 0, 1440, 2560));
+    }
+}
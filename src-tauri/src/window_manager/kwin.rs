@@ -0,0 +1,692 @@
+use super::{is_framefit, ContentArea, Insets, Rect, ScreenInfo, WindowBackend, WindowInfo};
+use crate::control;
+use crate::error::FrameFitError;
+
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zbus::blocking::connection::Builder;
+use zbus::blocking::Connection;
+
+const REPLY_PATH: &str = "/FrameFit/KWin";
+const REPLY_INTERFACE: &str = "com.eliostruyf.FrameFit.KWin";
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Shared helpers for every script; works with the Plasma 5 and 6 APIs
+const SCRIPT_PRELUDE: &str = r#"
+const windows = workspace.windowList ? workspace.windowList() : workspace.clientList();
+const activeWindow = workspace.activeWindow !== undefined ? workspace.activeWindow : workspace.activeClient;
+const stackingOrder = workspace.stackingOrder || [];
+
+function describe(w) {
+    const g = w.frameGeometry;
+    return {
+        id: w.internalId.toString(),
+        title: String(w.caption),
+        app: String(w.resourceClass),
+        x: g.x,
+        y: g.y,
+        width: g.width,
+        height: g.height,
+        active: w === activeWindow,
+        normal: w.normalWindow,
+        stacking: stackingOrder.indexOf(w),
+    };
+}
+
+function findWindow(id) {
+    for (const w of windows) {
+        if (w.internalId.toString() === id) {
+            return w;
+        }
+    }
+    return null;
+}
+
+function reply(result) {
+    callDBus(framefit.service, framefit.path, framefit.iface, "reply", framefit.token, JSON.stringify(result));
+}
+"#;
+
+const LIST_WINDOWS_SCRIPT: &str = "reply(windows.map(describe));";
+
+const LIST_SCREENS_SCRIPT: &str = r#"
+function rect(g) {
+    return { x: g.x, y: g.y, width: g.width, height: g.height };
+}
+
+if (workspace.screens !== undefined) {
+    reply(workspace.screens.map((output, index) => ({
+        name: output.name,
+        frame: rect(output.geometry),
+        workArea: rect(workspace.clientArea(KWin.MaximizeArea, output, workspace.currentDesktop)),
+        scale: output.devicePixelRatio,
+        primary: index === 0,
+    })));
+} else {
+    const screens = [];
+    for (let i = 0; i < workspace.numScreens; i++) {
+        screens.push({
+            name: "Screen " + i,
+            frame: rect(workspace.clientArea(KWin.ScreenArea, i, 0)),
+            workArea: rect(workspace.clientArea(KWin.MaximizeArea, i, workspace.currentDesktop)),
+            primary: i === 0,
+        });
+    }
+    reply(screens);
+}
+"#;
+
+// Plasma 6 has clientGeometry, Plasma 5 the client's offset and size
+const CONTENT_AREA_SCRIPT: &str = r#"
+const w = findWindow(framefit.window);
+if (w) {
+    const f = w.frameGeometry;
+    const c = w.clientGeometry !== undefined
+        ? w.clientGeometry
+        : { x: f.x + w.clientPos.x, y: f.y + w.clientPos.y, width: w.clientSize.width, height: w.clientSize.height };
+    reply({
+        top: Math.round(c.y - f.y),
+        left: Math.round(c.x - f.x),
+        bottom: Math.round(f.y + f.height - c.y - c.height),
+        right: Math.round(f.x + f.width - c.x - c.width),
+    });
+} else {
+    reply(null);
+}
+"#;
+
+#[derive(Debug, Deserialize)]
+struct KWinWindow {
+    id: String,
+    title: String,
+    app: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    active: bool,
+    normal: bool,
+    /// Position in the stacking order, bottom to top; -1 when unknown
+    #[serde(default = "unknown_stacking")]
+    stacking: i32,
+}
+
+fn unknown_stacking() -> i32 {
+    -1
+}
+
+/// The active window, or the topmost other one when FrameFit itself is
+/// active, e.g. after clicking its resize button.
+fn frontmost(windows: &[KWinWindow]) -> Option<&KWinWindow> {
+    let mut candidates: Vec<&KWinWindow> = windows.iter().filter(|window| window.normal).collect();
+    let active = candidates.iter().position(|window| window.active)?;
+    if !is_framefit(&candidates[active].app) {
+        return Some(candidates[active]);
+    }
+
+    candidates.sort_by_key(|window| std::cmp::Reverse(window.stacking));
+    candidates.into_iter().find(|window| !is_framefit(&window.app))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KWinScreen {
+    name: String,
+    frame: KWinRect,
+    work_area: KWinRect,
+    #[serde(default = "default_scale")]
+    scale: f64,
+    primary: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct KWinRect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl KWinRect {
+    fn round(&self) -> Rect {
+        Rect::new(
+            self.x.round() as i32,
+            self.y.round() as i32,
+            self.width.round() as i32,
+            self.height.round() as i32,
+        )
+    }
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// KWin identifies windows by UUID, `WindowInfo` by number. Numbers aren't
+/// reused, so a closed window's number never refers to another window.
+#[derive(Debug, Default)]
+struct WindowIds {
+    numbers: HashMap<String, u32>,
+    internal_ids: HashMap<u32, String>,
+    last: u32,
+}
+
+impl WindowIds {
+    fn id(&mut self, internal_id: &str) -> u32 {
+        if let Some(&id) = self.numbers.get(internal_id) {
+            return id;
+        }
+
+        self.last += 1;
+        self.numbers.insert(internal_id.to_string(), self.last);
+        self.internal_ids.insert(self.last, internal_id.to_string());
+        self.last
+    }
+
+    fn internal_id(&self, id: u32) -> Option<&str> {
+        self.internal_ids.get(&id).map(String::as_str)
+    }
+
+    /// Forgets the windows that are no longer in `windows`, all of KWin's.
+    fn retain(&mut self, windows: &[KWinWindow]) {
+        let open: HashSet<&str> = windows.iter().map(|window| window.id.as_str()).collect();
+        self.numbers.retain(|internal_id, _| open.contains(internal_id.as_str()));
+        self.internal_ids.retain(|_, internal_id| open.contains(internal_id.as_str()));
+    }
+}
+
+/// Receives the results that FrameFit's KWin scripts send back with `callDBus`.
+struct ScriptReplies {
+    sender: Mutex<Sender<(String, String)>>,
+}
+
+#[zbus::interface(name = "com.eliostruyf.FrameFit.KWin")]
+impl ScriptReplies {
+    #[zbus(name = "reply")]
+    fn reply(&self, token: String, payload: String) {
+        let _ = self.sender.lock().unwrap().send((token, payload));
+    }
+}
+
+/// Window backend for KDE Plasma. KWin has no direct window API on D-Bus,
+/// so every operation loads a short script through `org.kde.kwin.Scripting`
+/// which reports back to an object FrameFit serves on the session bus.
+pub struct KWinBackend {
+    connection: Connection,
+    replies: Mutex<Receiver<(String, String)>>,
+    next_token: AtomicU32,
+    window_ids: Mutex<WindowIds>,
+}
+
+impl KWinBackend {
+    pub fn from_env() -> Option<Self> {
+        let desktop = std::env::var("XDG_CURRENT_DESKTOP").ok()?;
+        if !desktop.split(':').any(|name| name.eq_ignore_ascii_case("KDE")) {
+            return None;
+        }
+
+        let builder = match Builder::session() {
+            Ok(builder) => builder,
+            Err(e) => {
+                eprintln!("KWin backend unavailable: {}", e);
+                return None;
+            }
+        };
+
+        match Self::connect(builder) {
+            Ok(backend) => Some(backend),
+            Err(e) => {
+                eprintln!("Skipping KWin backend: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn connect(builder: Builder<'_>) -> Result<Self, FrameFitError> {
+        let (sender, receiver) = channel();
+        let connection = builder
+            .serve_at(REPLY_PATH, ScriptReplies { sender: Mutex::new(sender) })
+            .and_then(|builder| builder.build())
+            .map_err(|e| FrameFitError::unavailable(format!("Failed to connect to the session bus: {}", e)))?;
+
+        Ok(Self {
+            connection,
+            replies: Mutex::new(receiver),
+            next_token: AtomicU32::new(1),
+            window_ids: Mutex::new(WindowIds::default()),
+        })
+    }
+
+    fn run_script(&self, body: &str) -> Result<String, FrameFitError> {
+        let token = self.next_token.fetch_add(1, Ordering::SeqCst).to_string();
+        let service = self
+            .connection
+            .unique_name()
+            .map(|name| name.to_string())
+            .ok_or_else(|| FrameFitError::unavailable("Not connected to the session bus"))?;
+        let header = serde_json::json!({
+            "service": service,
+            "path": REPLY_PATH,
+            "iface": REPLY_INTERFACE,
+            "token": token,
+        });
+
+        let script = format!("const framefit = {};\n{}\n{}\n", header, SCRIPT_PRELUDE, body);
+        let path = write_script(&script, &token)
+            .map_err(|e| FrameFitError::backend(format!("Failed to write KWin script: {}", e)))?;
+
+        let plugin_name = format!("framefit_{}", token);
+        let result = self
+            .load_and_run(&path.to_string_lossy(), &plugin_name)
+            .and_then(|_| self.wait_for_reply(&token));
+
+        let _ = self.connection.call_method(
+            Some("org.kde.KWin"),
+            "/Scripting",
+            Some("org.kde.kwin.Scripting"),
+            "unloadScript",
+            &(plugin_name.as_str(),),
+        );
+        let _ = std::fs::remove_file(&path);
+
+        result
+    }
+
+    fn load_and_run(&self, path: &str, plugin_name: &str) -> Result<(), FrameFitError> {
+        let reply = self
+            .connection
+            .call_method(
+                Some("org.kde.KWin"),
+                "/Scripting",
+                Some("org.kde.kwin.Scripting"),
+                "loadScript",
+                &(path, plugin_name),
+            )
+            .map_err(dbus_error)?;
+        let script_id: i32 = reply.body().deserialize().map_err(dbus_error)?;
+        if script_id < 0 {
+            return Err(FrameFitError::ScriptFailed {
+                stderr: "KWin refused to load the FrameFit script".to_string(),
+                exit_code: None,
+            });
+        }
+
+        // Plasma 6 exports scripts below /Scripting, Plasma 5 at the root
+        let run = |path: String| {
+            self.connection.call_method(
+                Some("org.kde.KWin"),
+                path.as_str(),
+                Some("org.kde.kwin.Script"),
+                "run",
+                &(),
+            )
+        };
+        run(format!("/Scripting/Script{}", script_id))
+            .or_else(|_| run(format!("/{}", script_id)))
+            .map_err(dbus_error)?;
+
+        Ok(())
+    }
+
+    fn wait_for_reply(&self, token: &str) -> Result<String, FrameFitError> {
+        let replies = self.replies.lock().unwrap();
+        let deadline = Instant::now() + SCRIPT_TIMEOUT;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match replies.recv_timeout(remaining) {
+                Ok((reply_token, payload)) if reply_token == token => return Ok(payload),
+                // A late reply from an earlier script that timed out
+                Ok(_) => continue,
+                Err(_) => return Err(FrameFitError::backend("Timed out waiting for KWin")),
+            }
+        }
+    }
+
+    fn kwin_windows(&self) -> Result<Vec<KWinWindow>, FrameFitError> {
+        let payload = self.run_script(LIST_WINDOWS_SCRIPT)?;
+        let windows: Vec<KWinWindow> = serde_json::from_str(&payload)
+            .map_err(|e| FrameFitError::backend(format!("Failed to parse KWin windows: {}", e)))?;
+        self.window_ids.lock().unwrap().retain(&windows);
+        Ok(windows)
+    }
+
+    fn to_window_info(&self, window: &KWinWindow) -> WindowInfo {
+        WindowInfo {
+            id: self.window_ids.lock().unwrap().id(&window.id),
+            title: window.title.clone(),
+            app_name: window.app.clone(),
+            x: window.x.round() as i32,
+            y: window.y.round() as i32,
+            width: window.width.round() as i32,
+            height: window.height.round() as i32,
+        }
+    }
+
+    /// The windows `list_windows` reports: normal ones, not too small to
+    /// resize, without FrameFit.
+    fn listed(&self, windows: &[KWinWindow]) -> Vec<WindowInfo> {
+        windows
+            .iter()
+            .filter(|window| window.normal)
+            .map(|window| self.to_window_info(window))
+            .filter(|window| window.width > 50 && window.height > 50 && !is_framefit(&window.app_name))
+            .collect()
+    }
+
+    fn internal_id(&self, window: &WindowInfo) -> Result<String, FrameFitError> {
+        self.window_ids
+            .lock()
+            .unwrap()
+            .internal_id(window.id)
+            .map(str::to_string)
+            .ok_or(FrameFitError::WindowNotFound { id: window.id })
+    }
+
+    fn with_window(&self, window: &WindowInfo, action: &str) -> Result<(), FrameFitError> {
+        let internal_id = serde_json::to_string(&self.internal_id(window)?).unwrap();
+        let body = format!(
+            "const w = findWindow({});\nif (w) {{\n{}\n}}\nreply(w !== null);",
+            internal_id, action
+        );
+
+        match self.run_script(&body)?.as_str() {
+            "true" => Ok(()),
+            _ => Err(FrameFitError::WindowNotFound { id: window.id }),
+        }
+    }
+}
+
+/// Writes a script where only this user can put one, as KWin runs whatever
+/// the file holds when it's loaded.
+fn write_script(script: &str, token: &str) -> std::io::Result<PathBuf> {
+    let path = control::user_dir()?.join(format!("framefit-kwin-{}-{}.js", std::process::id(), token));
+    let mut file = OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path)?;
+    file.write_all(script.as_bytes())?;
+    Ok(path)
+}
+
+impl WindowBackend for KWinBackend {
+    fn name(&self) -> &'static str {
+        "kwin"
+    }
+
+    fn check_permissions(&self) -> bool {
+        true
+    }
+
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, FrameFitError> {
+        let windows = self.kwin_windows()?;
+        Ok(self.listed(&windows))
+    }
+
+    fn get_frontmost_window(&self) -> Result<WindowInfo, FrameFitError> {
+        let windows = self.kwin_windows()?;
+        frontmost(&windows)
+            .map(|window| self.to_window_info(window))
+            .ok_or(FrameFitError::NoWindows)
+    }
+
+    // Every script is a round trip through KWin, so one for both
+    fn list_windows_with_frontmost(&self) -> Result<(Vec<WindowInfo>, Option<WindowInfo>), FrameFitError> {
+        let windows = self.kwin_windows()?;
+        let front = frontmost(&windows).map(|window| self.to_window_info(window));
+        Ok((self.listed(&windows), front))
+    }
+
+    fn focus_window(&self, window: &WindowInfo) -> Result<(), FrameFitError> {
+        self.with_window(
+            window,
+            "if (workspace.activeWindow !== undefined) { workspace.activeWindow = w; } else { workspace.activeClient = w; }",
+        )
+    }
+
+    fn move_window(&self, window: &WindowInfo, x: i32, y: i32) -> Result<(), FrameFitError> {
+        self.with_window(
+            window,
+            &format!(
+                "const g = w.frameGeometry;\nw.frameGeometry = {{ x: {}, y: {}, width: g.width, height: g.height }};",
+                x, y
+            ),
+        )
+    }
+
+    fn resize_window(&self, window: &WindowInfo, width: i32, height: i32) -> Result<(), FrameFitError> {
+        self.with_window(
+            window,
+            &format!(
+                "const g = w.frameGeometry;\nw.frameGeometry = {{ x: g.x, y: g.y, width: {}, height: {} }};",
+                width, height
+            ),
+        )
+    }
+
+    fn list_screens(&self) -> Result<Vec<ScreenInfo>, FrameFitError> {
+        let payload = self.run_script(LIST_SCREENS_SCRIPT)?;
+        let screens: Vec<KWinScreen> =
+            serde_json::from_str(&payload).map_err(|e| FrameFitError::backend(format!("Failed to parse KWin screens: {}", e)))?;
+
+        Ok(screens
+            .into_iter()
+            .enumerate()
+            .map(|(index, screen)| ScreenInfo {
+                id: index as u32,
+                frame: screen.frame.round(),
+                work_area: screen.work_area.round(),
+                scale_factor: screen.scale,
+                name: screen.name,
+                is_main: screen.primary,
+            })
+            .collect())
+    }
+
+    fn content_area(&self, window: &WindowInfo) -> Result<Option<ContentArea>, FrameFitError> {
+        let internal_id = serde_json::to_string(&self.internal_id(window)?).unwrap();
+        let body = CONTENT_AREA_SCRIPT.replace("framefit.window", &internal_id);
+        let insets: Option<Insets> = serde_json::from_str(&self.run_script(&body)?)
+            .map_err(|e| FrameFitError::backend(format!("Failed to parse KWin content area: {}", e)))?;
+
+        insets
+            .map(|insets| Some(ContentArea::Client(insets)))
+            .ok_or(FrameFitError::WindowNotFound { id: window.id })
+    }
+}
+
+fn dbus_error(error: zbus::Error) -> FrameFitError {
+    FrameFitError::backend(format!("D-Bus error: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::Arc;
+
+    const WINDOWS_REPLY: &str = include_str!("../../tests/fixtures/kwin/windows.json");
+    const SCREENS_REPLY: &str = include_str!("../../tests/fixtures/kwin/screens.json");
+
+    /// A private bus, so the tests never touch the user's real session.
+    struct PrivateBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl PrivateBus {
+        fn start() -> Self {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--nopidfile", "--print-address=1"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .expect("dbus-daemon not found");
+
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.as_mut().unwrap())
+                .read_line(&mut address)
+                .unwrap();
+
+            Self { daemon, address: address.trim().to_string() }
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    /// Plays KWin: remembers loaded scripts and answers them with recorded
+    /// results when they are run.
+    #[derive(Clone, Default)]
+    struct MockKWin {
+        scripts: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[zbus::interface(name = "org.kde.kwin.Scripting")]
+    impl MockKWin {
+        #[zbus(name = "loadScript")]
+        fn load_script(&self, path: String, _plugin_name: String) -> i32 {
+            let script = std::fs::read_to_string(path).unwrap();
+            let mut scripts = self.scripts.lock().unwrap();
+            scripts.push(script);
+            scripts.len() as i32 - 1
+        }
+
+        #[zbus(name = "unloadScript")]
+        fn unload_script(&self, _plugin_name: String) -> bool {
+            true
+        }
+    }
+
+    struct MockScript {
+        kwin: MockKWin,
+    }
+
+    #[zbus::interface(name = "org.kde.kwin.Script")]
+    impl MockScript {
+        #[zbus(name = "run")]
+        async fn run(&self, #[zbus(connection)] connection: &zbus::Connection) {
+            let script = self.kwin.scripts.lock().unwrap().last().cloned().unwrap();
+            let header = script
+                .lines()
+                .next()
+                .and_then(|line| line.strip_prefix("const framefit = "))
+                .and_then(|line| line.strip_suffix(';'))
+                .unwrap();
+            let header: serde_json::Value = serde_json::from_str(header).unwrap();
+
+            let payload = if script.contains("workspace.screens") {
+                SCREENS_REPLY
+            } else if script.contains("findWindow(\"") {
+                "true"
+            } else {
+                WINDOWS_REPLY
+            };
+
+            connection
+                .call_method(
+                    Some(header["service"].as_str().unwrap()),
+                    header["path"].as_str().unwrap(),
+                    Some(header["iface"].as_str().unwrap()),
+                    "reply",
+                    &(header["token"].as_str().unwrap(), payload),
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    fn start_mock_kwin(bus: &PrivateBus) -> (Connection, MockKWin) {
+        let kwin = MockKWin::default();
+        let mut builder = Builder::address(bus.address.as_str())
+            .unwrap()
+            .name("org.kde.KWin")
+            .unwrap()
+            .serve_at("/Scripting", kwin.clone())
+            .unwrap();
+
+        // The backend runs each script once, so a handful of objects is plenty
+        for id in 0..8 {
+            builder = builder
+                .serve_at(format!("/Scripting/Script{}", id), MockScript { kwin: kwin.clone() })
+                .unwrap();
+        }
+
+        (builder.build().unwrap(), kwin)
+    }
+
+    #[test]
+    fn frontmost_window_skips_an_active_framefit() {
+        let mut windows: Vec<KWinWindow> = serde_json::from_str(WINDOWS_REPLY).unwrap();
+        assert_eq!(frontmost(&windows).unwrap().app, "firefox");
+
+        // Konsole is above Firefox in the fixture's stacking order
+        for window in &mut windows {
+            window.active = window.app == "framefit";
+        }
+        assert_eq!(frontmost(&windows).unwrap().app, "konsole");
+
+        for window in &mut windows {
+            window.active = false;
+        }
+        assert!(frontmost(&windows).is_none());
+    }
+
+    #[test]
+    fn numbers_windows_without_reusing_closed_ones() {
+        let mut windows: Vec<KWinWindow> = serde_json::from_str(WINDOWS_REPLY).unwrap();
+        let mut ids = WindowIds::default();
+        let numbers: Vec<u32> = windows.iter().map(|window| ids.id(&window.id)).collect();
+        assert_eq!(ids.id(&windows[1].id), numbers[1]);
+        assert_eq!(ids.internal_id(numbers[1]), Some(windows[1].id.as_str()));
+
+        let closed = windows.remove(1);
+        ids.retain(&windows);
+        assert_eq!(ids.internal_id(numbers[1]), None);
+        assert_eq!(ids.numbers.len(), windows.len());
+        assert!(!numbers.contains(&ids.id(&closed.id)));
+    }
+
+    #[test]
+    #[ignore = "requires dbus-daemon"]
+    fn talks_to_kwin_through_scripts() {
+        let bus = PrivateBus::start();
+        let (_kwin_connection, kwin) = start_mock_kwin(&bus);
+        let backend = KWinBackend::connect(Builder::address(bus.address.as_str()).unwrap()).unwrap();
+
+        let windows = backend.list_windows().unwrap();
+        let apps: Vec<_> = windows.iter().map(|w| w.app_name.as_str()).collect();
+        assert_eq!(apps, ["konsole", "firefox"]);
+        assert_eq!((windows[1].x, windows[1].y, windows[1].width, windows[1].height), (960, 0, 960, 1048));
+
+        let frontmost = backend.get_frontmost_window().unwrap();
+        assert_eq!(frontmost.app_name, "firefox");
+        assert_eq!(frontmost.id, windows[1].id);
+
+        let loaded = kwin.scripts.lock().unwrap().len();
+        let (polled, polled_frontmost) = backend.list_windows_with_frontmost().unwrap();
+        assert_eq!((polled, polled_frontmost), (windows.clone(), Some(frontmost.clone())));
+        assert_eq!(kwin.scripts.lock().unwrap().len(), loaded + 1);
+
+        backend.resize_window(&frontmost, 390, 844).unwrap();
+        let script = kwin.scripts.lock().unwrap().last().cloned().unwrap();
+        assert!(script.contains("findWindow(\"{3c1c5b7e-3f44-4a35-a6b5-5d5fc8f1b2a4}\")"));
+        assert!(script.contains("width: 390, height: 844"));
+
+        let screens = backend.list_screens().unwrap();
+        assert_eq!(screens.len(), 2);
+        assert!(screens[0].is_main);
+        assert_eq!(screens[0].work_area, Rect::new(0, 0, 1920, 1036));
+        assert_eq!(screens[0].scale_factor, 1.25);
+        assert_eq!(screens[1].name, "DP-2");
+    }
+}
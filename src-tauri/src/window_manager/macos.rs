@@ -0,0 +1,426 @@
+use super::applescript::{self, WindowAction, WindowCommand, WindowSelector};
+use super::{ContentArea, Insets, Rect, ScreenInfo, WindowBackend, WindowInfo};
+use crate::error::FrameFitError;
+
+#[allow(deprecated)]
+use cocoa::base::id;
+
+use core_foundation::{
+    array::{CFArrayGetCount, CFArrayGetValueAtIndex},
+    base::TCFType,
+    dictionary::{CFDictionaryGetValueIfPresent, CFDictionaryRef},
+    number::{CFNumberGetValue, CFNumberRef},
+    string::{CFString, CFStringGetCString, CFStringRef},
+};
+
+use core_graphics::window::{kCGWindowListOptionOnScreenOnly, CGWindowListCopyWindowInfo};
+
+use objc::{msg_send, sel, sel_impl};
+
+use std::collections::HashMap;
+
+/// Window backend built on Core Graphics for enumeration and AppleScript
+/// (System Events) for moving and resizing.
+pub struct MacOsBackend;
+
+impl WindowBackend for MacOsBackend {
+    fn name(&self) -> &'static str {
+        "macos"
+    }
+
+    fn check_permissions(&self) -> bool {
+        use core_graphics::event::CGEvent;
+
+        let event = CGEvent::new(core_graphics::event_source::CGEventSource::new(
+            core_graphics::event_source::CGEventSourceStateID::CombinedSessionState,
+        ).unwrap());
+
+        event.is_ok()
+    }
+
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, FrameFitError> {
+        unsafe {
+            let window_list = CGWindowListCopyWindowInfo(kCGWindowListOptionOnScreenOnly, 0);
+
+            if window_list.is_null() {
+                return Err(FrameFitError::backend("Failed to get window list"));
+            }
+
+            let count = CFArrayGetCount(window_list);
+            let mut windows = Vec::new();
+            let excluded_apps = ["Dock", "Window Server", "FrameFit", "framefit"];
+
+            for i in 0..count {
+                let window_info = CFArrayGetValueAtIndex(window_list, i) as CFDictionaryRef;
+
+                if let Some(window) = parse_window_info(window_info) {
+                    if window.width > 50
+                        && window.height > 50
+                        && !excluded_apps.iter().any(|app| app.eq_ignore_ascii_case(&window.app_name))
+                    {
+                        windows.push(window);
+                    }
+                }
+            }
+
+            Ok(windows)
+        }
+    }
+
+    fn get_frontmost_window(&self) -> Result<WindowInfo, FrameFitError> {
+        frontmost(&self.list_windows()?).ok_or(FrameFitError::NoWindows)
+    }
+
+    fn list_windows_with_frontmost(&self) -> Result<(Vec<WindowInfo>, Option<WindowInfo>), FrameFitError> {
+        let windows = self.list_windows()?;
+        let front = frontmost(&windows);
+        Ok((windows, front))
+    }
+
+    fn focus_window(&self, window: &WindowInfo) -> Result<(), FrameFitError> {
+        self.run_command(window, WindowAction::Focus).map(|_| ())
+    }
+
+    fn move_window(&self, window: &WindowInfo, x: i32, y: i32) -> Result<(), FrameFitError> {
+        self.run_command(window, WindowAction::Move { x, y }).map(|_| ())
+    }
+
+    fn resize_window(&self, window: &WindowInfo, width: i32, height: i32) -> Result<(), FrameFitError> {
+        self.run_command(window, WindowAction::Resize { width, height }).map(|_| ())
+    }
+
+    fn list_screens(&self) -> Result<Vec<ScreenInfo>, FrameFitError> {
+        use core_graphics::display::{CGDisplay, CGMainDisplayID};
+
+        let display_ids = CGDisplay::active_displays()
+            .map_err(|e| FrameFitError::backend(format!("Failed to get display list: {}", e)))?;
+        let main_display = unsafe { CGMainDisplayID() };
+        let details = unsafe { screen_details() };
+
+        Ok(display_ids
+            .into_iter()
+            .map(|display_id| {
+                let display = CGDisplay::new(display_id);
+                let bounds = display.bounds();
+                let frame = Rect::new(
+                    bounds.origin.x as i32,
+                    bounds.origin.y as i32,
+                    bounds.size.width as i32,
+                    bounds.size.height as i32,
+                );
+                let details = details.get(&display_id);
+
+                ScreenInfo {
+                    id: display_id,
+                    name: details.map_or_else(|| format!("Display {}", display_id), |d| d.name.clone()),
+                    frame,
+                    work_area: details.map_or(frame, |d| d.work_area),
+                    scale_factor: details.map_or_else(
+                        || display.pixels_wide() as f64 / bounds.size.width,
+                        |d| d.scale_factor,
+                    ),
+                    is_main: display_id == main_display,
+                }
+            })
+            .collect())
+    }
+
+    fn content_area(&self, window: &WindowInfo) -> Result<Option<ContentArea>, FrameFitError> {
+        // Apps other than browsers report no web area and are sized by their frame
+        let output = self.run_command(window, WindowAction::ContentInsets)?;
+        let edges: Vec<i32> = output.trim().split(',').filter_map(|edge| edge.trim().parse().ok()).collect();
+
+        Ok(match edges[..] {
+            [top, left, bottom, right] => Some(ContentArea::Viewport(Insets { top, left, bottom, right })),
+            _ => None,
+        })
+    }
+}
+
+impl MacOsBackend {
+    /// Runs `action` on the window, picked by title and position among the
+    /// app's windows rather than just its first one.
+    fn run_command(&self, window: &WindowInfo, action: WindowAction) -> Result<String, FrameFitError> {
+        let command = WindowCommand {
+            app_name: window.app_name.clone(),
+            window: WindowSelector::for_window(window, &self.list_windows()?),
+            action,
+        };
+
+        match run_applescript(&command.script()) {
+            Err(FrameFitError::ScriptFailed { stderr, .. }) if stderr.contains(applescript::WINDOW_NOT_FOUND) => {
+                Err(FrameFitError::WindowNotFound { id: window.id })
+            }
+            result => result,
+        }
+    }
+}
+
+struct ScreenDetails {
+    name: String,
+    work_area: Rect,
+    scale_factor: f64,
+}
+
+/// Display details only AppKit knows about, keyed by display id: the visible
+/// frame without the menu bar and Dock, the backing scale and the name.
+#[allow(unexpected_cfgs, deprecated)]
+unsafe fn screen_details() -> HashMap<u32, ScreenDetails> {
+    use cocoa::appkit::NSScreen;
+    use cocoa::base::{nil, BOOL, NO};
+    use cocoa::foundation::{NSArray, NSRect, NSString};
+    use core_graphics::display::CGDisplay;
+
+    let mut details = HashMap::new();
+    let screens = NSScreen::screens(nil);
+    if screens == nil {
+        return details;
+    }
+
+    // AppKit's origin is the bottom-left corner of the main display
+    let main_height = CGDisplay::main().bounds().size.height;
+    let key = NSString::alloc(nil).init_str("NSScreenNumber");
+
+    for index in 0..NSArray::count(screens) {
+        let screen = NSArray::objectAtIndex(screens, index);
+        let description: id = msg_send![screen, deviceDescription];
+        let number: id = msg_send![description, objectForKey: key];
+        if number == nil {
+            continue;
+        }
+        let display_id: u32 = msg_send![number, unsignedIntValue];
+
+        // localizedName is only available from macOS 10.15
+        let has_name: BOOL = msg_send![screen, respondsToSelector: sel!(localizedName)];
+        let name = if has_name != NO {
+            let name: id = msg_send![screen, localizedName];
+            nsstring_to_string(name)
+        } else {
+            format!("Display {}", display_id)
+        };
+
+        let visible: NSRect = NSScreen::visibleFrame(screen);
+        details.insert(
+            display_id,
+            ScreenDetails {
+                name,
+                work_area: Rect::new(
+                    visible.origin.x as i32,
+                    (main_height - visible.origin.y - visible.size.height) as i32,
+                    visible.size.width as i32,
+                    visible.size.height as i32,
+                ),
+                scale_factor: NSScreen::backingScaleFactor(screen),
+            },
+        );
+    }
+
+    let _: () = msg_send![key, release];
+    details
+}
+
+unsafe fn parse_window_info(window_dict: CFDictionaryRef) -> Option<WindowInfo> {
+    let mut window_id: u32 = 0;
+    let mut x: i32 = 0;
+    let mut y: i32 = 0;
+    let mut width: i32 = 0;
+    let mut height: i32 = 0;
+    let mut title = String::new();
+    let mut app_name = String::new();
+
+    let key_window_id = CFString::new("kCGWindowNumber");
+    let key_bounds = CFString::new("kCGWindowBounds");
+    let key_name = CFString::new("kCGWindowName");
+    let key_owner = CFString::new("kCGWindowOwnerName");
+
+    let mut value: *const std::ffi::c_void = std::ptr::null();
+
+    if CFDictionaryGetValueIfPresent(
+        window_dict,
+        key_window_id.as_CFTypeRef() as *const _,
+        &mut value,
+    ) != 0
+    {
+        let num = value as CFNumberRef;
+        CFNumberGetValue(
+            num,
+            core_foundation::number::kCFNumberSInt32Type,
+            &mut window_id as *mut _ as *mut _,
+        );
+    }
+
+    if CFDictionaryGetValueIfPresent(
+        window_dict,
+        key_name.as_CFTypeRef() as *const _,
+        &mut value,
+    ) != 0
+    {
+        let str_ref = value as CFStringRef;
+        let mut buffer = vec![0u8; 256];
+        if CFStringGetCString(
+            str_ref,
+            buffer.as_mut_ptr() as *mut _,
+            256,
+            core_foundation::string::kCFStringEncodingUTF8,
+        ) != 0
+        {
+            if let Ok(s) = std::ffi::CStr::from_ptr(buffer.as_ptr() as *const _).to_str() {
+                title = s.to_string();
+            }
+        }
+    }
+
+    if CFDictionaryGetValueIfPresent(
+        window_dict,
+        key_owner.as_CFTypeRef() as *const _,
+        &mut value,
+    ) != 0
+    {
+        let str_ref = value as CFStringRef;
+        let mut buffer = vec![0u8; 256];
+        if CFStringGetCString(
+            str_ref,
+            buffer.as_mut_ptr() as *mut _,
+            256,
+            core_foundation::string::kCFStringEncodingUTF8,
+        ) != 0
+        {
+            if let Ok(s) = std::ffi::CStr::from_ptr(buffer.as_ptr() as *const _).to_str() {
+                app_name = s.to_string();
+            }
+        }
+    }
+
+    if CFDictionaryGetValueIfPresent(
+        window_dict,
+        key_bounds.as_CFTypeRef() as *const _,
+        &mut value,
+    ) != 0
+    {
+        let bounds_dict = value as CFDictionaryRef;
+
+        let key_x = CFString::new("X");
+        let key_y = CFString::new("Y");
+        let key_width = CFString::new("Width");
+        let key_height = CFString::new("Height");
+
+        let mut bound_value: *const std::ffi::c_void = std::ptr::null();
+
+        if CFDictionaryGetValueIfPresent(
+            bounds_dict,
+            key_x.as_CFTypeRef() as *const _,
+            &mut bound_value,
+        ) != 0
+        {
+            let num = bound_value as CFNumberRef;
+            let mut val: f64 = 0.0;
+            CFNumberGetValue(
+                num,
+                core_foundation::number::kCFNumberFloat64Type,
+                &mut val as *mut _ as *mut _,
+            );
+            x = val as i32;
+        }
+
+        if CFDictionaryGetValueIfPresent(
+            bounds_dict,
+            key_y.as_CFTypeRef() as *const _,
+            &mut bound_value,
+        ) != 0
+        {
+            let num = bound_value as CFNumberRef;
+            let mut val: f64 = 0.0;
+            CFNumberGetValue(
+                num,
+                core_foundation::number::kCFNumberFloat64Type,
+                &mut val as *mut _ as *mut _,
+            );
+            y = val as i32;
+        }
+
+        if CFDictionaryGetValueIfPresent(
+            bounds_dict,
+            key_width.as_CFTypeRef() as *const _,
+            &mut bound_value,
+        ) != 0
+        {
+            let num = bound_value as CFNumberRef;
+            let mut val: f64 = 0.0;
+            CFNumberGetValue(
+                num,
+                core_foundation::number::kCFNumberFloat64Type,
+                &mut val as *mut _ as *mut _,
+            );
+            width = val as i32;
+        }
+
+        if CFDictionaryGetValueIfPresent(
+            bounds_dict,
+            key_height.as_CFTypeRef() as *const _,
+            &mut bound_value,
+        ) != 0
+        {
+            let num = bound_value as CFNumberRef;
+            let mut val: f64 = 0.0;
+            CFNumberGetValue(
+                num,
+                core_foundation::number::kCFNumberFloat64Type,
+                &mut val as *mut _ as *mut _,
+            );
+            height = val as i32;
+        }
+    }
+
+    Some(WindowInfo {
+        id: window_id,
+        title,
+        app_name,
+        x,
+        y,
+        width,
+        height,
+    })
+}
+
+#[allow(unexpected_cfgs, deprecated)]
+unsafe fn nsstring_to_string(ns_string: id) -> String {
+    let utf8: *const u8 = msg_send![ns_string, UTF8String];
+    let len: usize = msg_send![ns_string, lengthOfBytesUsingEncoding: 4];
+    let bytes = std::slice::from_raw_parts(utf8, len);
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// The first window of `windows`, which are listed front to back, that
+/// isn't FrameFit's own or part of the system UI.
+fn frontmost(windows: &[WindowInfo]) -> Option<WindowInfo> {
+    let excluded_apps = ["framefit", "Dock", "Window Server"];
+    windows.iter().find(|window| !excluded_apps.contains(&window.app_name.as_str())).cloned()
+}
+
+// AppleScript error numbers for missing Automation or Accessibility access
+const PERMISSION_ERRORS: [&str; 3] = ["(-1743)", "(-1719)", "(-25211)"];
+
+/// Runs a script and returns what it printed.
+fn run_applescript(script: &str) -> Result<String, FrameFitError> {
+    use std::process::Command;
+
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .output()
+        .map_err(|e| FrameFitError::unavailable(format!("Failed to execute AppleScript: {}", e)))?;
+
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    if PERMISSION_ERRORS.iter().any(|code| stderr.contains(code)) {
+        return Err(FrameFitError::PermissionDenied);
+    }
+
+    Err(FrameFitError::ScriptFailed {
+        stderr,
+        exit_code: output.status.code(),
+    })
+}
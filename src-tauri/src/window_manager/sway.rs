@@ -0,0 +1,445 @@
+use super::{is_framefit, ContentArea, Insets, Rect as ScreenRect, ScreenInfo, WindowBackend, WindowInfo};
+use crate::error::FrameFitError;
+
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+const IPC_MAGIC: &[u8; 6] = b"i3-ipc";
+
+const RUN_COMMAND: u32 = 0;
+const GET_WORKSPACES: u32 = 1;
+const GET_OUTPUTS: u32 = 3;
+const GET_TREE: u32 = 4;
+
+#[derive(Debug, Deserialize)]
+struct Rect {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl Rect {
+    fn to_screen_rect(&self) -> ScreenRect {
+        ScreenRect::new(self.x, self.y, self.width, self.height)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct WindowProperties {
+    class: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Node {
+    id: i64,
+    #[serde(rename = "type")]
+    node_type: String,
+    name: Option<String>,
+    rect: Rect,
+    /// The client's area, relative to `rect`
+    window_rect: Option<Rect>,
+    #[serde(default)]
+    focused: bool,
+    /// Ids of the children, most recently focused first
+    #[serde(default)]
+    focus: Vec<i64>,
+    app_id: Option<String>,
+    window_properties: Option<WindowProperties>,
+    #[serde(default)]
+    nodes: Vec<Node>,
+    #[serde(default)]
+    floating_nodes: Vec<Node>,
+}
+
+impl Node {
+    fn is_window(&self) -> bool {
+        (self.node_type == "con" || self.node_type == "floating_con")
+            && (self.app_id.is_some() || self.window_properties.is_some())
+    }
+
+    fn app_name(&self) -> String {
+        self.app_id
+            .clone()
+            .or_else(|| self.window_properties.as_ref().and_then(|props| props.class.clone()))
+            .unwrap_or_default()
+    }
+
+    fn to_window_info(&self) -> WindowInfo {
+        WindowInfo {
+            id: self.id as u32,
+            title: self.name.clone().unwrap_or_default(),
+            app_name: self.app_name(),
+            x: self.rect.x,
+            y: self.rect.y,
+            width: self.rect.width,
+            height: self.rect.height,
+        }
+    }
+
+    fn insets(&self) -> Option<Insets> {
+        let client = self.window_rect.as_ref()?;
+        Some(Insets {
+            top: client.y,
+            left: client.x,
+            bottom: self.rect.height - client.y - client.height,
+            right: self.rect.width - client.x - client.width,
+        })
+    }
+
+    fn collect_windows<'a>(&'a self, windows: &mut Vec<&'a Node>) {
+        // The scratchpad holds hidden windows which cannot be resized in place
+        if self.node_type == "workspace" && self.name.as_deref() == Some("__i3_scratch") {
+            return;
+        }
+
+        if self.is_window() {
+            windows.push(self);
+        }
+
+        for child in self.nodes.iter().chain(self.floating_nodes.iter()) {
+            child.collect_windows(windows);
+        }
+    }
+
+    /// Like `collect_windows`, but most recently focused first.
+    fn collect_windows_by_focus<'a>(&'a self, windows: &mut Vec<&'a Node>) {
+        if self.node_type == "workspace" && self.name.as_deref() == Some("__i3_scratch") {
+            return;
+        }
+
+        if self.is_window() {
+            windows.push(self);
+        }
+
+        let mut children: Vec<&Node> = self.nodes.iter().chain(self.floating_nodes.iter()).collect();
+        // Stable, so children missing from `focus` keep the layout order
+        children.sort_by_key(|child| self.focus.iter().position(|id| *id == child.id).unwrap_or(usize::MAX));
+        for child in children {
+            child.collect_windows_by_focus(windows);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Output {
+    name: String,
+    #[serde(default)]
+    active: bool,
+    #[serde(default)]
+    primary: bool,
+    #[serde(default = "default_scale")]
+    scale: f64,
+    rect: Rect,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Deserialize)]
+struct Workspace {
+    output: String,
+    visible: bool,
+    rect: Rect,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommandResult {
+    success: bool,
+    error: Option<String>,
+}
+
+/// Window backend for sway (and other compositors speaking the i3 IPC
+/// protocol), found through `SWAYSOCK`.
+pub struct SwayBackend {
+    socket_path: PathBuf,
+}
+
+impl SwayBackend {
+    pub fn from_env() -> Option<Self> {
+        std::env::var_os("SWAYSOCK").map(|path| Self::new(PathBuf::from(path)))
+    }
+
+    pub fn new(socket_path: PathBuf) -> Self {
+        Self { socket_path }
+    }
+
+    fn request(&self, message_type: u32, payload: &str) -> Result<Vec<u8>, FrameFitError> {
+        let mut stream = UnixStream::connect(&self.socket_path)
+            .map_err(|e| FrameFitError::unavailable(format!("Failed to connect to sway IPC socket: {}", e)))?;
+
+        let mut message = Vec::with_capacity(14 + payload.len());
+        message.extend_from_slice(IPC_MAGIC);
+        message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+        message.extend_from_slice(&message_type.to_ne_bytes());
+        message.extend_from_slice(payload.as_bytes());
+        stream.write_all(&message).map_err(ipc_error)?;
+
+        let mut header = [0u8; 14];
+        stream.read_exact(&mut header).map_err(ipc_error)?;
+        if &header[..6] != IPC_MAGIC {
+            return Err(FrameFitError::backend("Invalid reply from sway IPC socket"));
+        }
+
+        let length = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]) as usize;
+        let mut reply = vec![0u8; length];
+        stream.read_exact(&mut reply).map_err(ipc_error)?;
+
+        Ok(reply)
+    }
+
+    fn get_tree(&self) -> Result<Node, FrameFitError> {
+        let reply = self.request(GET_TREE, "")?;
+        serde_json::from_slice(&reply).map_err(|e| FrameFitError::backend(format!("Failed to parse sway tree: {}", e)))
+    }
+
+    fn run_command(&self, command: &str) -> Result<(), FrameFitError> {
+        let reply = self.request(RUN_COMMAND, command)?;
+        let results: Vec<CommandResult> = serde_json::from_slice(&reply)
+            .map_err(|e| FrameFitError::backend(format!("Failed to parse sway command reply: {}", e)))?;
+
+        match results.into_iter().find(|result| !result.success) {
+            Some(failed) => Err(FrameFitError::backend(format!(
+                "sway command failed: {}",
+                failed.error.unwrap_or_else(|| command.to_string())
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl WindowBackend for SwayBackend {
+    fn name(&self) -> &'static str {
+        "sway"
+    }
+
+    fn check_permissions(&self) -> bool {
+        UnixStream::connect(&self.socket_path).is_ok()
+    }
+
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, FrameFitError> {
+        let tree = self.get_tree()?;
+        let mut nodes = Vec::new();
+        tree.collect_windows(&mut nodes);
+
+        Ok(nodes
+            .into_iter()
+            .map(Node::to_window_info)
+            .filter(|window| window.width > 50 && window.height > 50 && !is_framefit(&window.app_name))
+            .collect())
+    }
+
+    fn get_frontmost_window(&self) -> Result<WindowInfo, FrameFitError> {
+        let tree = self.get_tree()?;
+        let mut nodes = Vec::new();
+        tree.collect_windows_by_focus(&mut nodes);
+
+        let focused = nodes.iter().find(|node| node.focused).ok_or(FrameFitError::NoWindows)?;
+        if !is_framefit(&focused.app_name()) {
+            return Ok(focused.to_window_info());
+        }
+
+        // FrameFit itself is focused, e.g. after clicking its resize button,
+        // so take the window that had the focus before it
+        nodes
+            .into_iter()
+            .map(Node::to_window_info)
+            .find(|window| !is_framefit(&window.app_name))
+            .ok_or(FrameFitError::NoWindows)
+    }
+
+    fn focus_window(&self, window: &WindowInfo) -> Result<(), FrameFitError> {
+        self.run_command(&format!("[con_id={}] focus", window.id))
+    }
+
+    fn move_window(&self, window: &WindowInfo, x: i32, y: i32) -> Result<(), FrameFitError> {
+        self.run_command(&format!(
+            "[con_id={}] floating enable, move absolute position {} px {} px",
+            window.id, x, y
+        ))
+    }
+
+    fn resize_window(&self, window: &WindowInfo, width: i32, height: i32) -> Result<(), FrameFitError> {
+        // Tiled windows get their size from the layout, so float them first
+        self.run_command(&format!(
+            "[con_id={}] floating enable, resize set width {} px height {} px",
+            window.id, width, height
+        ))
+    }
+
+    fn list_screens(&self) -> Result<Vec<ScreenInfo>, FrameFitError> {
+        let reply = self.request(GET_OUTPUTS, "")?;
+        let outputs: Vec<Output> = serde_json::from_slice(&reply)
+            .map_err(|e| FrameFitError::backend(format!("Failed to parse sway outputs: {}", e)))?;
+        let has_primary = outputs.iter().any(|output| output.active && output.primary);
+
+        // The visible workspace's rect is the output minus bars and other
+        // exclusive layer-shell surfaces
+        let reply = self.request(GET_WORKSPACES, "")?;
+        let workspaces: Vec<Workspace> = serde_json::from_slice(&reply)
+            .map_err(|e| FrameFitError::backend(format!("Failed to parse sway workspaces: {}", e)))?;
+
+        Ok(outputs
+            .into_iter()
+            .filter(|output| output.active)
+            .enumerate()
+            .map(|(index, output)| {
+                let frame = output.rect.to_screen_rect();
+                let work_area = workspaces
+                    .iter()
+                    .find(|workspace| workspace.visible && workspace.output == output.name)
+                    .map_or(frame, |workspace| workspace.rect.to_screen_rect());
+
+                ScreenInfo {
+                    id: index as u32,
+                    is_main: output.primary || (!has_primary && index == 0),
+                    name: output.name,
+                    frame,
+                    work_area,
+                    scale_factor: output.scale,
+                }
+            })
+            .collect())
+    }
+
+    fn content_area(&self, window: &WindowInfo) -> Result<Option<ContentArea>, FrameFitError> {
+        let tree = self.get_tree()?;
+        let mut nodes = Vec::new();
+        tree.collect_windows(&mut nodes);
+
+        let node = nodes
+            .into_iter()
+            .find(|node| node.id as u32 == window.id)
+            .ok_or(FrameFitError::WindowNotFound { id: window.id })?;
+        Ok(node.insets().map(ContentArea::Client))
+    }
+}
+
+fn ipc_error(error: std::io::Error) -> FrameFitError {
+    FrameFitError::backend(format!("sway IPC error: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::fake_ipc::FakeIpcServer;
+
+    const GET_TREE_REPLY: &str = include_str!("../../tests/fixtures/sway/get_tree.json");
+    const GET_OUTPUTS_REPLY: &str = include_str!("../../tests/fixtures/sway/get_outputs.json");
+    const GET_WORKSPACES_REPLY: &str = include_str!("../../tests/fixtures/sway/get_workspaces.json");
+    const COMMAND_REPLY: &str = include_str!("../../tests/fixtures/sway/run_command.json");
+
+    fn frame(message_type: u32, payload: &str) -> Vec<u8> {
+        let mut message = IPC_MAGIC.to_vec();
+        message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+        message.extend_from_slice(&message_type.to_ne_bytes());
+        message.extend_from_slice(payload.as_bytes());
+        message
+    }
+
+    fn fake_sway() -> FakeIpcServer {
+        fake_sway_with_tree(GET_TREE_REPLY.to_string())
+    }
+
+    fn fake_sway_with_tree(tree: String) -> FakeIpcServer {
+        FakeIpcServer::spawn("sway", move |stream| {
+            let mut header = [0u8; 14];
+            stream.read_exact(&mut header).ok()?;
+            let length = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]) as usize;
+            let message_type = u32::from_ne_bytes([header[10], header[11], header[12], header[13]]);
+            let mut payload = vec![0u8; length];
+            stream.read_exact(&mut payload).ok()?;
+
+            let reply = match message_type {
+                GET_TREE => tree.as_str(),
+                GET_OUTPUTS => GET_OUTPUTS_REPLY,
+                GET_WORKSPACES => GET_WORKSPACES_REPLY,
+                _ => COMMAND_REPLY,
+            };
+
+            Some((String::from_utf8_lossy(&payload).into_owned(), frame(message_type, reply)))
+        })
+    }
+
+    #[test]
+    fn lists_windows_from_the_tree() {
+        let server = fake_sway();
+        let backend = SwayBackend::new(server.path.clone());
+
+        let windows = backend.list_windows().unwrap();
+        let apps: Vec<_> = windows.iter().map(|w| w.app_name.as_str()).collect();
+
+        // FrameFit itself and the scratchpad window are filtered out
+        assert_eq!(apps, ["kitty", "firefox", "Gimp-2.10"]);
+        assert_eq!(windows[1].id, 7);
+        assert_eq!(windows[1].title, "Mozilla Firefox");
+        assert_eq!((windows[1].x, windows[1].y, windows[1].width, windows[1].height), (960, 30, 960, 1050));
+    }
+
+    #[test]
+    fn frontmost_window_is_the_focused_node() {
+        let server = fake_sway();
+        let backend = SwayBackend::new(server.path.clone());
+
+        let window = backend.get_frontmost_window().unwrap();
+        assert_eq!(window.app_name, "firefox");
+    }
+
+    #[test]
+    fn frontmost_window_skips_a_focused_framefit() {
+        // FrameFit focused, Firefox before it and kitty before that
+        let tree = GET_TREE_REPLY
+            .replace(r#""focused": true"#, r#""focused": false"#)
+            .replace(
+                r#""focused": false,
+              "focus": [],
+              "app_id": "FrameFit""#,
+                r#""focused": true,
+              "focus": [],
+              "app_id": "FrameFit""#,
+            )
+            .replace("[7, 10, 4, 9]", "[10, 7, 4, 9]");
+        let server = fake_sway_with_tree(tree);
+        let backend = SwayBackend::new(server.path.clone());
+
+        let window = backend.get_frontmost_window().unwrap();
+        assert_eq!(window.app_name, "firefox");
+    }
+
+    #[test]
+    fn resize_floats_the_window_first() {
+        let server = fake_sway();
+        let backend = SwayBackend::new(server.path.clone());
+        let window = backend.get_frontmost_window().unwrap();
+
+        backend.resize_window(&window, 390, 844).unwrap();
+        backend.move_window(&window, 10, 20).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[requests.len() - 2],
+            "[con_id=7] floating enable, resize set width 390 px height 844 px"
+        );
+        assert_eq!(
+            requests[requests.len() - 1],
+            "[con_id=7] floating enable, move absolute position 10 px 20 px"
+        );
+    }
+
+    #[test]
+    fn lists_active_outputs() {
+        let server = fake_sway();
+        let backend = SwayBackend::new(server.path.clone());
+
+        let screens = backend.list_screens().unwrap();
+        assert_eq!(screens.len(), 2);
+        assert_eq!(screens[0].name, "eDP-1");
+        assert!(screens[0].is_main);
+        assert_eq!(screens[1].frame, ScreenRect::new(1920, 0, 2560, 1440));
+        assert_eq!(screens[0].work_area, ScreenRect::new(0, 0, 1920, 1050));
+        assert_eq!(screens[1].scale_factor, 1.5);
+    }
+}
@@ -0,0 +1,528 @@
+use super::{is_framefit, ContentArea, Insets, Rect, ScreenInfo, Size, SizeHints, WindowBackend, WindowInfo};
+use crate::error::FrameFitError;
+
+use std::time::Duration;
+use x11rb::connection::Connection;
+use x11rb::properties::WmSizeHints;
+use x11rb::protocol::randr::ConnectionExt as _;
+use x11rb::protocol::xproto::{AtomEnum, ClientMessageEvent, ConnectionExt as _, EventMask, Window};
+use x11rb::rust_connection::RustConnection;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_CLIENT_LIST,
+        _NET_CLIENT_LIST_STACKING,
+        _NET_ACTIVE_WINDOW,
+        _NET_MOVERESIZE_WINDOW,
+        _NET_WM_NAME,
+        _NET_WM_WINDOW_TYPE,
+        _NET_WM_WINDOW_TYPE_DOCK,
+        _NET_WM_WINDOW_TYPE_DESKTOP,
+        _NET_WORKAREA,
+        _NET_FRAME_EXTENTS,
+        _NET_CURRENT_DESKTOP,
+        RESOURCE_MANAGER,
+        UTF8_STRING,
+    }
+}
+
+// EWMH source indication: requests come from a pager-like tool acting for the user
+const SOURCE_PAGER: u32 = 2;
+
+// _NET_MOVERESIZE_WINDOW flags (which of x, y, width and height are present)
+const MOVERESIZE_X: u32 = 1 << 8;
+const MOVERESIZE_Y: u32 = 1 << 9;
+const MOVERESIZE_WIDTH: u32 = 1 << 10;
+const MOVERESIZE_HEIGHT: u32 = 1 << 11;
+
+// Static gravity makes x/y refer to the client area; positions are converted
+// from and to the frame with _NET_FRAME_EXTENTS
+const STATIC_GRAVITY: u32 = 10;
+
+/// Window backend for X11 desktops, driven through the EWMH hints that any
+/// reasonably modern window manager supports.
+pub struct X11Backend {
+    conn: RustConnection,
+    root: Window,
+    atoms: Atoms,
+}
+
+impl X11Backend {
+    pub fn connect() -> Result<Self, FrameFitError> {
+        let (conn, screen_num) =
+            x11rb::connect(None).map_err(|e| FrameFitError::unavailable(format!("Failed to connect to X server: {}", e)))?;
+        let root = conn.setup().roots[screen_num].root;
+        let atoms = Atoms::new(&conn).map_err(x11_error)?.reply().map_err(x11_error)?;
+
+        Ok(Self { conn, root, atoms })
+    }
+
+    fn client_list(&self) -> Result<Vec<Window>, FrameFitError> {
+        let reply = self
+            .conn
+            .get_property(false, self.root, self.atoms._NET_CLIENT_LIST, AtomEnum::WINDOW, 0, u32::MAX)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+
+        let windows = reply.value32().map(|windows| windows.collect());
+        windows.ok_or_else(|| FrameFitError::unavailable("Window manager does not support _NET_CLIENT_LIST"))
+    }
+
+    /// Managed windows from bottom to top.
+    fn stacking_order(&self) -> Result<Vec<Window>, FrameFitError> {
+        let reply = self
+            .conn
+            .get_property(false, self.root, self.atoms._NET_CLIENT_LIST_STACKING, AtomEnum::WINDOW, 0, u32::MAX)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+
+        Ok(reply.value32().map(|windows| windows.collect()).unwrap_or_default())
+    }
+
+    fn window_info(&self, window: Window) -> Result<WindowInfo, FrameFitError> {
+        let geometry = self
+            .conn
+            .get_geometry(window)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+        let origin = self
+            .conn
+            .translate_coordinates(window, self.root, 0, 0)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+
+        // Report the frame including decorations, like the other backends
+        let extents = self.frame_extents(window);
+        Ok(WindowInfo {
+            id: window,
+            title: self.window_title(window)?,
+            app_name: self.window_class(window)?,
+            x: i32::from(origin.dst_x) - extents.left,
+            y: i32::from(origin.dst_y) - extents.top,
+            width: i32::from(geometry.width) + extents.horizontal(),
+            height: i32::from(geometry.height) + extents.vertical(),
+        })
+    }
+
+    /// The window manager's decorations around a client, zero for
+    /// undecorated windows or window managers without the hint.
+    fn frame_extents(&self, window: Window) -> Insets {
+        let reply = self
+            .conn
+            .get_property(false, window, self.atoms._NET_FRAME_EXTENTS, AtomEnum::CARDINAL, 0, 4)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok());
+        let extents: Option<Vec<u32>> = reply.as_ref().and_then(|reply| reply.value32()).map(|values| values.collect());
+
+        match extents.as_deref() {
+            Some([left, right, top, bottom]) => Insets {
+                top: *top as i32,
+                left: *left as i32,
+                bottom: *bottom as i32,
+                right: *right as i32,
+            },
+            _ => Insets::default(),
+        }
+    }
+
+    fn window_title(&self, window: Window) -> Result<String, FrameFitError> {
+        let title = self.read_string(window, self.atoms._NET_WM_NAME, self.atoms.UTF8_STRING)?;
+        if !title.is_empty() {
+            return Ok(title);
+        }
+
+        self.read_string(window, AtomEnum::WM_NAME.into(), AtomEnum::STRING.into())
+    }
+
+    fn window_class(&self, window: Window) -> Result<String, FrameFitError> {
+        let reply = self
+            .conn
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+
+        // WM_CLASS holds "instance\0class\0"; the class is the application name
+        let mut parts = reply.value.split(|b| *b == 0).filter(|part| !part.is_empty());
+        let instance = parts.next();
+        let class = parts.next().or(instance);
+
+        Ok(class
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .unwrap_or_default())
+    }
+
+    fn read_string(&self, window: Window, property: u32, kind: u32) -> Result<String, FrameFitError> {
+        let reply = self
+            .conn
+            .get_property(false, window, property, kind, 0, 1024)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+
+        Ok(String::from_utf8_lossy(&reply.value).into_owned())
+    }
+
+    fn root_cardinals(&self, property: u32) -> Option<Vec<u32>> {
+        let reply = self
+            .conn
+            .get_property(false, self.root, property, AtomEnum::CARDINAL, 0, 1024)
+            .ok()?
+            .reply()
+            .ok()?;
+
+        let values = reply.value32().map(|values| values.collect());
+        values
+    }
+
+    /// The usable area of the current desktop, which window managers
+    /// publish as the bounding box of all monitors minus panel struts.
+    fn work_area(&self) -> Option<Rect> {
+        let desktop = self
+            .root_cardinals(self.atoms._NET_CURRENT_DESKTOP)
+            .and_then(|values| values.first().copied())
+            .unwrap_or(0) as usize;
+        let areas = self.root_cardinals(self.atoms._NET_WORKAREA)?;
+        let area = areas.get(desktop * 4..desktop * 4 + 4)?;
+
+        Some(Rect::new(area[0] as i32, area[1] as i32, area[2] as i32, area[3] as i32))
+    }
+
+    /// X11 has no per-monitor scaling; desktops that scale set `Xft.dpi`.
+    fn scale_factor(&self) -> f64 {
+        let resources = self
+            .read_string(self.root, self.atoms.RESOURCE_MANAGER, AtomEnum::STRING.into())
+            .unwrap_or_default();
+
+        resources
+            .lines()
+            .find_map(|line| line.strip_prefix("Xft.dpi:"))
+            .and_then(|dpi| dpi.trim().parse::<f64>().ok())
+            .map_or(1.0, |dpi| dpi / 96.0)
+    }
+
+    fn is_desktop_or_dock(&self, window: Window) -> bool {
+        let reply = self
+            .conn
+            .get_property(false, window, self.atoms._NET_WM_WINDOW_TYPE, AtomEnum::ATOM, 0, 32)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok());
+
+        reply
+            .and_then(|reply| {
+                reply.value32().map(|mut types| {
+                    types.any(|kind| {
+                        kind == self.atoms._NET_WM_WINDOW_TYPE_DOCK
+                            || kind == self.atoms._NET_WM_WINDOW_TYPE_DESKTOP
+                    })
+                })
+            })
+            .unwrap_or(false)
+    }
+
+    fn send_client_message(&self, window: Window, message_type: u32, data: [u32; 5]) -> Result<(), FrameFitError> {
+        let event = ClientMessageEvent::new(32, window, message_type, data);
+        self.conn
+            .send_event(
+                false,
+                self.root,
+                EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                event,
+            )
+            .map_err(x11_error)?;
+        self.conn.flush().map_err(x11_error)?;
+
+        Ok(())
+    }
+}
+
+impl WindowBackend for X11Backend {
+    fn name(&self) -> &'static str {
+        "x11"
+    }
+
+    fn check_permissions(&self) -> bool {
+        // X11 has no permission model for window management
+        true
+    }
+
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, FrameFitError> {
+        let mut windows = Vec::new();
+
+        for window in self.client_list()? {
+            if self.is_desktop_or_dock(window) {
+                continue;
+            }
+
+            // Windows can disappear between listing and querying them
+            let Ok(info) = self.window_info(window) else {
+                continue;
+            };
+
+            if info.width > 50
+                && info.height > 50
+                && !is_framefit(&info.app_name)
+            {
+                windows.push(info);
+            }
+        }
+
+        Ok(windows)
+    }
+
+    fn get_frontmost_window(&self) -> Result<WindowInfo, FrameFitError> {
+        let reply = self
+            .conn
+            .get_property(false, self.root, self.atoms._NET_ACTIVE_WINDOW, AtomEnum::WINDOW, 0, 1)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+        let active = reply.value32().and_then(|mut windows| windows.next()).unwrap_or(0);
+
+        // When FrameFit itself is active, e.g. after clicking its resize
+        // button, fall back to the window right below it
+        let stacking = self.stacking_order().unwrap_or_default();
+        for window in frontmost_candidates(active, &stacking) {
+            if self.is_desktop_or_dock(window) {
+                continue;
+            }
+            let Ok(info) = self.window_info(window) else {
+                continue;
+            };
+            if !is_framefit(&info.app_name) {
+                return Ok(info);
+            }
+        }
+
+        Err(FrameFitError::NoWindows)
+    }
+
+    fn focus_window(&self, window: &WindowInfo) -> Result<(), FrameFitError> {
+        self.send_client_message(window.id, self.atoms._NET_ACTIVE_WINDOW, [SOURCE_PAGER, 0, 0, 0, 0])
+    }
+
+    fn move_window(&self, window: &WindowInfo, x: i32, y: i32) -> Result<(), FrameFitError> {
+        let extents = self.frame_extents(window.id);
+        let (x, y) = (x + extents.left, y + extents.top);
+        let flags = STATIC_GRAVITY | MOVERESIZE_X | MOVERESIZE_Y | (SOURCE_PAGER << 12);
+        self.send_client_message(
+            window.id,
+            self.atoms._NET_MOVERESIZE_WINDOW,
+            [flags, x as u32, y as u32, 0, 0],
+        )
+    }
+
+    fn resize_window(&self, window: &WindowInfo, width: i32, height: i32) -> Result<(), FrameFitError> {
+        let extents = self.frame_extents(window.id);
+        let width = (width - extents.horizontal()).max(1);
+        let height = (height - extents.vertical()).max(1);
+        let flags = STATIC_GRAVITY | MOVERESIZE_WIDTH | MOVERESIZE_HEIGHT | (SOURCE_PAGER << 12);
+        self.send_client_message(
+            window.id,
+            self.atoms._NET_MOVERESIZE_WINDOW,
+            [flags, 0, 0, width as u32, height as u32],
+        )
+    }
+
+    fn content_area(&self, window: &WindowInfo) -> Result<Option<ContentArea>, FrameFitError> {
+        Ok(Some(ContentArea::Client(self.frame_extents(window.id))))
+    }
+
+    fn window_frame(&self, window: &WindowInfo) -> Result<Rect, FrameFitError> {
+        self.window_info(window.id).map(|info| info.frame())
+    }
+
+    fn size_hints(&self, window: &WindowInfo) -> Result<SizeHints, FrameFitError> {
+        let hints = WmSizeHints::get_normal_hints(&self.conn, window.id)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+        let Some(hints) = hints else {
+            return Ok(SizeHints::default());
+        };
+
+        // Hints are for the client; resizes are in frame sizes
+        let extents = self.frame_extents(window.id);
+        let frame = |(width, height): (i32, i32)| Size {
+            width: width + extents.horizontal(),
+            height: height + extents.vertical(),
+        };
+
+        Ok(SizeHints {
+            min: hints.min_size.map(frame),
+            max: hints.max_size.map(frame),
+            increment: hints.size_increment.map(|(width, height)| Size { width, height }),
+        })
+    }
+
+    fn settle_delay(&self) -> Duration {
+        // The window manager handles _NET_MOVERESIZE_WINDOW asynchronously
+        Duration::from_millis(100)
+    }
+
+    fn list_screens(&self) -> Result<Vec<ScreenInfo>, FrameFitError> {
+        let monitors = self
+            .conn
+            .randr_get_monitors(self.root, true)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?
+            .monitors;
+
+        let work_area = self.work_area();
+        let scale_factor = self.scale_factor();
+        let screen_work_area = |frame: Rect| {
+            work_area
+                .map(|area| {
+                    let x = frame.x.max(area.x);
+                    let y = frame.y.max(area.y);
+                    let right = (frame.x + frame.width).min(area.x + area.width);
+                    let bottom = (frame.y + frame.height).min(area.y + area.height);
+                    Rect::new(x, y, right - x, bottom - y)
+                })
+                .filter(|area| area.width > 0 && area.height > 0)
+                .unwrap_or(frame)
+        };
+
+        if monitors.is_empty() {
+            let screen = &self.conn.setup().roots[0];
+            let frame = Rect::new(0, 0, screen.width_in_pixels.into(), screen.height_in_pixels.into());
+            return Ok(vec![ScreenInfo {
+                id: 0,
+                name: "Screen 0".to_string(),
+                frame,
+                work_area: screen_work_area(frame),
+                scale_factor,
+                is_main: true,
+            }]);
+        }
+
+        Ok(monitors
+            .iter()
+            .enumerate()
+            .map(|(index, monitor)| {
+                let name = self
+                    .conn
+                    .get_atom_name(monitor.name)
+                    .ok()
+                    .and_then(|cookie| cookie.reply().ok())
+                    .map(|reply| String::from_utf8_lossy(&reply.name).into_owned())
+                    .unwrap_or_else(|| format!("Monitor {}", index));
+
+                let frame = Rect::new(monitor.x.into(), monitor.y.into(), monitor.width.into(), monitor.height.into());
+                ScreenInfo {
+                    id: index as u32,
+                    name,
+                    frame,
+                    work_area: screen_work_area(frame),
+                    scale_factor,
+                    is_main: monitor.primary || (index == 0 && !monitors.iter().any(|m| m.primary)),
+                }
+            })
+            .collect())
+    }
+}
+
+/// The active window, then the rest from the top of the stack down.
+fn frontmost_candidates(active: Window, stacking: &[Window]) -> Vec<Window> {
+    let active = Some(active).filter(|window| *window != 0);
+    active
+        .into_iter()
+        .chain(stacking.iter().rev().copied().filter(|window| Some(*window) != active))
+        .collect()
+}
+
+fn x11_error(error: impl std::fmt::Display) -> FrameFitError {
+    FrameFitError::backend(format!("X11 error: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use x11rb::protocol::xproto::{CreateWindowAux, PropMode, WindowClass};
+    use x11rb::wrapper::ConnectionExt as _;
+
+    fn wait_for<T>(mut probe: impl FnMut() -> Option<T>) -> T {
+        for _ in 0..50 {
+            if let Some(value) = probe() {
+                return value;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        panic!("timed out waiting for the window manager");
+    }
+
+    #[test]
+    fn frontmost_candidates_start_with_the_active_window() {
+        // _NET_CLIENT_LIST_STACKING is bottom to top
+        assert_eq!(frontmost_candidates(0x30, &[0x10, 0x20, 0x30]), [0x30, 0x20, 0x10]);
+        assert_eq!(frontmost_candidates(0x10, &[0x10, 0x20, 0x30]), [0x10, 0x30, 0x20]);
+        assert_eq!(frontmost_candidates(0, &[0x10, 0x20]), [0x20, 0x10]);
+        assert!(frontmost_candidates(0, &[]).is_empty());
+    }
+
+    // Run with e.g. `Xvfb :99 & DISPLAY=:99 openbox & DISPLAY=:99 cargo test -- --ignored`
+    #[test]
+    #[ignore = "requires an X server with an EWMH window manager"]
+    fn lists_moves_and_resizes_a_managed_window() {
+        let backend = X11Backend::connect().expect("no X server on $DISPLAY");
+
+        // A second connection plays the part of another application
+        let (conn, screen_num) = x11rb::connect(None).unwrap();
+        let root = conn.setup().roots[screen_num].root;
+        let window = conn.generate_id().unwrap();
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            300,
+            200,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )
+        .unwrap();
+        conn.change_property8(PropMode::REPLACE, window, AtomEnum::WM_CLASS, AtomEnum::STRING, b"xtest\0XTest\0")
+            .unwrap();
+        conn.change_property8(
+            PropMode::REPLACE,
+            window,
+            backend.atoms._NET_WM_NAME,
+            backend.atoms.UTF8_STRING,
+            "Test – window".as_bytes(),
+        )
+        .unwrap();
+        conn.map_window(window).unwrap();
+        conn.flush().unwrap();
+
+        let info = wait_for(|| backend.list_windows().ok()?.into_iter().find(|w| w.id == window));
+        assert_eq!(info.app_name, "XTest");
+        assert_eq!(info.title, "Test – window");
+
+        backend.resize_window(&info, 640, 480).unwrap();
+        let info = wait_for(|| {
+            backend
+                .list_windows()
+                .ok()?
+                .into_iter()
+                .find(|w| w.id == window && w.width == 640 && w.height == 480)
+        });
+
+        backend.move_window(&info, 100, 120).unwrap();
+        wait_for(|| {
+            backend
+                .list_windows()
+                .ok()?
+                .into_iter()
+                .find(|w| w.id == window && w.x == 100 && w.y == 120)
+        });
+    }
+}
@@ -0,0 +1,151 @@
+//! Drives the control server through a real Unix socket, like an external
+//! script would.
+#![cfg(unix)]
+
+use framefit_lib::control::{Client, ClientError, Context, ControlServer, ResizeParams, RpcError, PROTOCOL_VERSION};
+use framefit_lib::placement::Placement;
+use framefit_lib::presets::{self, Preset};
+use framefit_lib::window_manager::fake::{FakeWindow, FakeWindowServer};
+use framefit_lib::window_manager::{ResizeOutcome, WindowBackend};
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+static NEXT_SOCKET: AtomicUsize = AtomicUsize::new(0);
+
+struct FakeApp {
+    backend: FakeWindowServer,
+    resizes: Mutex<Vec<ResizeOutcome>>,
+}
+
+impl Context for FakeApp {
+    fn backend(&self) -> &dyn WindowBackend {
+        &self.backend
+    }
+
+    fn presets(&self) -> Vec<Preset> {
+        let mut presets = presets::default_presets();
+        presets.push(Preset { placement: Some(Placement::KEEP), ..Preset::new("Blog", 1200, 800) });
+        presets
+    }
+
+    fn resized(&self, outcome: &ResizeOutcome) {
+        self.resizes.lock().unwrap().push(outcome.clone());
+    }
+}
+
+fn start() -> (ControlServer, Arc<FakeApp>) {
+    let app = Arc::new(FakeApp {
+        backend: FakeWindowServer::new()
+            .with_screen("Studio Display", 0, 0, 2560, 1440)
+            .with_window(FakeWindow::new(1, "Safari", "Apple", 100, 100, 1024, 768))
+            .with_window(FakeWindow::new(2, "Terminal", "~ — zsh", 200, 200, 720, 480)),
+        resizes: Mutex::new(Vec::new()),
+    });
+
+    let path = socket_path();
+    let server = ControlServer::bind(&path, app.clone()).unwrap();
+    (server, app)
+}
+
+fn socket_path() -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "framefit-control-{}-{}.sock",
+        std::process::id(),
+        NEXT_SOCKET.fetch_add(1, Ordering::SeqCst)
+    ))
+}
+
+#[test]
+fn client_lists_and_resizes_windows() {
+    let (server, app) = start();
+    let mut client = Client::connect(server.path()).unwrap();
+
+    assert_eq!(client.version().unwrap().protocol, PROTOCOL_VERSION);
+    assert_eq!(client.windows().unwrap().len(), 2);
+
+    let outcome = client.resize(Some(2), 800, 600, ResizeParams::default()).unwrap();
+
+    assert_eq!((outcome.achieved.width, outcome.achieved.height), (800, 600));
+    assert_eq!(app.backend.window(2).unwrap().x, 200);
+    assert_eq!(app.resizes.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn presets_use_their_own_placement() {
+    let (server, app) = start();
+    let mut client = Client::connect(server.path()).unwrap();
+
+    client.apply_preset("ipad", Some(1), ResizeParams::default()).unwrap();
+    let ipad = app.backend.window(1).unwrap();
+    assert_eq!((ipad.x, ipad.y, ipad.width, ipad.height), (896, 208, 768, 1024));
+
+    client.apply_preset("blog", None, ResizeParams::default()).unwrap();
+    let blog = app.backend.window(1).unwrap();
+    assert_eq!((blog.x, blog.y, blog.width, blog.height), (896, 208, 1200, 800));
+}
+
+#[test]
+fn errors_keep_their_codes() {
+    let (server, _app) = start();
+    let mut client = Client::connect(server.path()).unwrap();
+
+    match client.apply_preset("Watch", None, ResizeParams::default()) {
+        Err(ClientError::Rpc(error)) => assert_eq!(error.code, RpcError::UNKNOWN_PRESET),
+        other => panic!("expected an unknown preset, got {:?}", other.map(|_| ())),
+    }
+    match client.resize(Some(42), 800, 600, ResizeParams::default()) {
+        Err(ClientError::Rpc(error)) => assert_eq!(error.app_code(), Some("WINDOW_NOT_FOUND")),
+        other => panic!("expected a missing window, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn plain_socket_clients_can_pipeline_requests() {
+    let (server, _app) = start();
+    let mut stream = UnixStream::connect(server.path()).unwrap();
+
+    writeln!(stream, r#"{{"jsonrpc":"2.0","id":1,"method":"get_screens"}}"#).unwrap();
+    writeln!(stream, r#"{{"jsonrpc":"2.0","id":2,"method":"check_permissions","params":{{}}}}"#).unwrap();
+
+    let mut lines = BufReader::new(stream).lines();
+    let first: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    let second: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+
+    assert_eq!(first["result"][0]["name"], "Studio Display");
+    assert_eq!(second, serde_json::json!({ "jsonrpc": "2.0", "version": 1, "id": 2, "result": true }));
+}
+
+#[test]
+fn refuses_to_replace_a_running_server() {
+    let (server, _app) = start();
+    let (_, app) = start();
+
+    let error = ControlServer::bind(server.path(), app).err().unwrap();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::AddrInUse);
+}
+
+#[test]
+fn only_the_user_can_connect() {
+    let (server, _app) = start();
+
+    let mode = std::fs::metadata(server.path()).unwrap().permissions().mode();
+
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn dropping_the_server_removes_the_socket() {
+    let (server, _app) = start();
+    let path = server.path().to_path_buf();
+
+    drop(server);
+
+    assert!(!path.exists());
+    assert!(Client::connect(&path).is_err());
+}
@@ -1,11 +1,65 @@
-import { useEffect } from "react";
+import { useEffect, useState } from "react";
 import { Store } from "@tauri-apps/plugin-store";
 import { getCurrentWindow } from "@tauri-apps/api/window";
-import { AppProvider, useApp, DEFAULT_PRESETS } from "./context/AppContext";
+import { invoke } from "@tauri-apps/api/core";
+import {
+  AppProvider,
+  useApp,
+  type Anchor,
+  type HotkeyAction,
+  type HotkeyBinding,
+  type Placement,
+  type Preset,
+  type SizeMode,
+} from "./context/AppContext";
 import { usePresetManagement } from "./hooks/usePresetManagement";
-import { useWindowOperations } from "./hooks/useWindowOperations";
+import { useHotkeys, shortcutFromEvent } from "./hooks/useHotkeys";
+import { useLayouts } from "./hooks/useLayouts";
+import { useRules, type RuleMatch } from "./hooks/useRules";
+import { useDevices, type Device, type DeviceCategory } from "./hooks/useDevices";
+import { useWindowOperations, errorMessage, type ResizeOutcome, type WindowInfo } from "./hooks/useWindowOperations";
 import "./App.css";
 
+const DEVICE_CATEGORIES: { value: DeviceCategory | ""; label: string }[] = [
+  { value: "", label: "All devices" },
+  { value: "phone", label: "Phones" },
+  { value: "tablet", label: "Tablets" },
+  { value: "laptop", label: "Laptops" },
+  { value: "desktop", label: "Desktops" },
+  { value: "tv", label: "TVs" },
+  { value: "social", label: "Social video" },
+];
+
+const PLACEMENT_OPTIONS: { value: Anchor | "absolute"; label: string }[] = [
+  { value: "keep", label: "Keep position" },
+  { value: "center", label: "Center" },
+  { value: "top-left", label: "Top left" },
+  { value: "top", label: "Top" },
+  { value: "top-right", label: "Top right" },
+  { value: "left", label: "Left" },
+  { value: "right", label: "Right" },
+  { value: "bottom-left", label: "Bottom left" },
+  { value: "bottom", label: "Bottom" },
+  { value: "bottom-right", label: "Bottom right" },
+  { value: "absolute", label: "Absolute" },
+];
+
+function placementLabel(placement: Placement): string {
+  return PLACEMENT_OPTIONS.find((option) => option.value === placement.mode)?.label ?? placement.mode;
+}
+
+// Select values for hotkey actions; presets are "preset:<id>"
+function actionValue(action: HotkeyAction): string {
+  return action.action === "preset" ? `preset:${action.id}` : action.action;
+}
+
+function actionFromValue(value: string): HotkeyAction {
+  if (value.startsWith("preset:")) {
+    return { action: "preset", id: value.slice("preset:".length) };
+  }
+  return { action: value as Exclude<HotkeyAction["action"], "preset"> };
+}
+
 function AppContent() {
   const {
     windows,
@@ -30,19 +84,81 @@ function AppContent() {
     setNewPresetName,
     showMenu,
     setShowMenu,
-    centerWindow,
-    setCenterWindow,
+    placement,
+    setPlacement,
+    sizeMode,
+    setSizeMode,
+    hotkeys,
+    setHotkeys,
+    showHotkeys,
+    setShowHotkeys,
+    layouts,
+    showLayouts,
+    setShowLayouts,
+    rules,
+    setRules,
+    showRules,
+    setShowRules,
+    showDevices,
+    setShowDevices,
     setStore: setContextStore,
   } = useApp();
 
-  const { loadPresets, addPreset, deletePreset, exportPresets, importPresetsHandler, resetPresets } = usePresetManagement();
-  const { checkPermissions, loadWindows, resizeFrontmost, resizeSelected, resizeFrontmostByDimensions } = useWindowOperations();
+  const {
+    loadPresets,
+    addPreset,
+    deletePreset,
+    movePreset,
+    exportPresets,
+    importPresetsHandler,
+    resetPresets,
+  } = usePresetManagement();
+  const {
+    checkPermissions,
+    loadWindows,
+    resizeFrontmost,
+    resizeSelected,
+    undoResize,
+    redoResize,
+    restoreOriginalSize,
+  } = useWindowOperations();
+  const { loadHotkeys, saveHotkeys } = useHotkeys();
+  const { loadLayouts, captureLayout, restoreLayout, deleteLayout } = useLayouts();
+  const [newLayoutName, setNewLayoutName] = useState("");
+  const { loadRules, saveRules, dryRunRules } = useRules();
+  const { searchDevices, addDevicePreset } = useDevices();
+  const [deviceQuery, setDeviceQuery] = useState("");
+  const [deviceCategory, setDeviceCategory] = useState<DeviceCategory | "">("");
+  const [devices, setDevices] = useState<Device[]>([]);
+
+  useEffect(() => {
+    if (!showDevices) {
+      return;
+    }
+    let current = true;
+    searchDevices(deviceQuery, { categories: deviceCategory ? [deviceCategory] : [] }).then((results) => {
+      // Ignore results of queries typed over since
+      if (current) {
+        setDevices(results);
+      }
+    });
+    return () => {
+      current = false;
+    };
+  }, [showDevices, deviceQuery, deviceCategory]);
+
+  function updateHotkey(index: number, binding: HotkeyBinding) {
+    setHotkeys(hotkeys.map((existing, i) => (i === index ? binding : existing)));
+  }
 
   useEffect(() => {
     async function initialize() {
       const s = await Store.load("presets.json");
       setContextStore(s);
-      await loadPresets(s);
+      await loadPresets();
+      await loadHotkeys();
+      await loadLayouts();
+      await loadRules();
 
       const hasPerms = await checkPermissions();
       setHasPermissions(hasPerms);
@@ -56,46 +172,76 @@ function AppContent() {
 
     initialize();
 
-    // Listen for tray menu actions
     const setupListeners = async () => {
       const window = getCurrentWindow();
-      const unlistenTray = await window.listen("tray_menu_action", async (event: any) => {
-        const action = event.payload;
-
-        const presetMap: Record<string, [number, number]> = {
-          "preset_iphone_se": [375, 667],
-          "preset_iphone_14": [390, 844],
-          "preset_ipad": [768, 1024],
-          "preset_hd": [1280, 720],
-          "preset_fhd": [1920, 1080],
-        };
-
-        if (presetMap[action]) {
-          const [w, h] = presetMap[action];
-          setWidth(w);
-          setHeight(h);
-          await resizeFrontmostByDimensions(w, h);
-        } else if (action.startsWith("custom_preset_")) {
-          // Handle dynamic custom preset IDs (now based on preset name)
-          const presetName = action.replace("custom_preset_", "").replace(/_/g, " ");
-
-          if (presetName) {
-            const store = await Store.load("presets.json");
-            const customPresetsData = await store.get("customPresets");
-
-            if (Array.isArray(customPresetsData)) {
-              const preset = customPresetsData.find((p: any) => p.name.toLowerCase() === presetName.toLowerCase());
-              if (preset) {
-                setWidth(preset.width);
-                setHeight(preset.height);
-                await resizeFrontmostByDimensions(preset.width, preset.height);
-              }
-            }
-          }
+
+      // Presets picked in the tray menu or opened as links are applied by the backend
+      const unlistenPresets = await window.listen<{ preset: Preset; outcome: ResizeOutcome | null; error: unknown }>("preset_applied", (event) => {
+        const { preset, error } = event.payload;
+        if (error) {
+          setMessage(`❌ ${preset.name}: ${errorMessage(error)}`);
+          setTimeout(() => setMessage(""), 5000);
+        } else {
+          setWidth(preset.width);
+          setHeight(preset.height);
+        }
+      });
+
+      const unlistenHotkeys = await window.listen<{ outcome: ResizeOutcome | null; error: unknown }>("hotkey_action", (event) => {
+        const { outcome, error } = event.payload;
+        if (error) {
+          setMessage(`❌ ${errorMessage(error)}`);
+          setTimeout(() => setMessage(""), 5000);
+        } else if (outcome) {
+          setWidth(outcome.requested.width);
+          setHeight(outcome.requested.height);
+        }
+      });
+
+      // Keeps the window list current as windows open, close and move
+      const unlistenWindows = await window.listen<{ windows: WindowInfo[] }>("windows_changed", (event) => {
+        setWindows(event.payload.windows);
+      });
+
+      type RuleResult = RuleMatch & { dry_run: boolean; outcome: ResizeOutcome | null; error: unknown };
+
+      const unlistenRules = await window.listen<RuleResult>("rule_matched", (event) => {
+        const { rule, window: matched, dry_run, error } = event.payload;
+        if (error) {
+          setMessage(`❌ Rule "${rule}": ${errorMessage(error)}`);
+        } else {
+          setMessage(`${dry_run ? "🔍 Would apply" : "✅ Applied"} "${rule}" to ${matched.app_name}`);
+        }
+        setTimeout(() => setMessage(""), 5000);
+      });
+
+      // The backend applies size links, like presets from the tray
+      type DeepLinkEvent =
+        | { kind: "resized"; outcome: ResizeOutcome }
+        | { kind: "error"; url: string; message: string };
+
+      const unlistenDeepLinks = await window.listen<DeepLinkEvent>("deep_link", (event) => {
+        const link = event.payload;
+        if (link.kind === "error") {
+          setMessage(`❌ ${link.message}`);
+          setTimeout(() => setMessage(""), 5000);
+          return;
         }
+
+        setWidth(link.outcome.requested.width);
+        setHeight(link.outcome.requested.height);
       });
 
-      return unlistenTray;
+      // Links that opened the app were held back until now
+      await invoke("deep_links_ready");
+
+      return () => {
+        unlistenPresets();
+        unlistenHotkeys();
+        unlistenDeepLinks();
+        unlistenRules();
+        unlistenWindows();
+      };
     };
 
     setupListeners().catch(err => console.error("Failed to setup listeners:", err));
@@ -137,8 +283,8 @@ function AppContent() {
             <span className="tile__value">{windows.length}</span>
           </div>
           <div className="tile">
-            <span className="tile__label">Center</span>
-            <span className="tile__value">{centerWindow ? '✓' : '✗'}</span>
+            <span className="tile__label">Placement</span>
+            <span className="tile__value">{placementLabel(placement)}</span>
           </div>
         </div>
 
@@ -153,6 +299,10 @@ function AppContent() {
                 <button onClick={() => { exportPresets(); setShowMenu(false); }}>Export Presets</button>
                 <button onClick={() => { importPresetsHandler(); setShowMenu(false); }}>Import Presets</button>
                 <button onClick={() => { resetPresets(); setShowMenu(false); }}>Reset to Defaults</button>
+                <button onClick={() => { setShowHotkeys(!showHotkeys); setShowMenu(false); }}>Hotkeys</button>
+                <button onClick={() => { setShowLayouts(!showLayouts); setShowMenu(false); }}>Layouts</button>
+                <button onClick={() => { setShowRules(!showRules); setShowMenu(false); }}>Rules</button>
+                <button onClick={() => { setShowDevices(!showDevices); setShowMenu(false); }}>Devices</button>
               </div>
             )}
           </div>
@@ -161,24 +311,56 @@ function AppContent() {
 
       <div className="wrapper">
         <div className="presets">
-          {presets.map((size, index) => (
-            <div key={index} className="preset-wrapper">
+          {presets.map((size) => (
+            <div
+              key={size.id}
+              className="preset-wrapper"
+              // Custom presets can be dragged onto each other to reorder them
+              draggable={!size.builtin}
+              onDragStart={(e) => e.dataTransfer.setData("text/plain", size.id)}
+              onDragOver={(e) => {
+                if (!size.builtin) {
+                  e.preventDefault();
+                }
+              }}
+              onDrop={(e) => {
+                e.preventDefault();
+                movePreset(e.dataTransfer.getData("text/plain"), size.id);
+              }}
+            >
               <button
                 onClick={() => {
                   setWidth(size.width);
                   setHeight(size.height);
+                  if (size.placement) {
+                    setPlacement(size.placement);
+                  }
                 }}
-                className={`preset-btn ${width === size.width && height === size.height ? 'active' : ''}`}
+                className={`preset-btn ${(width === size.width && height === size.height) || (width === size.height && height === size.width) ? 'active' : ''}`}
+                title={[size.category, size.notes].filter(Boolean).join(" · ") || undefined}
               >
                 <div className="preset-name">{size.name}</div>
                 <div className="preset-size">{size.width} × {size.height}</div>
               </button>
-              {index >= DEFAULT_PRESETS.length && (
+              <button
+                className="rotate-btn"
+                onClick={(e) => {
+                  e.stopPropagation();
+                  // Portrait to landscape and back
+                  const rotated = width === size.width && height === size.height;
+                  setWidth(rotated ? size.height : size.width);
+                  setHeight(rotated ? size.width : size.height);
+                }}
+                title="Rotate"
+              >
+                ↻
+              </button>
+              {!size.builtin && (
                 <button
                   className="delete-btn"
                   onClick={(e) => {
                     e.stopPropagation();
-                    deletePreset(index);
+                    deletePreset(size.id);
                   }}
                   title="Delete preset"
                 >
@@ -220,6 +402,207 @@ function AppContent() {
           </div>
         )}
 
+        {showHotkeys && (
+          <div className="hotkeys">
+            {hotkeys.map((binding, index) => (
+              <div key={index} className="hotkey-row">
+                <input
+                  type="text"
+                  placeholder="Press keys…"
+                  value={binding.shortcut}
+                  readOnly
+                  onKeyDown={(e) => {
+                    e.preventDefault();
+                    const shortcut = shortcutFromEvent(e);
+                    if (shortcut) {
+                      updateHotkey(index, { ...binding, shortcut });
+                    }
+                  }}
+                />
+                <select
+                  value={actionValue(binding)}
+                  onChange={(e) => updateHotkey(index, { shortcut: binding.shortcut, ...actionFromValue(e.target.value) })}
+                >
+                  {presets.map((preset) => (
+                    <option key={preset.id} value={`preset:${preset.id}`}>{preset.name}</option>
+                  ))}
+                  <option value="cycle-presets">Cycle presets</option>
+                  <option value="center-frontmost">Center window</option>
+                  <option value="undo-last-resize">Undo last resize</option>
+                  <option value="redo-resize">Redo resize</option>
+                  <option value="restore-original">Restore original size</option>
+                </select>
+                <button
+                  className="delete-btn"
+                  onClick={() => setHotkeys(hotkeys.filter((_, i) => i !== index))}
+                  title="Remove hotkey"
+                >
+                  ×
+                </button>
+              </div>
+            ))}
+            <div className="form-actions">
+              <button
+                onClick={() => setHotkeys([...hotkeys, { shortcut: "", action: "cycle-presets" }])}
+                className="btn btn-secondary btn-sm"
+              >
+                Add Hotkey
+              </button>
+              <button
+                onClick={async () => {
+                  if (await saveHotkeys(hotkeys)) {
+                    setShowHotkeys(false);
+                  }
+                }}
+                className="btn btn-primary btn-sm"
+              >
+                Save
+              </button>
+              <button onClick={() => {
+                loadHotkeys();
+                setShowHotkeys(false);
+              }} className="btn btn-secondary btn-sm">
+                Cancel
+              </button>
+            </div>
+          </div>
+        )}
+
+        {showLayouts && (
+          <div className="layouts">
+            {layouts.map((layout) => (
+              <div key={layout.name} className="layout-row">
+                <span className="layout-name">
+                  {layout.name} · {layout.windows.length} windows
+                </span>
+                <button onClick={() => restoreLayout(layout.name)} disabled={loading} className="btn btn-primary btn-sm">
+                  Restore
+                </button>
+                <button className="delete-btn" onClick={() => deleteLayout(layout.name)} title="Delete layout">
+                  ×
+                </button>
+              </div>
+            ))}
+            <div className="layout-row">
+              <input
+                type="text"
+                placeholder="Layout name"
+                value={newLayoutName}
+                onChange={(e) => setNewLayoutName(e.target.value)}
+              />
+              <button
+                onClick={async () => {
+                  await captureLayout(newLayoutName);
+                  setNewLayoutName("");
+                }}
+                className="btn btn-secondary btn-sm"
+              >
+                Save Open Windows
+              </button>
+            </div>
+          </div>
+        )}
+
+        {showRules && (
+          <div className="rules">
+            {rules.rules.length === 0 && (
+              <span className="layout-name">No rules yet, add them to presets.json</span>
+            )}
+            {rules.rules.map((rule, index) => (
+              <div key={rule.name} className="layout-row">
+                <input
+                  type="checkbox"
+                  checked={rule.enabled}
+                  onChange={(e) => setRules({
+                    ...rules,
+                    rules: rules.rules.map((existing, i) => (i === index ? { ...existing, enabled: e.target.checked } : existing)),
+                  })}
+                />
+                <span className="layout-name">
+                  {rule.name} · priority {rule.priority}
+                </span>
+                <button
+                  className="delete-btn"
+                  onClick={() => setRules({ ...rules, rules: rules.rules.filter((_, i) => i !== index) })}
+                  title="Delete rule"
+                >
+                  ×
+                </button>
+              </div>
+            ))}
+            <label className="layout-row">
+              <input
+                type="checkbox"
+                checked={rules.dryRun}
+                onChange={(e) => setRules({ ...rules, dryRun: e.target.checked })}
+              />
+              <span className="layout-name">Dry run: only report matches</span>
+            </label>
+            <div className="layout-row">
+              <button onClick={() => dryRunRules(rules)} className="btn btn-secondary btn-sm">
+                Test on Open Windows
+              </button>
+              <button
+                onClick={async () => {
+                  if (await saveRules(rules)) {
+                    setShowRules(false);
+                  }
+                }}
+                className="btn btn-primary btn-sm"
+              >
+                Save
+              </button>
+              <button onClick={() => {
+                loadRules();
+                setShowRules(false);
+              }} className="btn btn-secondary btn-sm">
+                Cancel
+              </button>
+            </div>
+          </div>
+        )}
+
+        {showDevices && (
+          <div className="devices">
+            <div className="layout-row">
+              <input
+                type="text"
+                placeholder="Search devices, e.g. iPhone 15 or 9:16"
+                value={deviceQuery}
+                onChange={(e) => setDeviceQuery(e.target.value)}
+              />
+              <select
+                value={deviceCategory}
+                onChange={(e) => setDeviceCategory(e.target.value as DeviceCategory | "")}
+              >
+                {DEVICE_CATEGORIES.map((option) => (
+                  <option key={option.value} value={option.value}>{option.label}</option>
+                ))}
+              </select>
+            </div>
+            <div className="device-results">
+              {devices.length === 0 && <span className="layout-name">No matching devices</span>}
+              {devices.map((device) => (
+                <div key={device.id} className="layout-row">
+                  <button
+                    className="layout-name preset-btn"
+                    onClick={() => {
+                      setWidth(device.width);
+                      setHeight(device.height);
+                    }}
+                    title={`${device.manufacturer}${device.year ? ` · ${device.year}` : ""} · ${device.device_pixel_ratio}x`}
+                  >
+                    {device.manufacturer === "Generic" ? device.name : `${device.manufacturer} ${device.name}`} · {device.width} × {device.height}
+                  </button>
+                  <button onClick={() => addDevicePreset(device)} className="btn btn-secondary btn-sm" title="Add as preset">
+                    +
+                  </button>
+                </div>
+              ))}
+            </div>
+          </div>
+        )}
+
         <div className="custom-size">
           <div className="input-wrapper">
             <label>Width</label>
@@ -228,7 +611,7 @@ function AppContent() {
               value={width}
               onChange={(e) => setWidth(Number(e.target.value))}
               min="100"
-              max="5000"
+              max="8192"
             />
           </div>
           <div className="input-wrapper">
@@ -238,20 +621,68 @@ function AppContent() {
               value={height}
               onChange={(e) => setHeight(Number(e.target.value))}
               min="100"
-              max="5000"
+              max="8192"
             />
           </div>
         </div>
 
-        <div className="center-option">
-          <label className="checkbox-label">
-            <input
-              type="checkbox"
-              checked={centerWindow}
-              onChange={(e) => setCenterWindow(e.target.checked)}
-            />
-            <span>Center window after resize</span>
-          </label>
+        <div className="placement-option">
+          <div className="input-wrapper">
+            <label>Placement</label>
+            <select
+              value={placement.mode}
+              onChange={(e) => {
+                const mode = e.target.value as Anchor | "absolute";
+                setPlacement(
+                  mode === "absolute"
+                    ? { mode, x: 0, y: 0, margin: placement.margin }
+                    : { mode, margin: placement.margin }
+                );
+              }}
+            >
+              {PLACEMENT_OPTIONS.map((option) => (
+                <option key={option.value} value={option.value}>{option.label}</option>
+              ))}
+            </select>
+          </div>
+          {placement.mode === "absolute" && (
+            <>
+              <div className="input-wrapper">
+                <label>X</label>
+                <input
+                  type="number"
+                  value={placement.x}
+                  onChange={(e) => setPlacement({ ...placement, x: Number(e.target.value) })}
+                />
+              </div>
+              <div className="input-wrapper">
+                <label>Y</label>
+                <input
+                  type="number"
+                  value={placement.y}
+                  onChange={(e) => setPlacement({ ...placement, y: Number(e.target.value) })}
+                />
+              </div>
+            </>
+          )}
+          {placement.mode !== "keep" && (
+            <div className="input-wrapper">
+              <label>Margin</label>
+              <input
+                type="number"
+                value={placement.margin ?? 0}
+                onChange={(e) => setPlacement({ ...placement, margin: Number(e.target.value) })}
+                min="0"
+              />
+            </div>
+          )}
+          <div className="input-wrapper">
+            <label>Size of</label>
+            <select value={sizeMode} onChange={(e) => setSizeMode(e.target.value as SizeMode)}>
+              <option value="frame">Frame</option>
+              <option value="content">Content (viewport)</option>
+            </select>
+          </div>
         </div>
 
         <div className="actions">
@@ -276,6 +707,23 @@ function AppContent() {
           </button>
         </div>
 
+        <div className="history-actions">
+          <button onClick={() => undoResize()} disabled={loading} className="btn btn-secondary btn-sm" title="Undo the last resize">
+            ↶ Undo
+          </button>
+          <button onClick={() => redoResize()} disabled={loading} className="btn btn-secondary btn-sm" title="Redo the last undone resize">
+            ↷ Redo
+          </button>
+          <button
+            onClick={() => restoreOriginalSize()}
+            disabled={loading}
+            className="btn btn-secondary btn-sm"
+            title="Return the last resized window to its size before FrameFit"
+          >
+            Restore Original
+          </button>
+        </div>
+
         {showWindowList && (
           <div className="window-list">
             <div className="window-list-header">
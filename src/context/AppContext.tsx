@@ -11,19 +11,91 @@ interface WindowInfo {
   height: number;
 }
 
-interface Preset {
-  name: string;
+export type Anchor =
+  | "keep"
+  | "top-left"
+  | "top"
+  | "top-right"
+  | "left"
+  | "center"
+  | "right"
+  | "bottom-left"
+  | "bottom"
+  | "bottom-right";
+
+// Mirrors `Placement` in the backend: where the window goes after resizing
+export type Placement =
+  | { mode: Anchor; margin?: number }
+  | { mode: "absolute"; x: number; y: number; margin?: number };
+
+export const DEFAULT_PLACEMENT: Placement = { mode: "center" };
+
+// Whether a size applies to the whole window or only to its content/viewport
+export type SizeMode = "frame" | "content";
+
+// Mirrors `HotkeyBinding` in the backend
+export type HotkeyAction =
+  | { action: "preset"; id: string }
+  | { action: "cycle-presets" }
+  | { action: "center-frontmost" }
+  | { action: "undo-last-resize" }
+  | { action: "redo-resize" }
+  | { action: "restore-original" };
+
+export type HotkeyBinding = { shortcut: string } & HotkeyAction;
+
+// Mirrors `Layout` in the backend; positions are relative to the display's work area
+export interface LayoutWindow {
+  app_name: string;
+  title_pattern?: string;
+  x: number;
+  y: number;
   width: number;
   height: number;
+  display?: string;
+}
+
+export interface Layout {
+  name: string;
+  windows: LayoutWindow[];
+}
+
+// Mirrors `Rule` in the backend; every condition that is set has to hold
+export interface RuleMatcher {
+  app_name?: string;
+  title_pattern?: string;
+  min_size?: { width: number; height: number };
+  max_size?: { width: number; height: number };
+  display?: number | string;
+}
+
+export interface Rule {
+  name: string;
+  enabled: boolean;
+  priority: number;
+  match: RuleMatcher;
+  // `preset` is a preset id
+  action: { preset?: string; placement?: Placement };
 }
 
-export const DEFAULT_PRESETS: Preset[] = [
-  { name: "iPhone SE", width: 375, height: 667 },
-  { name: "iPhone 14", width: 390, height: 844 },
-  { name: "iPad", width: 768, height: 1024 },
-  { name: "HD", width: 1280, height: 720 },
-  { name: "FHD", width: 1920, height: 1080 },
-];
+export interface RulesConfig {
+  dryRun: boolean;
+  rules: Rule[];
+}
+
+// Mirrors `Preset` in the backend, which also owns the built-in presets
+export interface Preset {
+  id: string;
+  name: string;
+  width: number;
+  height: number;
+  category?: string;
+  device_pixel_ratio: number;
+  tags?: string[];
+  notes?: string;
+  placement?: Placement;
+  builtin?: boolean;
+}
 
 interface AppContextType {
   // Window management
@@ -58,9 +130,33 @@ interface AppContextType {
   newPresetName: string;
   setNewPresetName: (name: string) => void;
 
+  // Hotkeys
+  hotkeys: HotkeyBinding[];
+  setHotkeys: (hotkeys: HotkeyBinding[]) => void;
+  showHotkeys: boolean;
+  setShowHotkeys: (show: boolean) => void;
+
+  // Layouts
+  layouts: Layout[];
+  setLayouts: (layouts: Layout[]) => void;
+  showLayouts: boolean;
+  setShowLayouts: (show: boolean) => void;
+
+  // Rules
+  rules: RulesConfig;
+  setRules: (rules: RulesConfig) => void;
+  showRules: boolean;
+  setShowRules: (show: boolean) => void;
+
+  // Device catalog
+  showDevices: boolean;
+  setShowDevices: (show: boolean) => void;
+
   // Options
-  centerWindow: boolean;
-  setCenterWindow: (center: boolean) => void;
+  placement: Placement;
+  setPlacement: (placement: Placement) => void;
+  sizeMode: SizeMode;
+  setSizeMode: (sizeMode: SizeMode) => void;
 
   // Store
   store: Store | null;
@@ -81,9 +177,17 @@ export function AppProvider({ children }: { children: ReactNode }) {
   const [showAddPreset, setShowAddPreset] = useState<boolean>(false);
   const [newPresetName, setNewPresetName] = useState<string>("");
   const [showMenu, setShowMenu] = useState<boolean>(false);
-  const [centerWindow, setCenterWindow] = useState<boolean>(true);
+  const [hotkeys, setHotkeys] = useState<HotkeyBinding[]>([]);
+  const [showHotkeys, setShowHotkeys] = useState<boolean>(false);
+  const [layouts, setLayouts] = useState<Layout[]>([]);
+  const [showLayouts, setShowLayouts] = useState<boolean>(false);
+  const [rules, setRules] = useState<RulesConfig>({ dryRun: false, rules: [] });
+  const [showRules, setShowRules] = useState<boolean>(false);
+  const [showDevices, setShowDevices] = useState<boolean>(false);
+  const [placement, setPlacement] = useState<Placement>(DEFAULT_PLACEMENT);
+  const [sizeMode, setSizeMode] = useState<SizeMode>("frame");
   const [store, setStore] = useState<Store | null>(null);
-  const [presets, setPresets] = useState<Preset[]>(DEFAULT_PRESETS);
+  const [presets, setPresets] = useState<Preset[]>([]);
 
   const value: AppContextType = {
     windows,
@@ -110,8 +214,24 @@ export function AppProvider({ children }: { children: ReactNode }) {
     setShowAddPreset,
     newPresetName,
     setNewPresetName,
-    centerWindow,
-    setCenterWindow,
+    hotkeys,
+    setHotkeys,
+    showHotkeys,
+    setShowHotkeys,
+    layouts,
+    setLayouts,
+    showLayouts,
+    setShowLayouts,
+    rules,
+    setRules,
+    showRules,
+    setShowRules,
+    showDevices,
+    setShowDevices,
+    placement,
+    setPlacement,
+    sizeMode,
+    setSizeMode,
     store,
     setStore,
   };
@@ -0,0 +1,60 @@
+import { useApp, type Preset } from "../context/AppContext";
+import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "./useWindowOperations";
+import { usePresetManagement } from "./usePresetManagement";
+
+export type DeviceCategory = "phone" | "tablet" | "laptop" | "desktop" | "tv" | "social";
+
+// Mirrors `Device` in the backend; sizes are CSS viewports
+export interface Device {
+  id: string;
+  name: string;
+  manufacturer: string;
+  category: DeviceCategory;
+  year?: number;
+  width: number;
+  height: number;
+  device_pixel_ratio: number;
+}
+
+// Mirrors `DeviceFilters` in the backend
+export interface DeviceFilters {
+  categories?: DeviceCategory[];
+  manufacturer?: string;
+  min_year?: number;
+  max_year?: number;
+  limit?: number;
+}
+
+export function useDevices() {
+  const { setMessage } = useApp();
+  const { savePreset } = usePresetManagement();
+
+  async function searchDevices(query: string, filters: DeviceFilters = {}): Promise<Device[]> {
+    try {
+      return await invoke<Device[]>("search_devices", { query, filters });
+    } catch (error) {
+      console.error("Failed to search devices:", error);
+      return [];
+    }
+  }
+
+  async function addDevicePreset(device: Device) {
+    try {
+      // Ids come from the device, so adding it twice is refused by the backend
+      const preset = await invoke<Preset>("device_preset", { id: device.id });
+      if (await savePreset(preset)) {
+        setMessage(`✅ Added ${preset.name}`);
+        setTimeout(() => setMessage(""), 2000);
+      }
+    } catch (error) {
+      setMessage(`❌ ${errorMessage(error)}`);
+      setTimeout(() => setMessage(""), 2000);
+    }
+  }
+
+  return {
+    searchDevices,
+    addDevicePreset,
+  };
+}
@@ -0,0 +1,98 @@
+import { useApp, type HotkeyAction, type HotkeyBinding } from "../context/AppContext";
+import { invoke } from "@tauri-apps/api/core";
+import type { KeyboardEvent as ReactKeyboardEvent } from "react";
+import { errorMessage } from "./useWindowOperations";
+
+// Mirrors `HotkeyConflict` in the backend
+export type HotkeyConflict =
+  | { kind: "invalid"; shortcut: string; message: string }
+  | { kind: "duplicate"; shortcut: string; actions: HotkeyAction[] }
+  | { kind: "unknown-preset"; shortcut: string; id: string }
+  | { kind: "unavailable"; shortcut: string; message: string };
+
+export function describeConflict(conflict: HotkeyConflict): string {
+  switch (conflict.kind) {
+    case "invalid":
+      return `${conflict.shortcut} is not a valid shortcut`;
+    case "duplicate":
+      return `${conflict.shortcut} is bound ${conflict.actions.length} times`;
+    case "unknown-preset":
+      return `${conflict.shortcut} uses a preset that no longer exists`;
+    case "unavailable":
+      return `${conflict.shortcut} is already in use by another app`;
+  }
+}
+
+const MODIFIER_KEYS = ["Meta", "Control", "Alt", "Shift"];
+
+// Turns a key press into the shortcut format of the backend, e.g. "Alt+Shift+Digit1"
+export function shortcutFromEvent(event: KeyboardEvent | ReactKeyboardEvent): string | null {
+  if (MODIFIER_KEYS.includes(event.key)) {
+    return null;
+  }
+
+  const parts: string[] = [];
+  if (event.metaKey) parts.push("Super");
+  if (event.ctrlKey) parts.push("Control");
+  if (event.altKey) parts.push("Alt");
+  if (event.shiftKey) parts.push("Shift");
+  parts.push(event.code);
+  return parts.join("+");
+}
+
+export function useHotkeys() {
+  const { setHotkeys, setMessage } = useApp();
+
+  function showConflicts(conflicts: HotkeyConflict[]) {
+    setMessage(`⚠️ ${conflicts.map(describeConflict).join(". ")}`);
+    setTimeout(() => setMessage(""), 5000);
+  }
+
+  async function loadHotkeys() {
+    try {
+      setHotkeys(await invoke<HotkeyBinding[]>("get_hotkeys"));
+    } catch (error) {
+      console.error("Failed to load hotkeys:", error);
+    }
+  }
+
+  async function saveHotkeys(bindings: HotkeyBinding[]): Promise<boolean> {
+    try {
+      const conflicts = await invoke<HotkeyConflict[]>("set_hotkeys", { bindings });
+      if (conflicts.some((conflict) => conflict.kind !== "unavailable")) {
+        // Nothing was saved
+        showConflicts(conflicts);
+        return false;
+      }
+
+      setHotkeys(bindings);
+      if (conflicts.length > 0) {
+        showConflicts(conflicts);
+      } else {
+        setMessage("✅ Hotkeys saved!");
+        setTimeout(() => setMessage(""), 2000);
+      }
+      return true;
+    } catch (error) {
+      setMessage(`❌ ${errorMessage(error)}`);
+      return false;
+    }
+  }
+
+  async function reloadHotkeys() {
+    try {
+      const conflicts = await invoke<HotkeyConflict[]>("reload_hotkeys");
+      if (conflicts.length > 0) {
+        showConflicts(conflicts);
+      }
+    } catch (error) {
+      console.error("Failed to reload hotkeys:", error);
+    }
+  }
+
+  return {
+    loadHotkeys,
+    saveHotkeys,
+    reloadHotkeys,
+  };
+}
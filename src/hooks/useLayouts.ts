@@ -0,0 +1,73 @@
+import { useApp, type Layout } from "../context/AppContext";
+import { invoke } from "@tauri-apps/api/core";
+import { errorMessage, type FrameFitError, type ResizeOutcome } from "./useWindowOperations";
+
+// Mirrors `LayoutOutcome` in the backend
+interface LayoutOutcome {
+  restored: ResizeOutcome[];
+  skipped: { app_name: string; title_pattern: string | null; error: FrameFitError }[];
+}
+
+export function useLayouts() {
+  const { layouts, setLayouts, setMessage, setLoading } = useApp();
+
+  function showMessage(message: string, timeout = 3000) {
+    setMessage(message);
+    setTimeout(() => setMessage(""), timeout);
+  }
+
+  async function loadLayouts() {
+    try {
+      setLayouts(await invoke<Layout[]>("get_layouts"));
+    } catch (error) {
+      console.error("Failed to load layouts:", error);
+    }
+  }
+
+  async function captureLayout(name: string, windowIds?: number[]) {
+    if (!name.trim()) {
+      showMessage("⚠️ Please enter a layout name");
+      return;
+    }
+
+    try {
+      const layout = await invoke<Layout>("capture_layout", { name, windowIds });
+      setLayouts([...layouts.filter((existing) => existing.name.toLowerCase() !== layout.name.toLowerCase()), layout]);
+      showMessage(`✅ Saved ${layout.windows.length} windows as "${layout.name}"`);
+    } catch (error) {
+      showMessage(`❌ ${errorMessage(error)}`, 5000);
+    }
+  }
+
+  async function restoreLayout(name: string) {
+    setLoading(true);
+    try {
+      const outcome = await invoke<LayoutOutcome>("restore_layout", { name });
+      if (outcome.skipped.length > 0) {
+        const skipped = outcome.skipped.map((window) => window.app_name).join(", ");
+        showMessage(`⚠️ Restored ${outcome.restored.length} windows, skipped ${skipped}`, 5000);
+      } else {
+        showMessage(`✅ Restored "${name}"`);
+      }
+    } catch (error) {
+      showMessage(`❌ ${errorMessage(error)}`, 5000);
+    } finally {
+      setLoading(false);
+    }
+  }
+
+  async function deleteLayout(name: string) {
+    try {
+      setLayouts(await invoke<Layout[]>("delete_layout", { name }));
+    } catch (error) {
+      showMessage(`❌ ${errorMessage(error)}`, 5000);
+    }
+  }
+
+  return {
+    loadLayouts,
+    captureLayout,
+    restoreLayout,
+    deleteLayout,
+  };
+}
@@ -1,19 +1,18 @@
-import { useApp } from "../context/AppContext";
-import { DEFAULT_PRESETS } from "../context/AppContext";
-import { Store } from "@tauri-apps/plugin-store";
-import { writeTextFile } from "@tauri-apps/plugin-fs";
+import { useApp, type Preset } from "../context/AppContext";
 import { save } from "@tauri-apps/plugin-dialog";
 import { invoke } from "@tauri-apps/api/core";
-
-interface Preset {
-  name: string;
-  width: number;
-  height: number;
+import { errorMessage } from "./useWindowOperations";
+
+// Mirrors `ImportReport` in the backend
+export interface ImportReport {
+  format: "framefit" | "chrome-devtools" | "playwright" | "responsively" | "csv";
+  presets: Preset[];
+  rejected: { entry: string; reason: string }[];
+  duplicates: string[];
 }
 
 export function usePresetManagement() {
   const {
-    store,
     presets,
     setPresets,
     newPresetName,
@@ -22,98 +21,74 @@ export function usePresetManagement() {
     setMessage,
   } = useApp();
 
-  async function loadPresets(storeInstance?: Store) {
-    const storeToUse = storeInstance || store;
-    if (!storeToUse) return;
+  function showMessage(message: string, timeout = 2000) {
+    setMessage(message);
+    setTimeout(() => setMessage(""), timeout);
+  }
 
+  async function loadPresets() {
     try {
-      const saved = await storeToUse.get<Preset[]>("customPresets");
-
-      if (saved && Array.isArray(saved) && saved.length > 0) {
-        setPresets([...DEFAULT_PRESETS, ...saved]);
-      } else {
-        setPresets(DEFAULT_PRESETS);
-
-        if (saved === null || saved === undefined) {
-          await storeToUse.set("customPresets", []);
-          await storeToUse.save();
-        }
-      }
+      setPresets(await invoke<Preset[]>("list_presets"));
     } catch (error) {
       console.error("Failed to load presets:", error);
-      setPresets(DEFAULT_PRESETS);
-
-      try {
-        await storeToUse.set("customPresets", []);
-        await storeToUse.save();
-      } catch (initError) {
-        console.error("Failed to initialize store:", initError);
-      }
     }
   }
 
-  async function saveCustomPresets(customPresets: Preset[]) {
-    if (!store) {
-      console.error("Store not initialized");
-      return;
-    }
+  // The backend validates and saves presets, then rebuilds the tray menu;
+  // every change returns all presets
+  async function changePresets(command: string, args: Record<string, unknown>): Promise<boolean> {
     try {
-      await store.set("customPresets", customPresets);
-      await store.save();
-      setPresets([...DEFAULT_PRESETS, ...customPresets]);
-
-      // Rebuild the tray menu with updated presets
-      try {
-        await invoke("rebuild_tray_menu", { customPresets });
-      } catch (error) {
-        console.error("Failed to rebuild tray menu:", error);
-      }
+      setPresets(await invoke<Preset[]>(command, args));
+      return true;
     } catch (error) {
-      console.error("Failed to save presets:", error);
-      setMessage("❌ Failed to save presets");
+      showMessage(`❌ ${errorMessage(error)}`, 4000);
+      return false;
     }
   }
 
+  // Presets without an id get a new one
+  async function savePreset(preset: Omit<Preset, "id"> & { id?: string }): Promise<boolean> {
+    return changePresets("add_preset", { preset });
+  }
+
   async function addPreset(width: number, height: number) {
     if (!newPresetName.trim()) {
       setMessage("Please enter a preset name");
       return;
     }
 
-    const newPreset: Preset = {
-      name: newPresetName.trim(),
-      width,
-      height,
-    };
+    if (await savePreset({ name: newPresetName.trim(), width, height, device_pixel_ratio: 1 })) {
+      setNewPresetName("");
+      setShowAddPreset(false);
+      showMessage("✅ Preset saved!");
+    }
+  }
 
-    const customPresets = presets.slice(DEFAULT_PRESETS.length);
-    const updated = [...customPresets, newPreset];
+  async function updatePreset(preset: Preset) {
+    return changePresets("update_preset", { preset });
+  }
 
-    await saveCustomPresets(updated);
-    setNewPresetName("");
-    setShowAddPreset(false);
-    setMessage("✅ Preset saved!");
-    setTimeout(() => setMessage(""), 2000);
+  async function deletePreset(id: string) {
+    if (await changePresets("delete_preset", { id })) {
+      showMessage("✅ Preset deleted!");
+    }
   }
 
-  async function deletePreset(index: number) {
-    if (index < DEFAULT_PRESETS.length) {
+  // Moves a custom preset to where another one is
+  async function movePreset(id: string, targetId: string) {
+    const ids = presets.filter((preset) => !preset.builtin).map((preset) => preset.id);
+    const from = ids.indexOf(id);
+    const to = ids.indexOf(targetId);
+    if (from === -1 || to === -1 || from === to) {
       return;
     }
-    const customPresets = presets.slice(DEFAULT_PRESETS.length);
-    const customIndex = index - DEFAULT_PRESETS.length;
-    const updated = customPresets.filter(
-      (_: Preset, i: number) => i !== customIndex
-    );
-    await saveCustomPresets(updated);
-    setMessage("✅ Preset deleted!");
-    setTimeout(() => setMessage(""), 2000);
+    ids.splice(to, 0, ...ids.splice(from, 1));
+    await changePresets("reorder_presets", { ids });
   }
 
   async function exportPresets() {
     try {
-      const customPresets = presets.slice(DEFAULT_PRESETS.length);
-      const filePath = await save({
+      const path = await save({
         defaultPath: "framefit-presets.json",
         filters: [
           {
@@ -123,10 +98,9 @@ export function usePresetManagement() {
         ],
       });
 
-      if (filePath) {
-        await writeTextFile(filePath, JSON.stringify(customPresets, null, 2));
-        setMessage("✅ Presets exported!");
-        setTimeout(() => setMessage(""), 2000);
+      if (path) {
+        await invoke("export_presets", { path });
+        showMessage("✅ Presets exported!");
       }
     } catch (error) {
       console.error("Failed to export presets:", error);
@@ -136,22 +110,23 @@ export function usePresetManagement() {
 
   async function importPresets(file: File) {
     try {
-      const text = await file.text();
-      const imported = JSON.parse(text) as Preset[];
-
-      if (
-        Array.isArray(imported) &&
-        imported.every((p) => p.name && p.width && p.height)
-      ) {
-        await saveCustomPresets(imported);
-        setMessage("✅ Presets imported!");
-        setTimeout(() => setMessage(""), 2000);
+      // The backend detects FrameFit, DevTools, Playwright, Responsively and CSV files
+      const report = await invoke<ImportReport>("import_presets", { text: await file.text() });
+      await loadPresets();
+
+      const skipped = [
+        report.duplicates.length > 0 ? `${report.duplicates.length} duplicates skipped` : "",
+        ...report.rejected.map((rejected) => `${rejected.entry}: ${rejected.reason}`),
+      ].filter(Boolean);
+      const imported = `Imported ${report.presets.length} presets`;
+      if (skipped.length > 0) {
+        showMessage(`⚠️ ${imported}; ${skipped.join("; ")}`, 5000);
       } else {
-        setMessage("❌ Invalid preset file");
+        showMessage(`✅ ${imported}!`);
       }
     } catch (error) {
       console.error("Failed to import presets:", error);
-      setMessage("❌ Failed to import presets");
+      setMessage(`❌ Invalid preset file: ${errorMessage(error)}`);
     }
   }
 
@@ -159,9 +134,9 @@ export function usePresetManagement() {
     if (
       confirm("Reset to default presets? This will delete all custom presets.")
     ) {
-      await saveCustomPresets([]);
-      setMessage("✅ Presets reset!");
-      setTimeout(() => setMessage(""), 2000);
+      if (await changePresets("reset_presets", {})) {
+        showMessage("✅ Presets reset!");
+      }
     }
   }
 
@@ -169,7 +144,7 @@ export function usePresetManagement() {
     try {
       const input = document.createElement("input");
       input.type = "file";
-      input.accept = ".json";
+      input.accept = ".json,.csv";
       input.onchange = async (e) => {
         const file = (e.target as HTMLInputElement).files?.[0];
         if (file) {
@@ -185,9 +160,11 @@ export function usePresetManagement() {
 
   return {
     loadPresets,
-    saveCustomPresets,
+    savePreset,
     addPreset,
+    updatePreset,
     deletePreset,
+    movePreset,
     exportPresets,
     importPresetsHandler,
     resetPresets,
@@ -0,0 +1,64 @@
+import { useApp, type RulesConfig } from "../context/AppContext";
+import { invoke } from "@tauri-apps/api/core";
+import { errorMessage, type WindowInfo } from "./useWindowOperations";
+
+// Mirrors `RuleMatch` in the backend
+export interface RuleMatch {
+  rule: string;
+  window: WindowInfo;
+  size: { width: number; height: number };
+}
+
+export function useRules() {
+  const { setRules, setMessage } = useApp();
+
+  function showMessage(message: string, timeout = 3000) {
+    setMessage(message);
+    setTimeout(() => setMessage(""), timeout);
+  }
+
+  async function loadRules() {
+    try {
+      setRules(await invoke<RulesConfig>("get_rules"));
+    } catch (error) {
+      console.error("Failed to load rules:", error);
+    }
+  }
+
+  async function saveRules(config: RulesConfig): Promise<boolean> {
+    try {
+      const problems = await invoke<string[]>("set_rules", { config });
+      if (problems.length > 0) {
+        showMessage(`⚠️ ${problems.join("; ")}`, 5000);
+        return false;
+      }
+      setRules(config);
+      showMessage("✅ Rules saved");
+      return true;
+    } catch (error) {
+      showMessage(`❌ ${errorMessage(error)}`, 5000);
+      return false;
+    }
+  }
+
+  // Reports what the rules would do to the open windows, without resizing
+  async function dryRunRules(config: RulesConfig) {
+    try {
+      const matches = await invoke<RuleMatch[]>("dry_run_rules", { rules: config.rules });
+      if (matches.length === 0) {
+        showMessage("No open windows match the rules");
+      } else {
+        const described = matches.map((match) => `${match.rule} → ${match.window.app_name}`).join(", ");
+        showMessage(`🔍 ${described}`, 5000);
+      }
+    } catch (error) {
+      showMessage(`❌ ${errorMessage(error)}`, 5000);
+    }
+  }
+
+  return {
+    loadRules,
+    saveRules,
+    dryRunRules,
+  };
+}
@@ -1,7 +1,7 @@
-import { useApp } from "../context/AppContext";
+import { useApp, type Placement, type SizeMode } from "../context/AppContext";
 import { invoke } from "@tauri-apps/api/core";
 
-interface WindowInfo {
+export interface WindowInfo {
   id: number;
   title: string;
   app_name: string;
@@ -11,8 +11,119 @@ interface WindowInfo {
   height: number;
 }
 
+interface Size {
+  width: number;
+  height: number;
+}
+
+interface Rect {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+interface Insets {
+  top: number;
+  left: number;
+  bottom: number;
+  right: number;
+}
+
+type SizeMismatch = "min-size" | "max-size" | "increments" | "other";
+
+const MISMATCH_LABELS: Record<SizeMismatch, string> = {
+  "min-size": "minimum size",
+  "max-size": "maximum size",
+  increments: "size increments",
+  other: "app constraint",
+};
+
+export interface ResizeOutcome {
+  window_id: number;
+  previous: Rect;
+  frame: Rect;
+  size_mode: SizeMode;
+  requested: Size;
+  applied: Size;
+  achieved: Size;
+  mismatch: SizeMismatch | null;
+  corrections: number;
+  insets: Insets | null;
+  warnings: string[];
+}
+
+// Mirrors the serialized `FrameFitError` from the backend
+export interface FrameFitError {
+  code:
+    | "PERMISSION_DENIED"
+    | "WINDOW_NOT_FOUND"
+    | "NO_MATCHING_WINDOW"
+    | "DISPLAY_NOT_FOUND"
+    | "EXCEEDS_WORK_AREA"
+    | "NO_WINDOWS"
+    | "SELF_RESIZE_REFUSED"
+    | "BACKEND_UNAVAILABLE"
+    | "SCRIPT_FAILED"
+    | "BACKEND_FAILED"
+    | "UNSUPPORTED"
+    | "INVALID_PRESET"
+    | "PRESET_NOT_FOUND";
+  message: string;
+  // A window id, or a preset id for PRESET_NOT_FOUND
+  id?: number | string;
+  query?: string;
+  display?: string;
+  max_width?: number;
+  max_height?: number;
+  reason?: string;
+  stderr?: string;
+  exit_code?: number | null;
+}
+
+// Mirrors `HistoryEntry` in the backend
+interface HistoryEntry {
+  window_id: number;
+  before: Rect;
+  after: Rect;
+}
+
+export function isFrameFitError(error: unknown): error is FrameFitError {
+  return typeof error === "object" && error !== null && "code" in error && "message" in error;
+}
+
+export function errorMessage(error: unknown): string {
+  return isFrameFitError(error) ? error.message : String(error);
+}
+
 export function useWindowOperations() {
-  const { width, height, centerWindow, setLoading, setMessage } = useApp();
+  const { width, height, placement, sizeMode, setLoading, setMessage, setHasPermissions } = useApp();
+
+  function showError(error: unknown) {
+    // Only a denied permission should bring up the permission prompt
+    if (isFrameFitError(error) && error.code === "PERMISSION_DENIED") {
+      setHasPermissions(false);
+    }
+
+    setMessage(`❌ ${errorMessage(error)}`);
+    setTimeout(() => setMessage(""), 10000);
+  }
+
+  function showOutcome(outcome: ResizeOutcome) {
+    const notes = [...outcome.warnings];
+    if (outcome.mismatch) {
+      const { width, height } = outcome.achieved;
+      notes.unshift(`Achieved ${width}×${height} (${MISMATCH_LABELS[outcome.mismatch]}).`);
+    }
+
+    if (notes.length > 0) {
+      setMessage(`⚠️ ${notes.join(" ")}`);
+      setTimeout(() => setMessage(""), 5000);
+    } else {
+      setMessage("✅ Resized!");
+      setTimeout(() => setMessage(""), 2000);
+    }
+  }
 
   async function checkPermissions(): Promise<boolean> {
     try {
@@ -31,7 +142,10 @@ export function useWindowOperations() {
       setMessage("");
       return result;
     } catch (error) {
-      setMessage(`Error: ${error}`);
+      if (isFrameFitError(error) && error.code === "PERMISSION_DENIED") {
+        setHasPermissions(false);
+      }
+      setMessage(`Error: ${errorMessage(error)}`);
       return [];
     } finally {
       setLoading(false);
@@ -40,20 +154,20 @@ export function useWindowOperations() {
 
   async function resizeFrontmostByDimensions(
     width: number,
-    height: number
+    height: number,
+    presetPlacement?: Placement
   ): Promise<void> {
     setLoading(true);
     try {
-      await invoke("resize_frontmost_window", {
+      const outcome = await invoke<ResizeOutcome>("resize_frontmost_window", {
         width,
         height,
-        center: centerWindow,
+        placement: presetPlacement ?? placement,
+        sizeMode,
       });
-      setMessage("✅ Resized!");
-      setTimeout(() => setMessage(""), 2000);
+      showOutcome(outcome);
     } catch (error) {
-      setMessage(`❌ ${error}`);
-      setTimeout(() => setMessage(""), 10000);
+      showError(error);
     } finally {
       setLoading(false);
     }
@@ -62,16 +176,15 @@ export function useWindowOperations() {
   async function resizeFrontmost(): Promise<void> {
     setLoading(true);
     try {
-      await invoke("resize_frontmost_window", {
+      const outcome = await invoke<ResizeOutcome>("resize_frontmost_window", {
         width,
         height,
-        center: centerWindow,
+        placement,
+        sizeMode,
       });
-      setMessage("✅ Resized!");
-      setTimeout(() => setMessage(""), 2000);
+      showOutcome(outcome);
     } catch (error) {
-      setMessage(`❌ ${error}`);
-      setTimeout(() => setMessage(""), 10000);
+      showError(error);
     } finally {
       setLoading(false);
     }
@@ -85,27 +198,55 @@ export function useWindowOperations() {
 
     setLoading(true);
     try {
-      await invoke("resize_specific_window", {
+      const outcome = await invoke<ResizeOutcome>("resize_specific_window", {
         windowId: selectedWindow,
         width,
         height,
-        center: centerWindow,
+        placement,
+        sizeMode,
       });
-      setMessage("✅ Resized!");
+      showOutcome(outcome);
+    } catch (error) {
+      showError(error);
+    } finally {
+      setLoading(false);
+    }
+  }
+
+  // Undo, redo and restore original share the reporting of their result
+  async function changeHistory(command: string, done: string, nothing: string): Promise<void> {
+    setLoading(true);
+    try {
+      const entry = await invoke<HistoryEntry | null>(command);
+      setMessage(entry ? `✅ ${done}` : `ℹ️ ${nothing}`);
       setTimeout(() => setMessage(""), 2000);
     } catch (error) {
-      setMessage(`❌ ${error}`);
-      setTimeout(() => setMessage(""), 10000);
+      showError(error);
     } finally {
       setLoading(false);
     }
   }
 
+  async function undoResize(): Promise<void> {
+    await changeHistory("undo_last_resize", "Undone", "Nothing to undo");
+  }
+
+  async function redoResize(): Promise<void> {
+    await changeHistory("redo_resize", "Redone", "Nothing to redo");
+  }
+
+  async function restoreOriginalSize(): Promise<void> {
+    await changeHistory("restore_original_size", "Restored original size", "No window was resized yet");
+  }
+
   return {
     checkPermissions,
     loadWindows,
     resizeFrontmost,
     resizeSelected,
     resizeFrontmostByDimensions,
+    undoResize,
+    redoResize,
+    restoreOriginalSize,
   };
 }